@@ -5,10 +5,10 @@ import re
 import os
 
 # This regex matches expressions of the form:
-RE = re.compile(r"!\[dcc_tiler_cli --single (.*?)\]\(img\/(.*?)\.svg\)")
+RE = re.compile(r"!\[dcc_tiler_cli single (.*?)\]\(img\/(.*?)\.svg\)")
 
 # This forms the base for the command that we eventually run
-command = "cargo run --release -- --single {} > img/{}.svg"
+command = "cargo run --release -- single {} > img/{}.svg"
 
 with open("README.md", "r") as readme:
     for line in readme.readlines():
@@ -0,0 +1,258 @@
+//! Proofs that boards can't be tiled.
+//!
+//! The classic proof that a chessboard with two opposite corners removed can't be tiled by
+//! dominoes colours the board like a chessboard: every domino covers one black and one white
+//! cell, but the board has two more black cells than white ones.  The same argument works
+//! with any integer weighting of the cells, provided the weights under every possible tile
+//! placement sum to a multiple of some modulus while the weights of the whole board don't.
+use crate::board::{Position, RectangularBoard};
+use crate::tile::TileCollection;
+use std::fmt;
+
+/// The moduli tried when searching for a colouring.  Working modulo a prime, the search is
+/// linear algebra over a field.
+const MODULI: [u64; 6] = [2, 3, 5, 7, 11, 13];
+
+/// A weighting of the cells of a board which proves that it can't be tiled: the weights
+/// of the cells covered by any tile placement sum to a multiple of the modulus, but the
+/// weights of all of the empty cells of the board don't.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coloring {
+    modulus: u64,
+    weights: Vec<Vec<Option<i64>>>,
+}
+
+impl Coloring {
+    /// The modulus of this colouring
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    /// The weight of the cell in the given row and column, or None if the cell is marked
+    pub fn weight(&self, row: usize, col: usize) -> Option<i64> {
+        self.weights[row][col]
+    }
+
+    /// The sum of the weights of all of the empty cells, modulo the modulus of this
+    /// colouring.  This is never zero.
+    pub fn total(&self) -> u64 {
+        let sum: i64 = self.weights.iter().flatten().flatten().sum();
+
+        sum.rem_euclid(self.modulus as i64) as u64
+    }
+
+    /// Checks that this colouring proves the given board can't be tiled by the given tiles
+    pub fn verify(&self, board: &RectangularBoard, tiles: &TileCollection) -> bool {
+        let m = self.modulus as i64;
+        let weight = |(row, col): (usize, usize)| {
+            self.weights
+                .get(row)
+                .and_then(|weights| weights.get(col).copied().flatten())
+        };
+
+        let cells_match = (0..board.height).all(|row| {
+            (0..board.width).all(|col| {
+                weight((row, col)).is_none() == board.is_marked(Position::from((row, col)))
+            })
+        });
+
+        cells_match
+            && self.total() != 0
+            && board.placements(tiles).into_iter().all(|cells| {
+                cells
+                    .into_iter()
+                    .map(|cell| weight(cell).unwrap_or(0))
+                    .sum::<i64>()
+                    .rem_euclid(m)
+                    == 0
+            })
+    }
+}
+
+impl fmt::Display for Coloring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .weights
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|weight| weight.to_string().len())
+            .max()
+            .unwrap_or(1);
+
+        for row in &self.weights {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|weight| match weight {
+                    Some(weight) => format!("{:>width$}", weight, width = width),
+                    None => format!("{:>width$}", ".", width = width),
+                })
+                .collect();
+
+            writeln!(f, "{}", cells.join(" "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Searches for a colouring proving that the given board can't be tiled by the given tiles,
+/// trying each modulus in turn.
+///
+/// Tile counts are ignored, so a colouring is only found if the board can't be tiled even
+/// with unlimited tiles.  Not finding a colouring doesn't mean that the board can be tiled.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::analysis::find_coloring;
+/// use dcc_tiler::board::RectangularBoard;
+/// use dcc_tiler::tile::{Tile, TileCollection};
+///
+/// // the mutilated chessboard
+/// let board = RectangularBoard::with_holes(8, 8, &[(0, 0), (7, 7)]);
+/// let tiles = TileCollection::from(Tile::domino());
+///
+/// let coloring = find_coloring(&board, &tiles).unwrap();
+/// assert!(coloring.verify(&board, &tiles));
+/// assert_eq!(coloring.modulus(), 3);
+///
+/// // an 8x8 board can be tiled by dominoes, so has no such colouring
+/// assert!(find_coloring(&RectangularBoard::new(8, 8), &tiles).is_none());
+/// ```
+pub fn find_coloring(board: &RectangularBoard, tiles: &TileCollection) -> Option<Coloring> {
+    // number the empty cells
+    let mut index = vec![vec![None; board.width]; board.height];
+    let mut cells = 0;
+
+    for (i, row) in index.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            if !board.is_marked(Position::from((i, j))) {
+                *cell = Some(cells);
+                cells += 1;
+            }
+        }
+    }
+
+    if cells == 0 {
+        return None;
+    }
+
+    let placements: Vec<Vec<usize>> = board
+        .placements(tiles)
+        .into_iter()
+        .map(|placement| {
+            placement
+                .into_iter()
+                .map(|(i, j)| index[i][j].unwrap())
+                .collect()
+        })
+        .collect();
+
+    MODULI.iter().find_map(|&p| {
+        let weights = find_weights(cells, &placements, p)?;
+
+        Some(Coloring {
+            modulus: p,
+            weights: index
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|cell| {
+                            cell.map(|k: usize| {
+                                // show weights as small positive or negative numbers
+                                let weight = weights[k] as i64;
+                                if 2 * weight > p as i64 {
+                                    weight - p as i64
+                                } else {
+                                    weight
+                                }
+                            })
+                        })
+                        .collect()
+                })
+                .collect(),
+        })
+    })
+}
+
+/// Finds weights modulo the prime `p` for each of the cells such that every placement has
+/// weight zero but the cells together don't, if there are any.
+///
+/// Such weights exist exactly when the all ones vector isn't in the span of the placement
+/// vectors, in which case they can be read off from the reduced row echelon form of the
+/// placement vectors.
+fn find_weights(cells: usize, placements: &[Vec<usize>], p: u64) -> Option<Vec<u64>> {
+    // each basis vector has a 1 in its pivot column, and a 0 in every other pivot column
+    let mut basis: Vec<(usize, Vec<u64>)> = Vec::new();
+
+    for placement in placements {
+        let mut v = vec![0; cells];
+        for &cell in placement {
+            v[cell] = 1;
+        }
+
+        for (pivot, row) in &basis {
+            let factor = v[*pivot];
+            if factor != 0 {
+                subtract_multiple(&mut v, row, factor, p);
+            }
+        }
+
+        let pivot = match v.iter().position(|&x| x != 0) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        let inverse = inverse(v[pivot], p);
+        for x in v.iter_mut() {
+            *x = *x * inverse % p;
+        }
+
+        for (_, row) in basis.iter_mut() {
+            let factor = row[pivot];
+            if factor != 0 {
+                subtract_multiple(row, &v, factor, p);
+            }
+        }
+        basis.push((pivot, v));
+
+        if basis.len() == cells {
+            // the placements span everything
+            return None;
+        }
+    }
+
+    // each free column gives a vector orthogonal to every placement, and we want one which
+    // isn't orthogonal to the all ones vector - preferring the one with the fewest nonzero
+    // weights, as the simplest to read
+    let mut is_pivot = vec![false; cells];
+    for (pivot, _) in &basis {
+        is_pivot[*pivot] = true;
+    }
+
+    (0..cells)
+        .filter(|&free| !is_pivot[free])
+        .map(|free| {
+            let mut weights = vec![0; cells];
+            weights[free] = 1;
+            for (pivot, row) in &basis {
+                weights[*pivot] = (p - row[free]) % p;
+            }
+            weights
+        })
+        .filter(|weights| weights.iter().sum::<u64>() % p != 0)
+        .min_by_key(|weights| weights.iter().filter(|&&w| w != 0).count())
+}
+
+/// Sets `v` to `v - factor * row`, modulo `p`
+fn subtract_multiple(v: &mut [u64], row: &[u64], factor: u64, p: u64) {
+    for (x, y) in v.iter_mut().zip(row) {
+        *x = (*x + (p - factor) * y) % p;
+    }
+}
+
+/// The inverse of `a` modulo the prime `p`
+fn inverse(a: u64, p: u64) -> u64 {
+    // by Fermat's little theorem a^(p - 1) = 1
+    (0..p - 2).fold(1, |acc, _| acc * a % p)
+}
@@ -0,0 +1,104 @@
+//! The arithmetic used to accumulate tiling counts.
+use num::{BigUint, ToPrimitive};
+use std::ops::AddAssign;
+
+/// Returns the natural logarithm of a count, which may be far too large for an `f64`.  The
+/// logarithm of zero is negative infinity.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::arith::ln;
+/// use num::BigUint;
+///
+/// assert_eq!(ln(&BigUint::from(1u32)), 0.0);
+/// assert!((ln(&BigUint::from(1000u32)) - 1000f64.ln()).abs() < 1e-12);
+///
+/// // 2^5000 is far larger than any f64
+/// let huge = BigUint::from(1u32) << 5000;
+/// assert!((ln(&huge) - 5000.0 * 2f64.ln()).abs() < 1e-9);
+/// ```
+pub fn ln(count: &BigUint) -> f64 {
+    // keep the leading 64 bits, which is more than an f64 can hold anyway
+    let shift = count.bits().saturating_sub(64);
+    let leading = (count >> shift).to_f64().unwrap_or(f64::INFINITY);
+
+    leading.ln() + shift as f64 * std::f64::consts::LN_2
+}
+
+/// How tiling counts are accumulated while counting
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Arith {
+    /// Every count is a `BigUint`
+    #[default]
+    Big,
+
+    /// Counts are kept in `u128`s, and each one switches to a `BigUint` if it overflows.
+    /// Most counts fit, and adding `u128`s is several times faster than adding `BigUint`s.
+    Auto,
+}
+
+/// A count of partial tilings, kept in a `u128` until it grows too large
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Count {
+    Small(u128),
+    Big(BigUint),
+}
+
+impl Count {
+    pub(crate) fn zero(arith: Arith) -> Self {
+        match arith {
+            Arith::Big => Count::Big(BigUint::from(0u32)),
+            Arith::Auto => Count::Small(0),
+        }
+    }
+
+    pub(crate) fn one(arith: Arith) -> Self {
+        match arith {
+            Arith::Big => Count::Big(BigUint::from(1u32)),
+            Arith::Auto => Count::Small(1),
+        }
+    }
+
+    /// Converts a count, keeping it in a `u128` if it fits and `arith` allows
+    #[cfg(feature = "fs")]
+    pub(crate) fn from_biguint(value: BigUint, arith: Arith) -> Self {
+        match (arith, <u128 as std::convert::TryFrom<_>>::try_from(&value)) {
+            (Arith::Auto, Ok(small)) => Count::Small(small),
+            _ => Count::Big(value),
+        }
+    }
+
+    pub(crate) fn to_biguint(&self) -> BigUint {
+        match self {
+            Count::Small(small) => BigUint::from(*small),
+            Count::Big(big) => big.clone(),
+        }
+    }
+}
+
+impl AddAssign<&Count> for Count {
+    fn add_assign(&mut self, other: &Count) {
+        match (&mut *self, other) {
+            (Count::Small(a), Count::Small(b)) => {
+                let (a, b) = (*a, *b);
+                *self = match a.checked_add(b) {
+                    Some(sum) => Count::Small(sum),
+                    None => Count::Big(BigUint::from(a) + b),
+                };
+            }
+            (Count::Small(a), Count::Big(b)) => *self = Count::Big(b + *a),
+            (Count::Big(a), Count::Small(b)) => *a += *b,
+            (Count::Big(a), Count::Big(b)) => *a += b,
+        }
+    }
+}
+
+impl From<Count> for BigUint {
+    fn from(count: Count) -> Self {
+        match count {
+            Count::Small(small) => BigUint::from(small),
+            Count::Big(big) => big,
+        }
+    }
+}
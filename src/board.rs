@@ -1,9 +1,17 @@
-use crate::tile::{Direction, Tile, TileCollection};
-use serde_derive::Serialize;
+use crate::error::TilerError;
+use crate::tile::{CellTile, Direction, TileCollection};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde_derive::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashSet;
+use std::error::Error;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
-#[derive(Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "SerializedBoard")]
 pub struct RectangularBoard {
     #[serde(skip_serializing)]
     pub width: usize,
@@ -11,293 +19,2407 @@ pub struct RectangularBoard {
     #[serde(skip_serializing)]
     pub height: usize,
 
-    pub board: Vec<Vec<bool>>,
+    // the cells of the board, true for marked cells.  The cells counted by `counts` and
+    // `constrained` must be kept up to date (see `recount`) whenever this changes, so it's
+    // only read from outside the crate, through `board()`
+    pub(crate) board: Vec<Vec<bool>>,
 
+    // the number of marked (or out of bounds) neighbours of each cell
     #[serde(skip_serializing)]
     counts: Vec<Vec<usize>>,
+
+    // the unmarked cells grouped by their counts
+    #[serde(skip_serializing)]
+    constrained: CountBuckets,
+
+    // the number of times each counted tile (see `TileCollection::with_counts`) has been
+    // placed on this board
+    #[serde(skip_serializing)]
+    used: Vec<usize>,
+
+    // the number of cells marked by `leave_uncovered` rather than covered by a tile
+    #[serde(skip_serializing)]
+    uncovered: usize,
+
+    // only boards on the triangular lattice say which lattice they're on, so that boards of
+    // square cells serialize to nothing but their cells
+    #[serde(skip_serializing_if = "Lattice::is_square")]
+    lattice: Lattice,
+
+    #[serde(skip_serializing)]
+    boundary: Boundary,
 }
 
 impl RectangularBoard {
+    /// Generates a `width x height` board with no cells marked
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the width or height is zero
     pub fn new(width: usize, height: usize) -> Self {
-        let mut counts = vec![vec![0; width]; height];
+        RectangularBoard::try_new(width, height).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `new`, but returns an error if the width or height is zero
+    pub fn try_new(width: usize, height: usize) -> Result<Self, TilerError> {
+        if width == 0 || height == 0 {
+            return Err(TilerError::EmptyBoard { width, height });
+        }
+
+        let mut board = RectangularBoard {
+            width,
+            height,
+            board: vec![vec![false; width]; height],
+            counts: vec![vec![0; width]; height],
+            constrained: CountBuckets::default(),
+            used: Vec::new(),
+            uncovered: 0,
+            lattice: Lattice::Square,
+            boundary: Boundary::Bounded,
+        };
+        board.recount();
+
+        Ok(board)
+    }
+
+    /// Generates a board of `height` rows of `width` unit triangles with no cells marked,
+    /// see `Lattice::Triangular`
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the width or height is zero
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{Lattice, RectangularBoard};
+    /// use dcc_tiler::tile::{CellTile, TileCollection};
+    ///
+    /// // a row of 4 triangles is a parallelogram made of two lozenges
+    /// let board = RectangularBoard::new_triangular(4, 1);
+    /// let lozenges = TileCollection::from(CellTile::lozenge());
+    ///
+    /// assert_eq!(board.lattice(), Lattice::Triangular);
+    /// assert_eq!(board.placements(&lozenges).len(), 3);
+    /// ```
+    pub fn new_triangular(width: usize, height: usize) -> Self {
+        RectangularBoard::try_new_triangular(width, height).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `new_triangular`, but returns an error if the width or height is zero
+    pub fn try_new_triangular(width: usize, height: usize) -> Result<Self, TilerError> {
+        RectangularBoard::try_with(width, height, Lattice::Triangular, Boundary::Bounded)
+    }
+
+    /// Generates a `width x height` board with no cells marked, which wraps around from
+    /// each edge to the opposite edge, see `Boundary::Toroidal`
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the width or height is zero
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// // dominoes may now also cover the two ends of each row or column
+    /// let board = RectangularBoard::new_toroidal(4, 2);
+    /// let mut tiler = Tiler::new(TileCollection::from(Tile::domino()), board);
+    /// assert_eq!(tiler.count_tilings(), 9u32.into());
+    /// ```
+    pub fn new_toroidal(width: usize, height: usize) -> Self {
+        RectangularBoard::try_new_toroidal(width, height).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `new_toroidal`, but returns an error if the width or height is zero
+    pub fn try_new_toroidal(width: usize, height: usize) -> Result<Self, TilerError> {
+        RectangularBoard::try_with(width, height, Lattice::Square, Boundary::Toroidal)
+    }
+
+    /// Generates a `width x height` board with no cells marked, which wraps around from its
+    /// left edge to its right edge, see `Boundary::Cylindrical`
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the width or height is zero
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// // a band of height one can only be covered by horizontal dominoes, in two ways
+    /// let board = RectangularBoard::new_cylindrical(4, 1);
+    /// let mut tiler = Tiler::new(TileCollection::from(Tile::domino()), board);
+    /// assert_eq!(tiler.count_tilings(), 2u32.into());
+    /// ```
+    pub fn new_cylindrical(width: usize, height: usize) -> Self {
+        RectangularBoard::try_new_cylindrical(width, height).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `new_cylindrical`, but returns an error if the width or height is zero
+    pub fn try_new_cylindrical(width: usize, height: usize) -> Result<Self, TilerError> {
+        RectangularBoard::try_with(width, height, Lattice::Square, Boundary::Cylindrical)
+    }
+
+    /// Generates an unmarked `width x height` grid of cells on the given lattice, with the
+    /// given boundary
+    fn try_with(
+        width: usize,
+        height: usize,
+        lattice: Lattice,
+        boundary: Boundary,
+    ) -> Result<Self, TilerError> {
+        let mut board = RectangularBoard::try_new(width, height)?;
+
+        if lattice != Lattice::Square || boundary != Boundary::Bounded {
+            board.lattice = lattice;
+            board.boundary = boundary;
+            board.recount();
+        }
+
+        Ok(board)
+    }
+
+    /// Returns the lattice that the cells of this board lie on
+    pub fn lattice(&self) -> Lattice {
+        self.lattice
+    }
+
+    /// Returns what happens at the edges of this board
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    /// Returns the position reached by moving one step in the given direction from `p`,
+    /// wrapping around the edges of the board which its `Boundary` joins together, or None
+    /// if the step leaves the board
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{Position, RectangularBoard};
+    /// use dcc_tiler::tile::Direction;
+    ///
+    /// let board = RectangularBoard::new(3, 2);
+    /// assert_eq!(board.move_in_direction(Position::new(0, 0), Direction::Up), None);
+    ///
+    /// let torus = RectangularBoard::new_toroidal(3, 2);
+    /// let p = torus.move_in_direction(Position::new(0, 0), Direction::UpLeft);
+    /// assert_eq!(p, Some(Position::new(1, 2)));
+    /// ```
+    pub fn move_in_direction(&self, p: Position, direction: Direction) -> Option<Position> {
+        let (dx, dy) = direction.offset();
+
+        self.boundary
+            .wrap(Position::new(p.x + dx, p.y + dy), self.width, self.height)
+    }
+
+    /// Returns the positions on the board of the cells sharing an edge with the given cell
+    pub(crate) fn neighbours(&self, p: Position) -> impl Iterator<Item = Position> + '_ {
+        let (up, down) = match self.lattice {
+            Lattice::Square => (true, true),
+            // an upward pointing triangle has a horizontal edge below it, and a
+            // downward pointing triangle has one above it
+            Lattice::Triangular => (!Lattice::points_up(p.x, p.y), Lattice::points_up(p.x, p.y)),
+        };
+
+        // this is called for every cell of every board we place a tile on, so it avoids
+        // allocating
+        IntoIterator::into_iter([
+            Some(Direction::Left),
+            Some(Direction::Right),
+            Some(Direction::Up).filter(|_| up),
+            Some(Direction::Down).filter(|_| down),
+        ])
+        .flatten()
+        .filter_map(move |direction| self.move_in_direction(p, direction))
+    }
+
+    /// Generates a `width x height` board with the given (x, y) cells already marked, so
+    /// that they can't be covered by tiles.  As with `Position`, x is the row and y is the
+    /// column of each hole.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the width or height is zero, or a hole lies outside of the board
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{Position, RectangularBoard};
+    ///
+    /// // the mutilated chessboard
+    /// let board = RectangularBoard::with_holes(8, 8, &[(0, 0), (7, 7)]);
+    ///
+    /// assert!(board.is_marked(Position::new(7, 7)));
+    /// assert_eq!(board.board().iter().flatten().filter(|marked| !**marked).count(), 62);
+    /// ```
+    pub fn with_holes(width: usize, height: usize, holes: &[(usize, usize)]) -> Self {
+        RectangularBoard::try_with_holes(width, height, holes).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `with_holes`, but returns an error if the width or height is zero, or a hole
+    /// lies outside of the board
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::error::TilerError;
+    ///
+    /// assert!(matches!(
+    ///     RectangularBoard::try_with_holes(8, 8, &[(8, 0)]),
+    ///     Err(TilerError::OutOfBounds { row: 8, col: 0, .. })
+    /// ));
+    /// assert!(RectangularBoard::try_with_holes(0, 8, &[]).is_err());
+    /// ```
+    pub fn try_with_holes(
+        width: usize,
+        height: usize,
+        holes: &[(usize, usize)],
+    ) -> Result<Self, TilerError> {
+        let board = RectangularBoard::try_new(width, height)?;
+
+        if let Some(&(row, col)) = holes
+            .iter()
+            .find(|(row, col)| *row >= height || *col >= width)
+        {
+            return Err(TilerError::OutOfBounds {
+                row: row as isize,
+                col: col as isize,
+                width,
+                height,
+            });
+        }
+
+        Ok(board.mark_cells(holes))
+    }
+
+    /// Generates a new L-tetromino shaped board.
+    ///
+    /// This is a two step process - first we make an L shape
+    /// with long side having length n, and then we replace each
+    /// box with a scale^2 box.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if n or scale is zero
+    pub fn l_board(n: usize, scale: usize) -> Self {
+        RectangularBoard::try_l_board(n, scale).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `l_board`, but returns an error if n or scale is zero
+    pub fn try_l_board(n: usize, scale: usize) -> Result<Self, TilerError> {
+        check_positive(n, "board size")?;
+        check_positive(scale, "board scale")?;
+
+        let mut board = RectangularBoard::new(n * scale, 2 * scale);
+
+        for row in 0..scale {
+            for col in scale..(n * scale) {
+                board.board[row][col] = true;
+            }
+        }
 
-        for row in counts.iter_mut() {
-            row[0] = 1;
-            row[width - 1] = 1;
+        board.recount();
+
+        Ok(board)
+    }
+
+    /// Generates a new T-tetromino shaped board.
+    ///
+    /// This is a two step process - first we make a T shape
+    /// where the two tils have length n, and then we replace
+    /// each box with a scale^2 box.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if scale is zero
+    pub fn t_board(n: usize, scale: usize) -> Self {
+        RectangularBoard::try_t_board(n, scale).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `t_board`, but returns an error if scale is zero
+    pub fn try_t_board(n: usize, scale: usize) -> Result<Self, TilerError> {
+        check_positive(scale, "board scale")?;
+
+        let mut board = RectangularBoard::new((2 * n + 1) * scale, 2 * scale);
+
+        for row in 0..scale {
+            for col in 0..(n * scale) {
+                board.board[row][col] = true;
+            }
+            for col in ((n + 1) * scale)..((2 * n + 1) * scale) {
+                board.board[row][col] = true;
+            }
+        }
+
+        board.recount();
+
+        Ok(board)
+    }
+
+    /// Generates the Aztec diamond of order n: a board of 2n rows, where the rows
+    /// have lengths 2, 4, ..., 2n, 2n, ..., 4, 2 and are centred on one another.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if n is zero
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::aztec_diamond(3);
+    /// let cells = board.board().iter().flatten().filter(|marked| !**marked).count();
+    /// assert_eq!(cells, 2 * 3 * 4);
+    /// ```
+    pub fn aztec_diamond(n: usize) -> Self {
+        RectangularBoard::try_aztec_diamond(n).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `aztec_diamond`, but returns an error if n is zero
+    pub fn try_aztec_diamond(n: usize) -> Result<Self, TilerError> {
+        check_positive(n, "order of the Aztec diamond")?;
+
+        let mut board = RectangularBoard::new(2 * n, 2 * n);
+
+        for row in 0..n {
+            for col in 0..(n - 1 - row) {
+                board.board[row][col] = true;
+                board.board[row][2 * n - 1 - col] = true;
+                board.board[2 * n - 1 - row][col] = true;
+                board.board[2 * n - 1 - row][2 * n - 1 - col] = true;
+            }
+        }
+
+        board.recount();
+
+        Ok(board)
+    }
+
+    /// Generates the staircase of size n: a board of n rows, where the rows
+    /// have lengths 1, 2, ..., n and are aligned on the left.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if n is zero
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::staircase(4);
+    /// let cells = board.board().iter().flatten().filter(|marked| !**marked).count();
+    /// assert_eq!(cells, 1 + 2 + 3 + 4);
+    /// ```
+    pub fn staircase(n: usize) -> Self {
+        RectangularBoard::try_staircase(n).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `staircase`, but returns an error if n is zero
+    pub fn try_staircase(n: usize) -> Result<Self, TilerError> {
+        check_positive(n, "staircase size")?;
+
+        let mut board = RectangularBoard::new(n, n);
+
+        for row in 0..n {
+            for col in (row + 1)..n {
+                board.board[row][col] = true;
+            }
         }
 
-        for j in 0..width {
-            counts[0][j] = 1;
-            counts[height - 1][j] = 1;
+        board.recount();
+
+        Ok(board)
+    }
+
+    /// Generates a plus shaped board: a central `width x width` square, with an arm of
+    /// length `arm` (and thickness `width`) attached to each of its sides.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if width is zero
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::cross(2, 1);
+    /// let cells = board.board().iter().flatten().filter(|marked| !**marked).count();
+    /// assert_eq!(cells, 4 * 2 + 1);
+    /// ```
+    pub fn cross(arm: usize, width: usize) -> Self {
+        RectangularBoard::try_cross(arm, width).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `cross`, but returns an error if width is zero
+    pub fn try_cross(arm: usize, width: usize) -> Result<Self, TilerError> {
+        check_positive(width, "width of the cross")?;
+
+        let size = 2 * arm + width;
+        let mut board = RectangularBoard::new(size, size);
+        let centre = arm..(arm + width);
+
+        for row in 0..size {
+            for col in 0..size {
+                if !centre.contains(&row) && !centre.contains(&col) {
+                    board.board[row][col] = true;
+                }
+            }
+        }
+
+        board.recount();
+
+        Ok(board)
+    }
+
+    /// Creates a board with bounded edges on the given lattice from its rows of cells,
+    /// where true marks a cell
+    pub(crate) fn from_cells(
+        cells: &[Vec<bool>],
+        lattice: Lattice,
+    ) -> Result<Self, ParseBoardError> {
+        let width = cells.first().map_or(0, Vec::len);
+        if width == 0 {
+            return Err(ParseBoardError::Empty);
+        }
+
+        let mut marked = Vec::new();
+        for (i, row) in cells.iter().enumerate() {
+            if row.len() != width {
+                return Err(ParseBoardError::UnevenRows { row: i });
+            }
+
+            marked.extend((0..width).filter(|&j| row[j]).map(|j| (i, j)));
+        }
+
+        let board = RectangularBoard::try_with(width, cells.len(), lattice, Boundary::Bounded)
+            .expect("the board has cells");
+
+        Ok(board.mark_cells(&marked))
+    }
+
+    /// Parses a board from a text mask, with one line per row of the board.  Each `.`
+    /// is a cell to be tiled and each `#` is a cell which is already marked.  Rows
+    /// shorter than the longest row are padded with marked cells, and blank lines at the
+    /// start and end of the mask are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::from_ascii("#.#\n...\n#.#").unwrap();
+    /// assert_eq!((board.width, board.height), (3, 3));
+    /// assert!(board.board()[0][0]);
+    /// assert!(!board.board()[1][1]);
+    ///
+    /// assert!(RectangularBoard::from_ascii("..x").is_err());
+    /// ```
+    pub fn from_ascii(mask: &str) -> Result<Self, ParseBoardError> {
+        let rows: Vec<&str> = mask
+            .trim_matches(|c| c == '\n' || c == '\r')
+            .lines()
+            .map(str::trim_end)
+            .collect();
+
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        if width == 0 {
+            return Err(ParseBoardError::Empty);
+        }
+
+        let mut board = RectangularBoard::new(width, rows.len());
+
+        for (i, row) in rows.iter().enumerate() {
+            for (j, c) in row.chars().enumerate() {
+                match c {
+                    '.' => {}
+                    '#' => board.board[i][j] = true,
+                    _ => {
+                        return Err(ParseBoardError::InvalidCharacter {
+                            character: c,
+                            row: i,
+                            col: j,
+                        })
+                    }
+                }
+            }
+            for col in row.chars().count()..width {
+                board.board[i][col] = true;
+            }
+        }
+
+        board.recount();
+
+        Ok(board)
+    }
+
+    /// Works out the number of marked (or out of bounds) neighbours of every cell, and so
+    /// which cells are the most constrained, from the marked cells of the board.  Marking
+    /// cells with `mark_cells` or by placing tiles keeps these up to date as it goes, so this
+    /// is only needed after building the grid of marked cells directly.
+    pub(crate) fn recount(&mut self) {
+        let neighbour_count = self.lattice.neighbour_count();
+        self.constrained = CountBuckets::new(self.width * self.height, neighbour_count);
+
+        for i in 0..self.height {
+            for j in 0..self.width {
+                let unmarked = self
+                    .neighbours(Position::from((i, j)))
+                    .filter(|q| !self.board[q.x as usize][q.y as usize])
+                    .count();
+                self.counts[i][j] = neighbour_count - unmarked;
+
+                if !self.board[i][j] {
+                    self.constrained
+                        .insert(self.cell_index(i, j), self.counts[i][j]);
+                }
+            }
+        }
+    }
+
+    /// Returns the number of the (row, column) cell in `constrained`, where cells are
+    /// numbered column by column so that ties between equally constrained cells are broken
+    /// in favour of the leftmost
+    fn cell_index(&self, i: usize, j: usize) -> usize {
+        j * self.height + i
+    }
+
+    /// Marks the given unmarked cell, updating the counts of its neighbours
+    fn mark(&mut self, p: Position) {
+        let (i, j) = (p.x as usize, p.y as usize);
+        debug_assert!(!self.board[i][j], "cell ({}, {}) is already marked", i, j);
+
+        self.constrained
+            .remove(self.cell_index(i, j), self.counts[i][j]);
+        self.board[i][j] = true;
+
+        let neighbours: Vec<Position> = self.neighbours(p).collect();
+        for neighbour in neighbours {
+            let (x, y) = (neighbour.x as usize, neighbour.y as usize);
+
+            if !self.board[x][y] {
+                let index = self.cell_index(x, y);
+                self.constrained.remove(index, self.counts[x][y]);
+                self.constrained.insert(index, self.counts[x][y] + 1);
+            }
+            self.counts[x][y] += 1;
+        }
+    }
+
+    /// Returns the cells of the board, as rows of cells where true marks the cells which have
+    /// been marked (or are holes)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::new(3, 2).mark_cells(&[(1, 2)]);
+    /// assert_eq!(board.board(), [vec![false, false, false], vec![false, false, true]]);
+    /// ```
+    pub fn board(&self) -> &[Vec<bool>] {
+        &self.board
+    }
+
+    /// Returns the number of cells which are still to be covered
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::new(4, 3).mark_cells(&[(0, 0), (2, 3)]);
+    /// assert_eq!(board.unmarked_count(), 10);
+    /// ```
+    pub fn unmarked_count(&self) -> usize {
+        self.constrained.len()
+    }
+
+    /// Determines whether the entire board is marked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::new(2, 1);
+    /// assert!(!board.is_all_marked());
+    /// assert!(board.mark_cells(&[(0, 0), (0, 1)]).is_all_marked());
+    /// ```
+    pub fn is_all_marked(&self) -> bool {
+        self.constrained.len() == 0
+    }
+
+    /// Returns an estimate of the number of bytes of memory used by this board
+    pub fn estimated_memory(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.width
+                * self.height
+                * (std::mem::size_of::<bool>() + std::mem::size_of::<usize>())
+            + self.constrained.estimated_memory()
+            + self.used.len() * std::mem::size_of::<usize>()
+    }
+
+    /// Returns the number of times each counted tile (see `TileCollection::with_counts`)
+    /// has been placed on this board by `place_tile`.  Tiles which haven't been placed yet
+    /// may be missing from the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let tiles = TileCollection::with_counts(vec![(Tile::domino(), 2)]);
+    ///
+    /// let placed = RectangularBoard::new(4, 1).place_tile(&tiles).remove(0);
+    /// assert_eq!(placed.used_counts(), &[1]);
+    ///
+    /// // two dominoes can't cover a 6x1 board
+    /// assert!(RectangularBoard::new(6, 1).place_tile(&tiles).is_empty());
+    /// ```
+    pub fn used_counts(&self) -> &[usize] {
+        &self.used
+    }
+
+    /// Returns a copy of this board with the given (row, column) cell marked without being
+    /// covered by a tile, for tilings which needn't cover every cell.  The number of cells
+    /// left uncovered is part of the board's key, so boards with the same marked cells but
+    /// different numbers of tiles placed are kept apart.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the cell is already marked, or lies outside of the board
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::new(2, 1);
+    /// let left = board.leave_uncovered((0, 0));
+    ///
+    /// assert_eq!(left.uncovered_count(), 1);
+    /// assert_ne!(left, board.mark_cells(&[(0, 0)]));
+    /// ```
+    pub fn leave_uncovered(&self, (i, j): (usize, usize)) -> RectangularBoard {
+        assert!(!self.board[i][j], "cell ({}, {}) is already marked", i, j);
+
+        let mut board = self.clone();
+        board.mark(Position::from((i, j)));
+        board.uncovered += 1;
+
+        board
+    }
+
+    /// Returns the number of cells marked by `leave_uncovered`
+    pub fn uncovered_count(&self) -> usize {
+        self.uncovered
+    }
+
+    /// Returns a lower bound on the number of unmarked cells which any placement of tiles
+    /// from the collection leaves uncovered.  Tiles can only cover a multiple of the greatest
+    /// common divisor of their areas in each connected region of unmarked cells (and nothing
+    /// of a region smaller than the smallest tile), and counted tiles which have been used up
+    /// can't cover anything.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let trominoes = TileCollection::from(Tile::l_tile(2));
+    /// assert_eq!(RectangularBoard::new(5, 5).uncovered_lower_bound(&trominoes), 1);
+    ///
+    /// // marking the middle column leaves two regions of two cells
+    /// let board = RectangularBoard::new(3, 2).mark_cells(&[(0, 1), (1, 1)]);
+    /// assert_eq!(board.uncovered_lower_bound(&trominoes), 4);
+    /// ```
+    pub fn uncovered_lower_bound(&self, tile_collection: &TileCollection) -> usize {
+        let area_gcd = tile_collection.area_gcd().max(1);
+        let smallest = tile_collection
+            .cell_tiles()
+            .map(|tile| tile.len())
+            .min()
+            .unwrap_or(1);
+
+        let bound: usize = self
+            .empty_region_sizes()
+            .into_iter()
+            .map(|area| {
+                if area < smallest {
+                    area
+                } else {
+                    area % area_gcd
+                }
+            })
+            .sum();
+
+        match tile_collection.remaining_area(&self.used) {
+            Some(area) => bound.max(self.unmarked_count().saturating_sub(area)),
+            None => bound,
+        }
+    }
+
+    /// Returns the key of this board (see `key`), as if no cells had been left uncovered
+    pub(crate) fn marked_key(&self) -> BoardKey {
+        BoardKey {
+            uncovered: 0,
+            ..self.key()
+        }
+    }
+
+    /// Returns a compact key identifying which cells of this board are marked, how many
+    /// times each counted tile has been used, and how many cells were left uncovered.
+    ///
+    /// Completely marked rows at the top of the board are retired - only their number
+    /// is recorded - and the remaining rows are bit-packed.  Two boards with the same
+    /// dimensions have the same key if and only if they have the same marked cells, used
+    /// counts and number of uncovered cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::l_board(3, 1);
+    /// assert_eq!(board.restore(&board.key()), board);
+    /// ```
+    pub fn key(&self) -> BoardKey {
+        self.key_of(&self.board)
+    }
+
+    /// Returns the key of a board with the given grid of marked cells, and the same
+    /// dimensions, used tiles and number of uncovered cells as this one
+    fn key_of(&self, board: &[Vec<bool>]) -> BoardKey {
+        let retired_rows = board
+            .iter()
+            .take_while(|row| row.iter().all(|b| *b))
+            .count();
+
+        let mut cells = vec![0u64; ((self.height - retired_rows) * self.width).div_ceil(64)];
+
+        for (index, marked) in board[retired_rows..].iter().flatten().enumerate() {
+            if *marked {
+                cells[index / 64] |= 1 << (index % 64);
+            }
+        }
+
+        BoardKey {
+            retired_rows,
+            cells,
+            used: self.used.clone(),
+            uncovered: self.uncovered,
+        }
+    }
+
+    /// Reconstructs a board obtained by marking cells of this board from its key.
+    ///
+    /// The returned board is identical to the one the key was created from, provided that
+    /// board was obtained from this one by placing tiles.
+    pub fn restore(&self, key: &BoardKey) -> RectangularBoard {
+        let mut board = self.clone();
+
+        for i in 0..self.height {
+            for j in 0..self.width {
+                if !board.board[i][j] && key.is_marked(i, j, self.width) {
+                    board.mark(Position::from((i, j)));
+                }
+            }
+        }
+        board.used = key.used.clone();
+        board.uncovered = key.uncovered;
+
+        board
+    }
+
+    /// Splits this board into its connected regions of unmarked cells.
+    ///
+    /// Each returned board has the same dimensions as this one, with every cell outside
+    /// of the corresponding region marked.  A board with fewer than two regions is
+    /// returned as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::new(5, 2).mark_cells(&[(0, 2), (1, 2)]);
+    /// assert_eq!(board.components().len(), 2);
+    /// ```
+    pub fn components(&self) -> Vec<RectangularBoard> {
+        let regions = self.empty_regions();
+
+        if regions.len() < 2 {
+            return vec![self.clone()];
+        }
+
+        regions
+            .iter()
+            .map(|region| {
+                let mut component = self.clone();
+
+                for i in 0..self.height {
+                    for j in 0..self.width {
+                        if !self.board[i][j] && !region.contains(&(i, j)) {
+                            component.mark(Position::from((i, j)));
+                        }
+                    }
+                }
+
+                component
+            })
+            .collect()
+    }
+
+    /// Returns a copy of this board with the cells that are marked in `after`,
+    /// but not in `before`, also marked.
+    ///
+    /// This replays a tile placement made on one board (e.g. a component of this board)
+    /// onto this board.
+    pub fn apply_difference(
+        &self,
+        before: &RectangularBoard,
+        after: &RectangularBoard,
+    ) -> RectangularBoard {
+        let mut board = self.clone();
+
+        for i in 0..self.height {
+            for j in 0..self.width {
+                if after.board[i][j] && !before.board[i][j] && !board.board[i][j] {
+                    board.mark(Position::from((i, j)));
+                }
+            }
+        }
+
+        board
+    }
+
+    /// Returns the cells (row, column) which are marked on one of this board and `other` but
+    /// not the other, in order.  For consecutive boards of a tiling these are the cells
+    /// covered by the tile placed between them.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the boards have different sizes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::new(3, 2);
+    /// let placed = board.mark_cells(&[(1, 2), (0, 2)]);
+    ///
+    /// assert_eq!(board.diff(&placed), vec![(0, 2), (1, 2)]);
+    /// assert_eq!(placed.diff(&board), vec![(0, 2), (1, 2)]);
+    /// ```
+    pub fn diff(&self, other: &RectangularBoard) -> Vec<(usize, usize)> {
+        assert_eq!((self.width, self.height), (other.width, other.height));
+
+        let mut cells = Vec::new();
+        for i in 0..self.height {
+            for j in 0..self.width {
+                if self.board[i][j] != other.board[i][j] {
+                    cells.push((i, j));
+                }
+            }
+        }
+
+        cells
+    }
+
+    /// Returns every way of placing a tile from the collection on this board, as the
+    /// list of cells (row, column) covered by each placement.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// // there are 4 ways to place an L-tromino on a 2x2 board
+    /// let board = RectangularBoard::new(2, 2);
+    /// let tiles = TileCollection::from(Tile::l_tile(2));
+    /// assert_eq!(board.placements(&tiles).len(), 4);
+    /// ```
+    pub fn placements(&self, tile_collection: &TileCollection) -> Vec<Vec<(usize, usize)>> {
+        self.indexed_placements(tile_collection)
+            .into_iter()
+            .map(|(_, cells)| cells)
+            .collect()
+    }
+
+    /// Like `placements`, but also returns the index (in `tile_collection.cell_tiles()`) of
+    /// the tile making each placement
+    pub(crate) fn indexed_placements(
+        &self,
+        tile_collection: &TileCollection,
+    ) -> Vec<(usize, Vec<(usize, usize)>)> {
+        let mut placements = Vec::new();
+        let mut seen = HashSet::new();
+
+        for i in 0..self.height {
+            for j in 0..self.width {
+                for (index, tile) in tile_collection.cell_tiles().enumerate() {
+                    // placing the first cell of each tile at each position gives every
+                    // placement once, since distinct tiles have distinct shapes - except
+                    // on a board which wraps around, where shapes can cover the same cells
+                    if let Some(tp) = self.tile_fits_at_position(tile, Position::from((i, j)), 0) {
+                        let mut cells: Vec<(usize, usize)> = tp
+                            .covered
+                            .iter()
+                            .map(|p| (p.x as usize, p.y as usize))
+                            .collect();
+                        cells.sort_unstable();
+
+                        if seen.insert(cells.clone()) {
+                            placements.push((index, cells));
+                        }
+                    }
+                }
+            }
+        }
+
+        placements
+    }
+
+    /// Returns a copy of this board with the specified cells (row, column) marked
+    pub fn mark_cells(&self, cells: &[(usize, usize)]) -> RectangularBoard {
+        let mut board = self.clone();
+
+        for cell in cells {
+            if !board.board[cell.0][cell.1] {
+                board.mark(Position::from(*cell));
+            }
+        }
+
+        board
+    }
+
+    /// Returns the image of this board under the given symmetry
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{RectangularBoard, Symmetry};
+    ///
+    /// let board = RectangularBoard::l_board(2, 1);
+    /// let rotated = board.transformed(Symmetry::Rotate90);
+    ///
+    /// assert_eq!((rotated.width, rotated.height), (2, 2));
+    /// assert!(rotated.board()[1][1]);
+    /// ```
+    pub fn transformed(&self, symmetry: Symmetry) -> RectangularBoard {
+        let (width, height) = if symmetry.swaps_dimensions() {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        };
+
+        let mut marked = Vec::new();
+        for i in 0..self.height {
+            for j in 0..self.width {
+                if self.board[i][j] {
+                    marked.push(symmetry.apply((i, j), self.width, self.height));
+                }
+            }
+        }
+
+        let mut board = RectangularBoard::try_with(width, height, self.lattice, self.boundary)
+            .unwrap()
+            .mark_cells(&marked);
+        board.used = self.used.clone();
+        board.uncovered = self.uncovered;
+
+        board
+    }
+
+    /// Returns a copy of this board with the given boundary, so that e.g. a board with holes
+    /// can be wrapped around a torus.  Boards on the triangular lattice should only be
+    /// wrapped if their width and height are both even, so that the triangles meeting
+    /// across each edge point in opposite directions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{Boundary, RectangularBoard};
+    ///
+    /// let board = RectangularBoard::with_holes(3, 3, &[(1, 1)]).with_boundary(Boundary::Toroidal);
+    /// assert_eq!(board.boundary(), Boundary::Toroidal);
+    /// assert_eq!(board.empty_region_sizes(), vec![8]);
+    /// ```
+    pub fn with_boundary(&self, boundary: Boundary) -> RectangularBoard {
+        let mut marked = Vec::new();
+        for i in 0..self.height {
+            for j in 0..self.width {
+                if self.board[i][j] {
+                    marked.push((i, j));
+                }
+            }
+        }
+
+        let mut board = RectangularBoard::try_with(self.width, self.height, self.lattice, boundary)
+            .unwrap()
+            .mark_cells(&marked);
+        board.used = self.used.clone();
+        board.uncovered = self.uncovered;
+
+        board
+    }
+
+    /// Returns a copy of this board with each cell replaced by a `scale x scale` block of
+    /// cells, which are marked if the cell is marked
+    ///
+    /// # Panics
+    ///
+    /// Will panic if scale is zero, or the board is on the triangular lattice
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// assert_eq!(
+    ///     RectangularBoard::l_board(2, 1).scaled(3).board(),
+    ///     RectangularBoard::l_board(2, 3).board()
+    /// );
+    ///
+    /// let board = RectangularBoard::with_holes(2, 1, &[(0, 1)]).scaled(2);
+    /// assert_eq!(board.board(), vec![vec![false, false, true, true]; 2]);
+    /// ```
+    pub fn scaled(&self, scale: usize) -> RectangularBoard {
+        self.try_scaled(scale).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `scaled`, but returns an error if scale is zero or the board is on the
+    /// triangular lattice
+    pub fn try_scaled(&self, scale: usize) -> Result<RectangularBoard, TilerError> {
+        check_positive(scale, "board scale")?;
+        if self.lattice != Lattice::Square {
+            return Err(TilerError::NotSquareLattice);
+        }
+
+        let mut marked = Vec::new();
+        for i in 0..self.height * scale {
+            for j in 0..self.width * scale {
+                if self.board[i / scale][j / scale] {
+                    marked.push((i, j));
+                }
+            }
+        }
+
+        Ok(RectangularBoard::try_with(
+            self.width * scale,
+            self.height * scale,
+            self.lattice,
+            self.boundary,
+        )?
+        .mark_cells(&marked))
+    }
+
+    /// Returns the symmetries which map the marked cells of this board onto themselves
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{RectangularBoard, Symmetry};
+    ///
+    /// // an L board is only symmetric in its anti-diagonal
+    /// let board = RectangularBoard::l_board(2, 1);
+    /// assert_eq!(
+    ///     board.symmetries(),
+    ///     vec![Symmetry::Identity, Symmetry::ReflectAntiDiagonal]
+    /// );
+    /// ```
+    pub fn symmetries(&self) -> Vec<Symmetry> {
+        Symmetry::all()
+            .iter()
+            .copied()
+            .filter(|symmetry| self.lattice.allows(*symmetry, self.width, self.height))
+            .filter(|symmetry| self.boundary.allows(*symmetry))
+            .filter(|symmetry| self.transformed(*symmetry).board == self.board)
+            .collect()
+    }
+
+    /// Returns the lexicographically least of the images of this board under the given
+    /// symmetries, comparing their grids of marked cells row by row.  Boards which are
+    /// rotations or reflections of one another under the symmetries have the same canonical
+    /// form, so long as the symmetries form a group (such as the symmetries of the initial
+    /// board, see `symmetries`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{RectangularBoard, Symmetry};
+    ///
+    /// let board = RectangularBoard::new(2, 2);
+    /// let top = board.mark_cells(&[(0, 0), (0, 1)]);
+    /// let left = board.mark_cells(&[(0, 0), (1, 0)]);
+    /// let bottom = board.mark_cells(&[(1, 0), (1, 1)]);
+    ///
+    /// let symmetries = board.symmetries();
+    /// assert_eq!(top.canonical_form(&symmetries), bottom);
+    /// assert_eq!(left.canonical_form(&symmetries), bottom);
+    ///
+    /// // without the reflections and rotations, every board is its own canonical form
+    /// assert_eq!(top.canonical_form(&[Symmetry::Identity]), top);
+    /// ```
+    pub fn canonical_form(&self, symmetries: &[Symmetry]) -> RectangularBoard {
+        match self.least_image(symmetries) {
+            Some((grid, symmetry)) if grid != self.board => self.transformed(symmetry),
+            _ => self.clone(),
+        }
+    }
+
+    /// Returns the key of the canonical form of this board (see `canonical_form`), without
+    /// building the canonical form itself
+    pub(crate) fn canonical_key(&self, symmetries: &[Symmetry]) -> BoardKey {
+        match self.least_image(symmetries) {
+            Some((grid, _)) => self.key_of(&grid),
+            None => self.key(),
+        }
+    }
+
+    /// Returns the lexicographically least grid of marked cells among the images of this
+    /// board under the given symmetries, along with the symmetry giving it
+    fn least_image(&self, symmetries: &[Symmetry]) -> Option<(Vec<Vec<bool>>, Symmetry)> {
+        let image = |symmetry: Symmetry| {
+            let mut grid = vec![vec![false; self.width]; self.height];
+            for i in 0..self.height {
+                for j in 0..self.width {
+                    if self.board[i][j] {
+                        let (row, col) = symmetry.apply((i, j), self.width, self.height);
+                        grid[row][col] = true;
+                    }
+                }
+            }
+            grid
+        };
+
+        symmetries
+            .iter()
+            .filter(|symmetry| !symmetry.swaps_dimensions() || self.width == self.height)
+            .map(|symmetry| (image(*symmetry), *symmetry))
+            .min_by(|(a, _), (b, _)| a.cmp(b))
+    }
+
+    /// Returns the sizes of the connected regions of unmarked cells on this board
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// // an L board of size 3 and scale 1 consists of a single region of 4 cells
+    /// let board = RectangularBoard::l_board(3, 1);
+    /// assert_eq!(board.empty_region_sizes(), vec![4]);
+    /// ```
+    pub fn empty_region_sizes(&self) -> Vec<usize> {
+        self.empty_regions()
+            .iter()
+            .map(|region| region.len())
+            .collect()
+    }
+
+    /// Determines whether the unmarked cells of this board form a single connected region
+    /// (or there are none)
+    pub(crate) fn is_connected(&self) -> bool {
+        let unmarked = self
+            .board
+            .iter()
+            .flatten()
+            .filter(|marked| !**marked)
+            .count();
+        let start = (0..self.height)
+            .flat_map(|i| (0..self.width).map(move |j| (i, j)))
+            .find(|&(i, j)| !self.board[i][j]);
+
+        let (i, j) = match start {
+            Some(start) => start,
+            None => return true,
+        };
+
+        let mut seen = vec![vec![false; self.width]; self.height];
+        let mut stack = vec![(i, j)];
+        let mut reached = 0;
+        seen[i][j] = true;
+
+        while let Some((row, col)) = stack.pop() {
+            reached += 1;
+
+            for p in self.neighbours(Position::from((row, col))) {
+                let (r, c) = (p.x as usize, p.y as usize);
+                if !self.board[r][c] && !seen[r][c] {
+                    seen[r][c] = true;
+                    stack.push((r, c));
+                }
+            }
+        }
+
+        reached == unmarked
+    }
+
+    /// Returns the connected regions of unmarked cells on this board
+    fn empty_regions(&self) -> Vec<HashSet<(usize, usize)>> {
+        let mut seen = vec![vec![false; self.width]; self.height];
+        let mut regions = Vec::new();
+
+        for i in 0..self.height {
+            for j in 0..self.width {
+                if self.board[i][j] || seen[i][j] {
+                    continue;
+                }
+
+                // flood fill the region containing (i, j)
+                let mut region = HashSet::new();
+                let mut stack = vec![(i, j)];
+                seen[i][j] = true;
+
+                while let Some((row, col)) = stack.pop() {
+                    region.insert((row, col));
+
+                    for p in self.neighbours(Position::from((row, col))) {
+                        let (r, c) = (p.x as usize, p.y as usize);
+                        if !self.board[r][c] && !seen[r][c] {
+                            seen[r][c] = true;
+                            stack.push((r, c));
+                        }
+                    }
+                }
+
+                regions.push(region);
+            }
+        }
+
+        regions
+    }
+
+    /// Returns the number of pairs of neighbouring unmarked cells, counting each pair in
+    /// both orders.  Each of these is a way that a tile covering one cell could go on to
+    /// cover the next, so the fewer there are, the more constrained the board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::new(3, 2);
+    /// assert_eq!(board.freedom(), 14);
+    ///
+    /// // marking the middle of the top row cuts the board's 7 pairs of neighbours down to 4
+    /// assert_eq!(board.mark_cells(&[(0, 1)]).freedom(), 8);
+    /// ```
+    pub fn freedom(&self) -> usize {
+        let mut freedom = 0;
+
+        for i in 0..self.height {
+            for j in 0..self.width {
+                if !self.board[i][j] {
+                    freedom += self
+                        .neighbours(Position::from((i, j)))
+                        .filter(|p| !self.is_marked(*p))
+                        .count();
+                }
+            }
+        }
+
+        freedom
+    }
+
+    /// Returns the unmarked cell with the most marked (or out of bounds) neighbours,
+    /// together with its number of such neighbours.  This is the cell that `place_tile`
+    /// places its next tile at, and ties are broken in favour of the leftmost cell, and
+    /// then the topmost.
+    ///
+    /// The unmarked cells are kept grouped by their number of marked neighbours as cells
+    /// are marked, so this doesn't need to look through the whole board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{Position, RectangularBoard};
+    /// use dcc_tiler::tile::{Direction, Tile, TileCollection};
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// // the unmarked cell with the most marked neighbours, found by looking at every cell
+    /// fn brute_force(board: &RectangularBoard) -> Option<((usize, usize), usize)> {
+    ///     let mut largest: Option<((usize, usize), usize)> = None;
+    ///     for j in 0..board.width {
+    ///         for i in 0..board.height {
+    ///             let p = Position::from((i, j));
+    ///             if board.is_marked(p) {
+    ///                 continue;
+    ///             }
+    ///
+    ///             let directions = [Direction::Left, Direction::Right, Direction::Up, Direction::Down];
+    ///             let count = directions
+    ///                 .iter()
+    ///                 .filter(|d| board.move_in_direction(p, **d).is_none_or(|q| board.is_marked(q)))
+    ///                 .count();
+    ///             if largest.is_none_or(|(_, most)| count > most) {
+    ///                 largest = Some(((i, j), count));
+    ///             }
+    ///         }
+    ///     }
+    ///     largest
+    /// }
+    ///
+    /// assert_eq!(RectangularBoard::new(3, 2).most_constrained_cell(), Some(((0, 0), 2)));
+    ///
+    /// // follow random placements of L-trominoes on a few boards until they get stuck
+    /// let tiles = TileCollection::from(Tile::l_tile(2));
+    /// let mut rng = StdRng::seed_from_u64(7);
+    /// for start in [
+    ///     RectangularBoard::l_board(4, 2),
+    ///     RectangularBoard::t_board(1, 3),
+    ///     RectangularBoard::aztec_diamond(3),
+    ///     RectangularBoard::new_toroidal(6, 4),
+    /// ] {
+    ///     for _ in 0..10 {
+    ///         let mut board = start.clone();
+    ///         loop {
+    ///             assert_eq!(board.most_constrained_cell(), brute_force(&board));
+    ///
+    ///             let unmarked = board.board().iter().flatten().filter(|marked| !**marked).count();
+    ///             assert_eq!(board.unmarked_count(), unmarked);
+    ///
+    ///             match board.place_tile_with_rng(&tiles, &mut rng).pop() {
+    ///                 Some(child) => board = child,
+    ///                 None => break,
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn most_constrained_cell(&self) -> Option<((usize, usize), usize)> {
+        self.constrained
+            .highest()
+            .map(|(index, count)| ((index % self.height, index / self.height), count))
+    }
+
+    pub fn place_tile(&self, tile_collection: &TileCollection) -> Vec<RectangularBoard> {
+        self.place_tile_filtered(tile_collection, &|_, _| true)
+    }
+
+    /// Returns the unmarked cell that `place_tile_with_selection` places its next tile at when
+    /// choosing cells by `selection`, or `None` if every cell is marked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{CellSelection, RectangularBoard};
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let board = RectangularBoard::new(3, 3).mark_cells(&[(0, 0), (2, 1)]);
+    /// let tiles = TileCollection::from(Tile::domino());
+    ///
+    /// assert_eq!(board.select_cell(CellSelection::Lexicographic, &tiles), Some((0, 1)));
+    ///
+    /// // the bottom left cell can only be covered by a vertical domino
+    /// assert_eq!(board.select_cell(CellSelection::FewestFits, &tiles), Some((2, 0)));
+    /// ```
+    pub fn select_cell(
+        &self,
+        selection: CellSelection,
+        tile_collection: &TileCollection,
+    ) -> Option<(usize, usize)> {
+        self.select_cell_filtered(selection, tile_collection, &|_, _| true)
+    }
+
+    /// Like `select_cell`, but only counting the placements accepted by `filter` when
+    /// choosing the cell with the fewest placements
+    pub(crate) fn select_cell_filtered(
+        &self,
+        selection: CellSelection,
+        tile_collection: &TileCollection,
+        filter: &dyn Fn(&RectangularBoard, &TilePosition) -> bool,
+    ) -> Option<(usize, usize)> {
+        match selection {
+            CellSelection::MostConstrained => self.most_constrained_cell().map(|(cell, _)| cell),
+            CellSelection::Lexicographic => (0..self.height)
+                .flat_map(|i| (0..self.width).map(move |j| (i, j)))
+                .find(|&(i, j)| !self.board[i][j]),
+            CellSelection::FewestFits => {
+                // the best cell so far, compared by its number of placements and then its
+                // count, so that ties go to the most constrained cell
+                let mut fewest = None;
+                let mut fewest_key = (usize::MAX, Reverse(0));
+
+                for j in 0..self.width {
+                    for i in 0..self.height {
+                        if self.board[i][j] {
+                            continue;
+                        }
+
+                        let fits = self.fitting_tiles_at((i, j), tile_collection, filter).len();
+                        let key = (fits, Reverse(self.counts[i][j]));
+                        if fewest.is_none() || key < fewest_key {
+                            fewest = Some((i, j));
+                            fewest_key = key;
+                        }
+
+                        // a cell which can't be covered at all ends the search here
+                        if fits == 0 {
+                            return Some((i, j));
+                        }
+                    }
+                }
+
+                fewest
+            }
+        }
+    }
+
+    /// Like `place_tile`, but only makes the placements accepted by `filter`, which is given
+    /// this board and the cells the tile would cover.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let board = RectangularBoard::new(2, 2);
+    /// let tiles = TileCollection::from(Tile::domino());
+    ///
+    /// // only allow horizontal dominoes
+    /// let children = board.place_tile_filtered(&tiles, &|_, tp| {
+    ///     tp.covered().iter().all(|p| p.x() == tp.covered().iter().next().unwrap().x())
+    /// });
+    /// assert_eq!(children.len(), 1);
+    /// ```
+    pub fn place_tile_filtered(
+        &self,
+        tile_collection: &TileCollection,
+        filter: &dyn Fn(&RectangularBoard, &TilePosition) -> bool,
+    ) -> Vec<RectangularBoard> {
+        self.place_tile_with_selection(tile_collection, CellSelection::MostConstrained, filter)
+    }
+
+    /// Like `place_tile_filtered`, but places the tile over the cell chosen by `selection`
+    /// (see `select_cell`) rather than the most constrained cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{CellSelection, RectangularBoard};
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let board = RectangularBoard::new(3, 3).mark_cells(&[(0, 0), (0, 2), (2, 1)]);
+    /// let tiles = TileCollection::from(Tile::domino());
+    ///
+    /// // each corner left can only be covered by one domino
+    /// let children = board.place_tile_with_selection(&tiles, CellSelection::FewestFits, &|_, _| true);
+    /// assert_eq!(children.len(), 1);
+    /// ```
+    pub fn place_tile_with_selection(
+        &self,
+        tile_collection: &TileCollection,
+        selection: CellSelection,
+        filter: &dyn Fn(&RectangularBoard, &TilePosition) -> bool,
+    ) -> Vec<RectangularBoard> {
+        self.place_tile_indexed(tile_collection, selection, filter)
+            .into_iter()
+            .map(|(_, board)| board)
+            .collect()
+    }
+
+    /// Like `place_tile_with_selection`, but also returns the index (in
+    /// `tile_collection.cell_tiles()`) of the tile placed to make each board
+    pub(crate) fn place_tile_indexed(
+        &self,
+        tile_collection: &TileCollection,
+        selection: CellSelection,
+        filter: &dyn Fn(&RectangularBoard, &TilePosition) -> bool,
+    ) -> Vec<(usize, RectangularBoard)> {
+        let largest = self.most_constrained_cell();
+
+        // If our tile collection doesn't contain a 1x1 tile and there's a spot
+        // surrounded on all sides, then that spot cannot be tiled, so we're done
+        let surrounded = self.lattice.neighbour_count();
+        if !tile_collection.contains_single_tile()
+            && largest.is_some_and(|(_, count)| count == surrounded)
+        {
+            return Vec::new();
+        }
+        if !self.has_room_for_remaining_tiles(tile_collection) || !self.prune_check(tile_collection)
+        {
+            return Vec::new();
+        }
+
+        let cell = match (selection, largest) {
+            (CellSelection::MostConstrained, largest) => largest.map(|(cell, _)| cell),
+            (_, None) => None,
+            (selection, Some(_)) => self.select_cell_filtered(selection, tile_collection, filter),
+        };
+
+        match cell {
+            Some(position) => self.place_tile_at(position, tile_collection, filter),
+            None => Vec::new(),
+        }
+    }
+
+    /// Like `place_tile`, except that ties between equally constrained cells are broken
+    /// at random, and the resulting boards are returned in a random order.
+    pub fn place_tile_with_rng<R: Rng>(
+        &self,
+        tile_collection: &TileCollection,
+        rng: &mut R,
+    ) -> Vec<RectangularBoard> {
+        self.place_tile_with_rng_filtered(tile_collection, rng, &|_, _| true)
+    }
+
+    /// Like `place_tile_with_rng`, but only makes the placements accepted by `filter`
+    pub fn place_tile_with_rng_filtered<R: Rng>(
+        &self,
+        tile_collection: &TileCollection,
+        rng: &mut R,
+        filter: &dyn Fn(&RectangularBoard, &TilePosition) -> bool,
+    ) -> Vec<RectangularBoard> {
+        let largest_count = match self.most_constrained_cell() {
+            Some((_, count)) => count,
+            None => return Vec::new(),
+        };
+
+        if !tile_collection.contains_single_tile()
+            && largest_count == self.lattice.neighbour_count()
+        {
+            return Vec::new();
+        }
+        if !self.has_room_for_remaining_tiles(tile_collection) || !self.prune_check(tile_collection)
+        {
+            return Vec::new();
+        }
+
+        let mut candidates = Vec::new();
+        for i in 0..self.height {
+            for j in 0..self.width {
+                if !self.board[i][j] && self.counts[i][j] == largest_count {
+                    candidates.push((i, j));
+                }
+            }
+        }
+
+        let mut boards: Vec<RectangularBoard> = self
+            .place_tile_at(*candidates.choose(rng).unwrap(), tile_collection, filter)
+            .into_iter()
+            .map(|(_, board)| board)
+            .collect();
+        boards.shuffle(rng);
+        boards
+    }
+
+    /// Determines whether the counted tiles which haven't been used up could still cover
+    /// every unmarked cell.  This is always true if some tile can be used any number of times.
+    fn has_room_for_remaining_tiles(&self, tile_collection: &TileCollection) -> bool {
+        match tile_collection.remaining_area(&self.used) {
+            Some(area) => area >= self.unmarked_count(),
+            None => true,
+        }
+    }
+
+    /// Determines whether every connected region of unmarked cells could still be covered
+    /// by tiles from the collection.  A region smaller than the smallest tile, or whose area
+    /// isn't a multiple of the greatest common divisor of the areas of the tiles, can never
+    /// be covered - so `place_tile` places no tiles on a board failing this check, cutting
+    /// off the search as soon as a tile walls off such a region.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// // marking the middle column leaves two regions of two cells
+    /// let board = RectangularBoard::new(3, 2).mark_cells(&[(0, 1), (1, 1)]);
+    ///
+    /// assert!(board.prune_check(&TileCollection::from(Tile::domino())));
+    /// assert!(!board.prune_check(&TileCollection::from(Tile::l_tile(2))));
+    /// ```
+    pub fn prune_check(&self, tile_collection: &TileCollection) -> bool {
+        let area_gcd = tile_collection.area_gcd();
+        let smallest = tile_collection
+            .cell_tiles()
+            .map(|tile| tile.len())
+            .min()
+            .unwrap_or(1);
+
+        if area_gcd <= 1 && smallest <= 1 {
+            return true;
+        }
+
+        let mut seen = vec![vec![false; self.width]; self.height];
+
+        for i in 0..self.height {
+            for j in 0..self.width {
+                if self.board[i][j] || seen[i][j] {
+                    continue;
+                }
+
+                // flood fill the region containing (i, j), counting its cells
+                let mut area = 0;
+                let mut stack = vec![(i, j)];
+                seen[i][j] = true;
+
+                while let Some((row, col)) = stack.pop() {
+                    area += 1;
+
+                    for p in self.neighbours(Position::from((row, col))) {
+                        let (r, c) = (p.x as usize, p.y as usize);
+                        if !self.board[r][c] && !seen[r][c] {
+                            seen[r][c] = true;
+                            stack.push((r, c));
+                        }
+                    }
+                }
+
+                if area < smallest || area % area_gcd.max(1) != 0 {
+                    return false;
+                }
+            }
         }
 
-        RectangularBoard {
-            width,
-            height,
-            board: vec![vec![false; width]; height],
-            counts,
-        }
+        true
     }
 
-    /// Generates a new L-tetromino shaped board.
-    ///
-    /// This is a two step process - first we make an L shape
-    /// with long side having length n, and then we replace each
-    /// box with a scale^2 box.
-    pub fn l_board(n: usize, scale: usize) -> Self {
-        let mut board = RectangularBoard::new(n * scale, 2 * scale);
+    /// Returns all boards obtained by placing a tile from the collection over the given cell,
+    /// keeping only the placements accepted by `filter`, along with the index of the tile
+    /// placed to make each board
+    pub(crate) fn place_tile_at(
+        &self,
+        cell: (usize, usize),
+        tile_collection: &TileCollection,
+        filter: &dyn Fn(&RectangularBoard, &TilePosition) -> bool,
+    ) -> Vec<(usize, RectangularBoard)> {
+        // For each fitting tile we find, return the corresponding board
+        self.fitting_tiles_at(cell, tile_collection, filter)
+            .into_iter()
+            .map(|(tp, index, count_index)| {
+                let mut child_board = self.clone();
+                child_board.mark_tile_at_position(tp);
 
-        for row in 0..scale {
-            for col in scale..(n * scale) {
-                board.board[row][col] = true;
-            }
-        }
+                if let Some(c) = count_index {
+                    if child_board.used.len() <= c {
+                        child_board.used.resize(tile_collection.counts().len(), 0);
+                    }
+                    child_board.used[c] += 1;
+                }
 
-        board
+                (index, child_board)
+            })
+            .collect()
     }
 
-    /// Generates a new T-tetromino shaped board.
+    /// Returns the distinct placements of tiles from the collection covering the given
+    /// (row, column) cell, leaving out tiles which have been used up.  Each placement can be
+    /// made with `mark_tile_at_position`.
     ///
-    /// This is a two step process - first we make a T shape
-    /// where the two tils have length n, and then we replace
-    /// each box with a scale^2 box.
-    pub fn t_board(n: usize, scale: usize) -> Self {
-        let mut board = RectangularBoard::new((2 * n + 1) * scale, 2 * scale);
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let board = RectangularBoard::new(3, 3);
+    /// let dominoes = TileCollection::from(Tile::domino());
+    ///
+    /// assert_eq!(board.placements_at((0, 0), &dominoes).len(), 2);
+    /// assert_eq!(board.placements_at((1, 1), &dominoes).len(), 4);
+    ///
+    /// // no tile can cover a marked cell
+    /// let board = board.mark_cells(&[(0, 0)]);
+    /// assert!(board.placements_at((0, 0), &dominoes).is_empty());
+    /// assert_eq!(board.placements_at((0, 1), &dominoes).len(), 2);
+    /// ```
+    pub fn placements_at(
+        &self,
+        cell: (usize, usize),
+        tile_collection: &TileCollection,
+    ) -> Vec<TilePosition> {
+        self.fitting_tiles_at(cell, tile_collection, &|_, _| true)
+            .into_iter()
+            .map(|(tp, _, _)| tp)
+            .collect()
+    }
 
-        for row in 0..scale {
-            for col in 0..(n * scale) {
-                board.board[row][col] = true;
+    /// Returns the distinct placements of tiles from the collection over the given cell which
+    /// are accepted by `filter`, along with the index of each tile and of its count (if the
+    /// tile can only be used a limited number of times)
+    fn fitting_tiles_at(
+        &self,
+        (i, j): (usize, usize),
+        tile_collection: &TileCollection,
+        filter: &dyn Fn(&RectangularBoard, &TilePosition) -> bool,
+    ) -> Vec<(TilePosition, usize, Option<usize>)> {
+        let mut fitting_tiles = Vec::new();
+
+        for (index, tile) in tile_collection.cell_tiles().enumerate() {
+            // skip tiles which have been used up
+            let count_index = tile_collection.count_index(index);
+            if let Some(c) = count_index {
+                if self.used.get(c).copied().unwrap_or(0) >= tile_collection.counts()[c] {
+                    continue;
+                }
             }
-            for col in ((n + 1) * scale)..((2 * n + 1) * scale) {
-                board.board[row][col] = true;
+
+            for anchor in 0..tile.len() {
+                if let Some(tp) = self.tile_fits_at_position(tile, Position::from((i, j)), anchor) {
+                    // Really we should be using a HashSet for fitting_tiles, but it's annoying
+                    // to hash a HashSet, so we just check for containment here instead
+                    if !fitting_tiles.iter().any(|(fitting, _, _)| *fitting == tp)
+                        && filter(self, &tp)
+                    {
+                        fitting_tiles.push((tp, index, count_index));
+                    }
+                }
             }
         }
 
-        board
+        fitting_tiles
     }
 
-    /// What does it do?
+    /// Determines whether the cell at the specified position is marked
+    ///
+    /// # Panics
     ///
-    /// Details here.
+    /// Will panic if the position is not on the board
+    pub fn is_marked(&self, p: Position) -> bool {
+        let p = self
+            .boundary
+            .wrap(p, self.width, self.height)
+            .expect("position is not on the board");
+
+        self.board[p.x as usize][p.y as usize]
+    }
+
+    /// Determines whether the specified position is on the board.  Every position is on a
+    /// board with a `Boundary::Toroidal` boundary, and every position in one of its rows is
+    /// on a board with a `Boundary::Cylindrical` boundary.
+    pub fn is_valid(&self, p: Position) -> bool {
+        self.boundary.wrap(p, self.width, self.height).is_some()
+    }
+
+    /// Tests whether the specified tile fits on the board when its `anchor`-th cell
+    /// is placed at the specified board position.  If it does, then return Some(TilePosition)
     ///
     /// # Panics
     ///
-    /// When does it panic?
+    /// Will panic if `anchor` is not less than the number of cells in the tile
     ///
     /// # Examples
     ///
     /// ```
-    /// // Example code here
+    /// use dcc_tiler::board::{Position, RectangularBoard};
+    /// use dcc_tiler::tile::{CellTile, Tile};
+    ///
+    /// let mut board = RectangularBoard::new(3, 1);
+    /// let domino = CellTile::from(Tile::domino());
+    ///
+    /// let tp = board.tile_fits_at_position(&domino, Position::new(0, 1), 0).unwrap();
+    /// assert!(tp.covered().contains(&Position::new(0, 2)));
+    /// assert!(board.tile_fits_at_position(&domino, Position::new(0, 2), 0).is_none());
+    ///
+    /// board.mark_tile_at_position(tp);
+    /// assert!(board.is_marked(Position::new(0, 1)));
     /// ```
-    fn mark(&mut self, p: Position) {
-        for xp in (p.x - 1)..=(p.x + 1) {
-            if xp == p.x {
-                continue;
-            }
-            if self.is_valid(Position::from((xp, p.y))) {
-                self.counts[xp as usize][p.y as usize] += 1;
-            }
+    pub fn tile_fits_at_position(
+        &self,
+        tile: &CellTile,
+        position: Position,
+        anchor: usize,
+    ) -> Option<TilePosition> {
+        // make sure our anchor is one of the tile's cells
+        assert!(anchor < tile.len());
+
+        if tile.lattice() != self.lattice {
+            return None;
+        }
+
+        let (anchor_row, anchor_col) = tile.cells()[anchor];
+
+        // on the triangular lattice the tile may only be moved by translations taking
+        // upward pointing triangles to upward pointing triangles
+        if self.lattice == Lattice::Triangular
+            && (position.x + position.y - (anchor_row + anchor_col) as isize) % 2 != 0
+        {
+            return None;
         }
-        for yp in (p.y - 1)..=(p.y + 1) {
-            if yp == p.y {
-                continue;
+        let mut covered = HashSet::new();
+
+        for (row, col) in tile.cells() {
+            let p = self.boundary.wrap(
+                Position::new(
+                    position.x + (row - anchor_row) as isize,
+                    position.y + (col - anchor_col) as isize,
+                ),
+                self.width,
+                self.height,
+            )?;
+
+            // a tile wrapping around a small enough board could overlap itself
+            if self.is_marked(p) || !covered.insert(p) {
+                return None;
             }
-            if self.is_valid(Position::from((p.x, yp))) {
-                self.counts[p.x as usize][yp as usize] += 1;
+        }
+
+        Some(TilePosition::new(covered))
+    }
+
+    /// Like `tile_fits_at_position`, but returns an error if `anchor` is not less than the
+    /// number of cells in the tile
+    pub fn try_tile_fits_at_position(
+        &self,
+        tile: &CellTile,
+        position: Position,
+        anchor: usize,
+    ) -> Result<Option<TilePosition>, TilerError> {
+        if anchor >= tile.len() {
+            return Err(TilerError::InvalidAnchor {
+                anchor,
+                cells: tile.len(),
+            });
+        }
+
+        Ok(self.tile_fits_at_position(tile, position, anchor))
+    }
+
+    /// Marks every cell covered by the specified tile position
+    pub fn mark_tile_at_position(&mut self, tp: TilePosition) {
+        for position in tp.covered {
+            self.mark(position);
+        }
+    }
+}
+
+/// Returns an error if the named size is zero
+pub(crate) fn check_positive(size: usize, name: &'static str) -> Result<(), TilerError> {
+    if size == 0 {
+        Err(TilerError::ZeroSize(name))
+    } else {
+        Ok(())
+    }
+}
+
+impl fmt::Debug for RectangularBoard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut os = Vec::with_capacity((1 + self.width) * self.height);
+
+        for i in 0..self.height {
+            for j in 0..self.width {
+                os.push(if self.board[i][j] { "x" } else { "*" });
             }
+            os.push("\n");
         }
 
-        self.board[p.x as usize][p.y as usize] = true;
+        write!(f, "{}", os.join(""))
     }
+}
 
-    /// Determines whether the entire board is marked
+/// The shape of the cells of a board.  Every board is stored as a grid of (row, column)
+/// cells, and the lattice decides which cells share an edge.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Lattice {
+    /// Square cells, each sharing an edge with the cells above, below, left and right of it
+    #[default]
+    Square,
+
+    /// Unit triangles, alternately pointing up and down along each row.  The (row, column)
+    /// cell points up when row + column is even, and shares an edge with the cells to its
+    /// left and right, and with the cell below it (if it points up) or above it (if it
+    /// points down).  Each row is half a triangle wider than its number of cells.
+    Triangular,
+}
+
+impl Lattice {
+    fn is_square(&self) -> bool {
+        *self == Lattice::Square
+    }
+
+    /// Returns the number of cells sharing an edge with each cell
+    pub fn neighbour_count(&self) -> usize {
+        match self {
+            Lattice::Square => 4,
+            Lattice::Triangular => 3,
+        }
+    }
+
+    /// Determines whether the (row, column) cell of the triangular lattice points up
+    pub fn points_up(row: isize, col: isize) -> bool {
+        (row + col) % 2 == 0
+    }
+
+    /// Determines whether the symmetry maps a `width x height` grid of cells on this
+    /// lattice onto itself, taking cells sharing an edge to cells sharing an edge
     ///
     /// # Examples
     ///
     /// ```
-    /// // Example code here
+    /// use dcc_tiler::board::{Lattice, Symmetry};
+    ///
+    /// // turning a triangle pointing up upside down gives a triangle pointing down
+    /// assert!(Lattice::Triangular.allows(Symmetry::Rotate180, 4, 3));
+    /// assert!(!Lattice::Triangular.allows(Symmetry::Rotate180, 4, 4));
+    /// assert!(!Lattice::Triangular.allows(Symmetry::Rotate90, 4, 4));
     /// ```
-    pub fn is_all_marked(&self) -> bool {
-        for row in self.board.iter() {
-            for col in row.iter() {
-                if !(*col) {
-                    return false;
-                }
-            }
+    pub fn allows(&self, symmetry: Symmetry, width: usize, height: usize) -> bool {
+        match self {
+            Lattice::Square => !symmetry.swaps_dimensions() || width == height,
+            Lattice::Triangular => match symmetry {
+                Symmetry::Identity => true,
+                Symmetry::ReflectVertical => width % 2 == 1,
+                Symmetry::ReflectHorizontal => height.is_multiple_of(2),
+                Symmetry::Rotate180 => (width + height) % 2 == 1,
+                _ => false,
+            },
         }
-        true
     }
+}
 
-    pub fn place_tile(&self, tile_collection: &TileCollection) -> Vec<RectangularBoard> {
-        let mut largest_count = None;
-        let mut largest_position = None;
+/// How `place_tile_with_selection` chooses the cell to cover with its next tile.  Every tiling
+/// covers every cell, so any choice finds each tiling exactly once, but the number of boards
+/// explored along the way can differ greatly.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum CellSelection {
+    /// The cell with the most marked (or out of bounds) neighbours, see
+    /// `most_constrained_cell`
+    #[default]
+    MostConstrained,
 
-        // find the position with the highest count
-        for j in 0..self.width {
-            for i in 0..self.height {
-                if !self.board[i][j] {
-                    let count = self.counts[i][j];
+    /// The first unmarked cell, reading the rows from top to bottom and each row from left
+    /// to right
+    Lexicographic,
 
-                    // If our tile collection doesn't contain a 1x1 tile,
-                    // then we've found a spot that cannot be tiled, so we're done
-                    if !tile_collection.contains_single_tile() && count == 4 {
-                        return Vec::new();
-                    }
+    /// The cell which can be covered in the fewest ways, found by trying every placement
+    /// over every unmarked cell.  Choosing the cell with the fewest options first keeps the
+    /// search narrow, which for some tile sets far outweighs the cost of trying them all.
+    /// Ties go to the most constrained cell.
+    FewestFits,
+}
 
-                    // keep track of the largest count we've found so far
-                    if largest_count.is_none() || self.counts[i][j] > largest_count.unwrap() {
-                        largest_count = Some(self.counts[i][j]);
-                        largest_position = Some((i, j));
-                    }
-                }
-            }
-        }
+/// What happens at the edges of a board
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Boundary {
+    /// Positions past the edges of the board aren't on the board
+    #[default]
+    Bounded,
 
-        // Next, find all the tiles that fit at out best position
-        let mut fitting_tiles = Vec::new();
+    /// The board wraps around like a torus: the cells at the ends of each row are
+    /// neighbours, as are the cells at the ends of each column
+    Toroidal,
 
-        if let Some((i, j)) = largest_position {
-            for tile in tile_collection.iter() {
-                for start_index in 0..=tile.directions.len() {
-                    if let Some(tp) =
-                        self.tile_fits_at_position(tile, Position::from((i, j)), start_index)
-                    {
-                        // Really we should be using a HashSet for fitting_tiles, but it's annoying
-                        // to hash a HashSet, so we just check for containment here instead
-                        if !fitting_tiles.contains(&tp) {
-                            fitting_tiles.push(tp);
-                        }
-                    }
+    /// The board wraps around horizontally, like a cylinder standing upright: the cells at
+    /// the ends of each row are neighbours, but the top and bottom rows are edges
+    Cylindrical,
+}
+
+impl Boundary {
+    /// Returns the position on a `width x height` board which `p` refers to, or None if
+    /// it doesn't lie on the board
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{Boundary, Position};
+    ///
+    /// let p = Position::new(-1, 5);
+    /// assert_eq!(Boundary::Bounded.wrap(p, 4, 3), None);
+    /// assert_eq!(Boundary::Toroidal.wrap(p, 4, 3), Some(Position::new(2, 1)));
+    /// assert_eq!(Boundary::Cylindrical.wrap(p, 4, 3), None);
+    /// ```
+    pub fn wrap(&self, p: Position, width: usize, height: usize) -> Option<Position> {
+        let (width, height) = (width as isize, height as isize);
+
+        match self {
+            Boundary::Bounded => {
+                if p.x >= 0 && p.x < height && p.y >= 0 && p.y < width {
+                    Some(p)
+                } else {
+                    None
                 }
             }
+            Boundary::Toroidal => {
+                Some(Position::new(p.x.rem_euclid(height), p.y.rem_euclid(width)))
+            }
+            Boundary::Cylindrical if p.x >= 0 && p.x < height => {
+                Some(Position::new(p.x, p.y.rem_euclid(width)))
+            }
+            Boundary::Cylindrical => None,
         }
+    }
 
-        // For each fitting tile we find, return the corresponding board
-        fitting_tiles
+    /// Determines whether the symmetry maps the edges of a board with this boundary onto
+    /// edges, which rules out exchanging the rows and columns of a cylinder
+    pub fn allows(&self, symmetry: Symmetry) -> bool {
+        *self != Boundary::Cylindrical || !symmetry.swaps_dimensions()
+    }
+}
+
+/// A region of cells which tiles can be placed on.
+///
+/// Every board is stored as a grid of cells on some `Lattice`, with the cells outside of the
+/// region marked.  `Tiler`, `BoardGraph` and the renderers are generic over boards, and
+/// only use the grid through the methods below, so a new kind of board only needs to say
+/// how its grid is stored and (if it differs) how tiles are placed on it.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::board::{Board, RectangularBoard, Symmetry};
+/// use dcc_tiler::tile::{Tile, TileCollection};
+///
+/// let board = RectangularBoard::new(2, 2);
+/// let tiles = TileCollection::from(Tile::domino());
+///
+/// // the two ways of covering the top left cell, and then the only way of finishing each
+/// let children = Board::place_tile(&board, &tiles);
+/// assert_eq!(children.len(), 2);
+/// assert!(children.iter().all(|child| !child.is_complete()));
+/// assert!(Board::place_tile(&children[0], &tiles)[0].is_complete());
+///
+/// // a vertical domino on the left is a horizontal domino at the top, turned around
+/// let turned = children[0].transform(Symmetry::ReflectDiagonal);
+/// assert!(children.contains(&turned));
+/// assert_ne!(children[0].canonical_hash(), children[1].canonical_hash());
+/// ```
+pub trait Board: Clone + Eq + Hash + Send + Sync {
+    /// Returns the grid of cells making up this board
+    fn grid(&self) -> &RectangularBoard;
+
+    /// Returns a board of the same kind as this one, made up of the given grid of cells
+    fn with_grid(&self, grid: RectangularBoard) -> Self;
+
+    /// Returns every board obtained by placing a tile from the collection over the first
+    /// unmarked cell of this board, see `RectangularBoard::place_tile`
+    fn place_tile(&self, tiles: &TileCollection) -> Vec<Self> {
+        self.grid()
+            .place_tile(tiles)
             .into_iter()
-            .map(|tp| {
-                let mut child_board = self.clone();
-                child_board.mark_tile_at_position(tp);
-                child_board
-            })
+            .map(|grid| self.with_grid(grid))
             .collect()
     }
 
-    fn is_marked(&self, p: Position) -> bool {
-        assert!(self.is_valid(p));
+    /// Like `place_tile`, but only makes the placements accepted by `filter`, see
+    /// `RectangularBoard::place_tile_filtered`
+    fn place_tile_filtered(
+        &self,
+        tiles: &TileCollection,
+        filter: &dyn Fn(&RectangularBoard, &TilePosition) -> bool,
+    ) -> Vec<Self> {
+        self.grid()
+            .place_tile_filtered(tiles, filter)
+            .into_iter()
+            .map(|grid| self.with_grid(grid))
+            .collect()
+    }
 
-        self.board[p.x as usize][p.y as usize]
+    /// Like `place_tile_filtered`, but places the tile over the cell chosen by `selection`,
+    /// see `RectangularBoard::place_tile_with_selection`
+    fn place_tile_with_selection(
+        &self,
+        tiles: &TileCollection,
+        selection: CellSelection,
+        filter: &dyn Fn(&RectangularBoard, &TilePosition) -> bool,
+    ) -> Vec<Self> {
+        self.grid()
+            .place_tile_with_selection(tiles, selection, filter)
+            .into_iter()
+            .map(|grid| self.with_grid(grid))
+            .collect()
     }
 
-    fn is_valid(&self, p: Position) -> bool {
-        p.x >= 0 && (p.x as usize) < self.height && p.y >= 0 && (p.y as usize) < self.width
+    /// Like `place_tile`, but breaks ties between equally constrained cells and orders the
+    /// placements at random, see `RectangularBoard::place_tile_with_rng`
+    fn place_tile_with_rng<R: Rng>(&self, tiles: &TileCollection, rng: &mut R) -> Vec<Self> {
+        self.grid()
+            .place_tile_with_rng(tiles, rng)
+            .into_iter()
+            .map(|grid| self.with_grid(grid))
+            .collect()
     }
 
-    fn move_in_direction(&self, p: Position, direction: Direction) -> Position {
-        let mut row = p.x;
-        let mut col = p.y;
+    /// Like `place_tile_with_rng`, but only makes the placements accepted by `filter`
+    fn place_tile_with_rng_filtered<R: Rng>(
+        &self,
+        tiles: &TileCollection,
+        rng: &mut R,
+        filter: &dyn Fn(&RectangularBoard, &TilePosition) -> bool,
+    ) -> Vec<Self> {
+        self.grid()
+            .place_tile_with_rng_filtered(tiles, rng, filter)
+            .into_iter()
+            .map(|grid| self.with_grid(grid))
+            .collect()
+    }
 
-        col += match direction {
-            Direction::Left => -1,
-            Direction::Right => 1,
-            Direction::UpLeft => -1,
-            Direction::UpRight => 1,
-            Direction::DownLeft => -1,
-            Direction::DownRight => 1,
-            _ => 0,
-        };
-        row += match direction {
-            Direction::Up => -1,
-            Direction::Down => 1,
-            Direction::UpLeft => -1,
-            Direction::UpRight => -1,
-            Direction::DownLeft => 1,
-            Direction::DownRight => 1,
-            _ => 0,
-        };
+    /// Determines whether every region of unmarked cells could still be covered by the
+    /// tiles, see `RectangularBoard::prune_check`
+    fn prune_check(&self, tiles: &TileCollection) -> bool {
+        self.grid().prune_check(tiles)
+    }
+
+    /// Determines whether every cell of this board has been covered
+    fn is_complete(&self) -> bool {
+        self.grid().is_all_marked()
+    }
+
+    /// Returns a hash of the cells covered so far and the tiles used, which only depends
+    /// on the compact key of the board (see `RectangularBoard::key`), so is the same from
+    /// one run to the next
+    fn canonical_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.grid().key().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns the image of this board under the given symmetry
+    fn transform(&self, symmetry: Symmetry) -> Self {
+        self.with_grid(self.grid().transformed(symmetry))
+    }
+}
+
+impl Board for RectangularBoard {
+    fn grid(&self) -> &RectangularBoard {
+        self
+    }
+
+    fn with_grid(&self, grid: RectangularBoard) -> Self {
+        grid
+    }
+}
+
+/// The symmetries of a rectangle (the dihedral group of order 8).  Rotations are clockwise,
+/// and reflections are in the named axis.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    ReflectHorizontal,
+    ReflectVertical,
+    ReflectDiagonal,
+    ReflectAntiDiagonal,
+}
 
-        Position::new(row, col)
+impl Symmetry {
+    /// Returns every symmetry, starting with the identity
+    pub fn all() -> [Symmetry; 8] {
+        [
+            Symmetry::Identity,
+            Symmetry::Rotate90,
+            Symmetry::Rotate180,
+            Symmetry::Rotate270,
+            Symmetry::ReflectHorizontal,
+            Symmetry::ReflectVertical,
+            Symmetry::ReflectDiagonal,
+            Symmetry::ReflectAntiDiagonal,
+        ]
     }
 
-    /// Tests whether the specified tile fits at the specified board position.
-    /// If it does, then return Some(TilePosition)
+    /// Determines whether this symmetry exchanges the width and height of a board
+    pub fn swaps_dimensions(&self) -> bool {
+        matches!(
+            self,
+            Symmetry::Rotate90
+                | Symmetry::Rotate270
+                | Symmetry::ReflectDiagonal
+                | Symmetry::ReflectAntiDiagonal
+        )
+    }
+
+    /// Returns the cell that the (row, column) cell of a `width x height` board is moved to
     ///
     /// # Examples
     ///
     /// ```
-    /// // Example code here
+    /// use dcc_tiler::board::Symmetry;
+    ///
+    /// // the top right corner of a 3 x 2 board is rotated to the bottom right corner
+    /// assert_eq!(Symmetry::Rotate90.apply((0, 2), 3, 2), (2, 1));
     /// ```
-    fn tile_fits_at_position(
-        &self,
-        tile: &Tile,
-        position: Position,
-        start_index: usize,
-    ) -> Option<TilePosition> {
-        // make sure our start index isn't too large
-        assert!(start_index <= tile.directions.len());
+    pub fn apply(&self, (row, col): (usize, usize), width: usize, height: usize) -> (usize, usize) {
+        match self {
+            Symmetry::Identity => (row, col),
+            Symmetry::Rotate90 => (col, height - 1 - row),
+            Symmetry::Rotate180 => (height - 1 - row, width - 1 - col),
+            Symmetry::Rotate270 => (width - 1 - col, row),
+            Symmetry::ReflectHorizontal => (height - 1 - row, col),
+            Symmetry::ReflectVertical => (row, width - 1 - col),
+            Symmetry::ReflectDiagonal => (col, row),
+            Symmetry::ReflectAntiDiagonal => (width - 1 - col, height - 1 - row),
+        }
+    }
+}
 
-        let mut current_position = position;
+/// The error returned when parsing a board from a text mask fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseBoardError {
+    /// The mask contains no cells
+    Empty,
 
-        let valid_and_unmarked = |p: Position| self.is_valid(p) && !self.is_marked(p);
+    /// The mask contains a character other than `.` or `#`
+    InvalidCharacter {
+        character: char,
+        row: usize,
+        col: usize,
+    },
 
-        if !valid_and_unmarked(current_position) {
-            return None;
+    /// A row of a serialized board has a different number of cells to the first row
+    UnevenRows { row: usize },
+}
+
+impl fmt::Display for ParseBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseBoardError::Empty => write!(f, "the board mask is empty"),
+            ParseBoardError::InvalidCharacter {
+                character,
+                row,
+                col,
+            } => write!(
+                f,
+                "invalid character '{}' at row {}, column {}",
+                character,
+                row + 1,
+                col + 1
+            ),
+            ParseBoardError::UnevenRows { row } => write!(
+                f,
+                "row {} of the board has a different number of cells to the first row",
+                row + 1
+            ),
         }
+    }
+}
 
-        let mut covered = HashSet::new();
-        covered.insert(current_position);
+impl Error for ParseBoardError {}
 
-        // move backwards from start_index - 1
-        for i in (0..start_index).rev() {
-            current_position =
-                self.move_in_direction(current_position, tile.directions[i].opposite());
+/// The serialized form of a `RectangularBoard`: just its cells (true for marked cells),
+/// and the lattice they lie on.  Everything else is worked out from the cells when the
+/// board is deserialized, except for the number of times each counted tile has been used
+/// and what happens at the edges of the board, so these boards always have bounded edges.
+#[derive(Deserialize)]
+struct SerializedBoard {
+    board: Vec<Vec<bool>>,
 
-            if !valid_and_unmarked(current_position) {
-                return None;
-            }
-            covered.insert(current_position);
-        }
+    #[serde(default)]
+    lattice: Lattice,
+}
 
-        let mut current_position = position;
+impl std::convert::TryFrom<SerializedBoard> for RectangularBoard {
+    type Error = ParseBoardError;
 
-        // now move forwards after the start index
-        for i in start_index..tile.directions.len() {
-            current_position = self.move_in_direction(current_position, tile.directions[i]);
+    fn try_from(serialized: SerializedBoard) -> Result<Self, Self::Error> {
+        RectangularBoard::from_cells(&serialized.board, serialized.lattice)
+    }
+}
 
-            if !valid_and_unmarked(current_position) {
-                return None;
-            }
+/// The unmarked cells of a board grouped by their number of marked (or out of bounds)
+/// neighbours, so that the most constrained cell can be found without looking through the
+/// whole board.  Each group is a bitset of cells, numbered by `RectangularBoard::cell_index`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+struct CountBuckets {
+    // the bitsets of each count, one after another
+    cells: Vec<u64>,
+    words: usize,
+    // the number of cells with each count
+    sizes: Vec<usize>,
+}
+
+impl CountBuckets {
+    /// Returns an empty set of groups, for the given number of cells with counts up to
+    /// `max_count`
+    fn new(cells: usize, max_count: usize) -> Self {
+        let words = cells.div_ceil(64);
 
-            covered.insert(current_position);
+        CountBuckets {
+            cells: vec![0; words * (max_count + 1)],
+            words,
+            sizes: vec![0; max_count + 1],
         }
+    }
 
-        Some(TilePosition::new(covered))
+    fn insert(&mut self, cell: usize, count: usize) {
+        self.cells[count * self.words + cell / 64] |= 1 << (cell % 64);
+        self.sizes[count] += 1;
     }
 
-    fn mark_tile_at_position(&mut self, tp: TilePosition) {
-        for position in tp.covered {
-            self.mark(position);
-        }
+    fn remove(&mut self, cell: usize, count: usize) {
+        self.cells[count * self.words + cell / 64] &= !(1 << (cell % 64));
+        self.sizes[count] -= 1;
+    }
+
+    /// Returns the number of cells in all of the groups
+    fn len(&self) -> usize {
+        self.sizes.iter().sum()
+    }
+
+    /// Returns the lowest numbered cell with the highest count, together with its count
+    fn highest(&self) -> Option<(usize, usize)> {
+        let count = (0..self.sizes.len()).rev().find(|&c| self.sizes[c] > 0)?;
+        let bucket = &self.cells[count * self.words..(count + 1) * self.words];
+
+        bucket
+            .iter()
+            .enumerate()
+            .find(|(_, word)| **word != 0)
+            .map(|(w, word)| (w * 64 + word.trailing_zeros() as usize, count))
+    }
+
+    fn estimated_memory(&self) -> usize {
+        self.cells.len() * std::mem::size_of::<u64>()
+            + self.sizes.len() * std::mem::size_of::<usize>()
     }
 }
 
-impl fmt::Debug for RectangularBoard {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut os = Vec::with_capacity((1 + self.width) * self.height);
+/// A compact key identifying the marked cells of a board, see `RectangularBoard::key`
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct BoardKey {
+    retired_rows: usize,
+    cells: Vec<u64>,
+    used: Vec<usize>,
+    uncovered: usize,
+}
 
-        for i in 0..self.height {
-            for j in 0..self.width {
-                os.push(if self.board[i][j] { "x" } else { "*" });
-            }
-            os.push("\n");
+impl BoardKey {
+    /// Returns an estimate of the number of bytes of memory used by this key
+    pub fn estimated_memory(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.cells.len() * std::mem::size_of::<u64>()
+            + self.used.len() * std::mem::size_of::<usize>()
+    }
+
+    fn is_marked(&self, i: usize, j: usize, width: usize) -> bool {
+        if i < self.retired_rows {
+            return true;
         }
 
-        write!(f, "{}", os.join(""))
+        let index = (i - self.retired_rows) * width + j;
+        self.cells[index / 64] & (1 << (index % 64)) != 0
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
-struct Position {
+/// A position on a board, which may lie outside of it.  `x` is the row and `y` is the column.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Position {
     x: isize,
     y: isize,
 }
@@ -318,10 +2440,21 @@ impl Position {
     pub fn new(x: isize, y: isize) -> Self {
         Position { x, y }
     }
+
+    /// Returns the row of this position
+    pub fn x(&self) -> isize {
+        self.x
+    }
+
+    /// Returns the column of this position
+    pub fn y(&self) -> isize {
+        self.y
+    }
 }
 
-#[derive(Eq, Clone)]
-struct TilePosition {
+/// The set of board positions covered by a tile placed on a board
+#[derive(Debug, Eq, Clone)]
+pub struct TilePosition {
     covered: HashSet<Position>,
 }
 
@@ -329,6 +2462,11 @@ impl TilePosition {
     pub fn new(covered: HashSet<Position>) -> Self {
         TilePosition { covered }
     }
+
+    /// Returns the positions covered by the tile
+    pub fn covered(&self) -> &HashSet<Position> {
+        &self.covered
+    }
 }
 
 impl PartialEq for TilePosition {
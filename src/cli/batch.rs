@@ -0,0 +1,279 @@
+//! The `batch` command, which runs many jobs described in a JSON file and writes their
+//! results to another JSON file.
+//!
+//! The jobs file holds a list of jobs, each taking the same options as the command line
+//! (with `-` replaced by `_`), for example
+//!
+//! ```json
+//! [
+//!     {"name": "dominoes", "board_type": "Rectangle", "board_size": 8, "tile_type": "Domino"},
+//!     {"board_type": "LBoard", "board_size": 2, "scale": 4, "tile": ["LTile:2"]},
+//!     {"board_file": "board.txt", "tile_type": "AllN", "tile_size": 5, "tile_count": 1, "mode": "exists"}
+//! ]
+//! ```
+
+use crate::{
+    build_board, interrupted, make_tile_collection, make_tiles, parse_custom_tile, parse_tile,
+    read_board_file, BoardShape, BoardType, TileType,
+};
+use clap::ValueEnum;
+use dcc_tiler::board::Boundary;
+use dcc_tiler::solver::Backend;
+use dcc_tiler::tile::TileSymmetry;
+use dcc_tiler::tiler::Tiler;
+use serde_derive::{Deserialize, Serialize};
+use std::io::{BufWriter, Result, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// What to find out about the tilings of a job's board
+#[derive(Debug, Copy, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Mode {
+    /// Count the tilings
+    #[default]
+    Count,
+
+    /// Count the tilings, treating tilings which are symmetric images of one another as one
+    CountUpToSymmetry,
+
+    /// Decide whether there are any tilings
+    Exists,
+}
+
+/// A board and tiles to tile it with, described like the command line options
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Job {
+    name: Option<String>,
+
+    board_type: Option<String>,
+    board_file: Option<PathBuf>,
+    #[serde(default)]
+    board_size: usize,
+    width: Option<usize>,
+    #[serde(default = "one")]
+    scale: usize,
+    #[serde(default)]
+    hole: Vec<(usize, usize)>,
+    #[serde(default)]
+    wrap: bool,
+    #[serde(default)]
+    wrap_x: bool,
+
+    tile_type: Option<String>,
+    #[serde(default)]
+    tile_size: usize,
+    #[serde(default)]
+    tile: Vec<String>,
+    #[serde(default)]
+    custom_tile: Vec<String>,
+    #[serde(default)]
+    one_sided: bool,
+    #[serde(default)]
+    fixed: bool,
+    tile_count: Option<usize>,
+
+    #[serde(default)]
+    mode: Mode,
+    #[serde(default)]
+    dlx: bool,
+}
+
+fn one() -> usize {
+    1
+}
+
+/// What a job found out about the tilings of its board, depending on its mode
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct Answer {
+    // tiling counts can be far too large for JSON numbers, so they're written as strings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    count: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exists: Option<bool>,
+}
+
+/// The outcome of a job, written to the results file
+#[derive(Debug, Serialize)]
+struct JobResult {
+    // the position of the job in the jobs file, counting from 0
+    job: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+
+    #[serde(flatten)]
+    answer: Answer,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+
+    wall_time: f64,
+}
+
+impl Job {
+    /// Builds a tiler for this job's board and tiles
+    pub(crate) fn tiler(&self) -> Result<Tiler> {
+        let invalid =
+            |message: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, message);
+
+        let symmetry = if self.one_sided {
+            TileSymmetry::RotationsOnly
+        } else if self.fixed {
+            TileSymmetry::None
+        } else {
+            TileSymmetry::All
+        };
+        let base_tiles = if self.tile.is_empty() && self.custom_tile.is_empty() {
+            let tile_type = self
+                .tile_type
+                .as_ref()
+                .ok_or_else(|| invalid("a job needs a tile_type, tile or custom_tile".into()))?;
+            let tile_type = TileType::from_str(tile_type, true).map_err(invalid)?;
+
+            make_tiles(tile_type, self.tile_size, symmetry).map_err(crate::invalid_input)?
+        } else {
+            self.tile
+                .iter()
+                .map(|s| parse_tile(s))
+                .chain(self.custom_tile.iter().map(|s| parse_custom_tile(s)))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(invalid)?
+        };
+        let tiles = make_tile_collection(&base_tiles, &[1], symmetry, self.tile_count)?;
+
+        let file_board = match &self.board_file {
+            Some(path) => Some(read_board_file(path)?),
+            None => None,
+        };
+        let shape = match (&file_board, &self.board_type) {
+            (Some(board), _) => BoardShape::File(board),
+            (None, Some(board_type)) => BoardShape::Type(
+                BoardType::from_str(board_type, true).map_err(invalid)?,
+                self.board_size,
+                self.width.unwrap_or(self.board_size),
+            ),
+            (None, None) => return Err(invalid("a job needs a board_type or board_file".into())),
+        };
+        let boundary = if self.wrap {
+            Boundary::Toroidal
+        } else if self.wrap_x {
+            Boundary::Cylindrical
+        } else {
+            Boundary::Bounded
+        };
+        let board = build_board(shape, &self.hole, self.scale, boundary)?;
+
+        let mut tiler = Tiler::new(tiles, board);
+        if self.dlx {
+            tiler = tiler.with_backend(Backend::Dlx);
+        }
+        if let Mode::CountUpToSymmetry = self.mode {
+            tiler = tiler.with_symmetry_reduction();
+        }
+
+        Ok(tiler)
+    }
+
+    /// Determines whether this job reads its board from a file
+    #[cfg(feature = "serve")]
+    pub(crate) fn reads_board_file(&self) -> bool {
+        self.board_file.is_some()
+    }
+
+    /// Finds out what this job's mode asks about the tilings of its board, failing with an
+    /// error of kind `TimedOut` if that takes longer than `time_limit`
+    pub(crate) fn answer(&self, time_limit: Option<Duration>) -> Result<Answer> {
+        let mut tiler = self.tiler()?;
+        if let Some(limit) = time_limit {
+            tiler = tiler.with_time_limit(limit);
+        }
+
+        Ok(match self.mode {
+            Mode::Count => Answer {
+                count: Some(tiler.try_count_tilings().map_err(interrupted)?.to_string()),
+                exists: None,
+            },
+            Mode::CountUpToSymmetry => Answer {
+                count: Some(
+                    tiler
+                        .try_count_tilings_up_to_symmetry()
+                        .map_err(interrupted)?
+                        .to_string(),
+                ),
+                exists: None,
+            },
+            Mode::Exists => Answer {
+                count: None,
+                exists: Some(tiler.try_exists_tiling().map_err(interrupted)?),
+            },
+        })
+    }
+
+    /// Runs this job, reporting any problem with it in the result rather than failing
+    fn run(&self, index: usize) -> JobResult {
+        let start = Instant::now();
+        let mut result = JobResult {
+            job: index,
+            name: self.name.clone(),
+            answer: Answer::default(),
+            error: None,
+            wall_time: 0.0,
+        };
+
+        match self.answer(None) {
+            Ok(answer) => result.answer = answer,
+            Err(e) => result.error = Some(e.to_string()),
+        }
+
+        result.wall_time = start.elapsed().as_secs_f64();
+        result
+    }
+}
+
+/// Runs the jobs in `jobs_file`, up to `parallel` at a time, printing a line for each as it
+/// finishes and writing all of their results (in the order of the jobs) to `output`
+pub fn run(jobs_file: &Path, output: &Path, parallel: usize) -> Result<()> {
+    let jobs: Vec<Job> = serde_json::from_slice(&std::fs::read(jobs_file)?)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<JobResult>>> = Mutex::new(jobs.iter().map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..parallel.clamp(1, jobs.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let job = match jobs.get(index) {
+                    Some(job) => job,
+                    None => break,
+                };
+
+                let result = job.run(index);
+                let name = job.name.clone().unwrap_or_else(|| format!("job {}", index));
+                match (&result.answer.count, result.answer.exists, &result.error) {
+                    (Some(count), _, _) => println!("{}: {} tilings found", name, count),
+                    (_, Some(true), _) => println!("{}: a tiling exists", name),
+                    (_, Some(false), _) => println!("{}: no tilings found", name),
+                    (_, _, error) => {
+                        println!("{}: failed, {}", name, error.as_deref().unwrap_or(""))
+                    }
+                }
+
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    let results: Vec<JobResult> = results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect();
+    let mut file = BufWriter::new(std::fs::File::create(output)?);
+    serde_json::to_writer_pretty(&mut file, &results)?;
+    writeln!(file)?;
+    file.flush()
+}
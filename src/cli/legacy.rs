@@ -0,0 +1,115 @@
+//! Support for invocations from before the command line was split into subcommands, where
+//! what to do was chosen by a flag, such as `dcc_tiler_cli --count 4 2`.  These are rewritten
+//! into the matching subcommand (`dcc_tiler_cli count 4 2`), with a warning.
+
+use std::ffi::OsString;
+
+/// The flags which used to choose what to do, and the subcommand each became.  Flags which
+/// took a value pass it on to the subcommand as the given option.
+const MODES: &[(&str, &str, Option<&str>)] = &[
+    ("--count", "count", None),
+    ("-c", "count", None),
+    ("--single", "single", None),
+    ("-s", "single", None),
+    ("--all", "all", Some("--output")),
+    ("-a", "all", Some("--output")),
+    ("--sheet", "render", Some("--output")),
+    ("--graph", "graph", None),
+    ("-g", "graph", None),
+    ("--scaling", "scaling", None),
+    ("--sweep", "sweep", Some("--max")),
+    ("--estimate", "estimate", None),
+    ("--exists", "exists", None),
+    ("--prove-impossible", "prove-impossible", None),
+    ("--sample-mcmc", "sample-mcmc", Some("--steps")),
+];
+
+/// The options which were renamed when they moved into a subcommand
+const RENAMED: &[(&str, &str)] = &[
+    ("--graph-format", "--format"),
+    ("--scaling-max", "--max"),
+    ("--scaling-output", "--output"),
+    ("--all-limit", "--limit"),
+    ("--all-sample", "--sample"),
+    ("--sheet-count", "--count"),
+    ("--sheet-columns", "--columns"),
+];
+
+/// Rewrites the command line arguments (including the program name) of an old invocation
+/// into the matching subcommand, leaving any other arguments unchanged
+pub fn rewrite_args(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let args: Vec<OsString> = args.into_iter().collect();
+
+    // new invocations start with a subcommand, and old ones with a flag or the board size
+    let old = match args.get(1).and_then(|arg| arg.to_str()) {
+        Some(first) => {
+            !["-h", "--help", "-V", "--version"].contains(&first)
+                && (first.starts_with('-') || first.parse::<usize>().is_ok())
+        }
+        None => false,
+    };
+    if !old {
+        return args;
+    }
+
+    let mut command = None;
+    let mut rewritten = Vec::with_capacity(args.len() + 1);
+    let mut rest = args.iter().skip(1);
+
+    while let Some(arg) = rest.next() {
+        let text = match arg.to_str() {
+            Some(text) => text,
+            None => {
+                rewritten.push(arg.clone());
+                continue;
+            }
+        };
+        if text == "--" {
+            rewritten.push(arg.clone());
+            rewritten.extend(rest.cloned());
+            break;
+        }
+
+        // options may be given as --name=value
+        let (name, value) = match text.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value)),
+            _ => (text, None),
+        };
+
+        match MODES.iter().find(|(flag, _, _)| *flag == name) {
+            Some((_, subcommand, option)) if command.is_none() => {
+                command = Some((name, *subcommand));
+
+                if let Some(option) = option {
+                    rewritten.push(OsString::from(option));
+                    match value {
+                        Some(value) => rewritten.push(OsString::from(value)),
+                        None => rewritten.extend(rest.next().cloned()),
+                    }
+                }
+            }
+            _ => match RENAMED.iter().find(|(old, _)| *old == name) {
+                Some((_, new)) => match value {
+                    Some(value) => rewritten.push(OsString::from(format!("{}={}", new, value))),
+                    None => rewritten.push(OsString::from(new)),
+                },
+                None => rewritten.push(arg.clone()),
+            },
+        }
+    }
+
+    match command {
+        Some((flag, subcommand)) => {
+            eprintln!(
+                "warning: {} is deprecated, use the {} subcommand instead",
+                flag, subcommand
+            );
+
+            let mut args = vec![args[0].clone(), OsString::from(subcommand)];
+            args.extend(rewritten);
+            args
+        }
+        // with nothing to do, let clap explain how the command line should look
+        None => args,
+    }
+}
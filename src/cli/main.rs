@@ -1,11 +1,36 @@
-use dcc_tiler::board::RectangularBoard;
-use dcc_tiler::tile::{Tile, TileCollection};
+mod batch;
+mod legacy;
+#[cfg(feature = "tui")]
+mod play;
+#[cfg(feature = "serve")]
+mod serve;
 
-use clap::{Parser, ValueEnum};
+use dcc_tiler::arith::{self, Arith};
+use dcc_tiler::board::{Boundary, CellSelection, RectangularBoard};
+use dcc_tiler::error::TilerError;
+use dcc_tiler::graph::BoardGraph;
+use dcc_tiler::sampler::glauber::GlauberSampler;
+use dcc_tiler::solver::{Backend, CancelToken};
+use dcc_tiler::tile::{
+    generate_polyominoes, PentominoKind, TetrominoKind, Tile, TileCollection, TileSymmetry,
+};
+use dcc_tiler::tiling::Tiling;
 
-use dcc_tiler::render::render_single_tiling_from_vec;
-use std::io::Result;
-use tiler::Tiler;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use indicatif::{ProgressBar, ProgressStyle};
+
+use dcc_tiler::render::{
+    render_single_tiling_from_vec, render_single_tiling_png, render_tiling_animation,
+    render_tiling_grid, render_tiling_grid_png, AnimationOptions, Coloring, ImageFormat, LabelKind,
+    RenderOptions,
+};
+use dcc_tiler::tiler::{Progress, SampleStrategy, SearchBudget, SearchStrategy, Target, Tiler};
+use num::BigUint;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::convert::TryFrom;
+use std::io::{BufWriter, Result, Write};
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Copy, Clone, ValueEnum)]
 #[value(rename_all = "PascalCase")]
@@ -13,6 +38,9 @@ pub enum BoardType {
     Rectangle,
     LBoard,
     TBoard,
+    AztecDiamond,
+    Staircase,
+    Cross,
 }
 
 #[derive(Debug, Copy, Clone, ValueEnum)]
@@ -21,27 +49,155 @@ pub enum TileType {
     LTile,
     TTile,
     BoxTile,
+    Domino,
+    ITetromino,
+    OTetromino,
+    TTetromino,
+    STetromino,
+    ZTetromino,
+    LTetromino,
+    JTetromino,
+    FPentomino,
+    IPentomino,
+    LPentomino,
+    NPentomino,
+    PPentomino,
+    TPentomino,
+    UPentomino,
+    VPentomino,
+    WPentomino,
+    XPentomino,
+    YPentomino,
+    ZPentomino,
+    AllN,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+#[value(rename_all = "lower")]
+pub enum BackendType {
+    Search,
+    Dlx,
+    #[cfg(feature = "ilp")]
+    Ilp,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum StrategyType {
+    Dfs,
+    Iddfs,
+    LeastFreedom,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum CellSelectionType {
+    MostConstrained,
+    Lexicographic,
+    FewestFits,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+#[value(rename_all = "lower")]
+pub enum ColoringType {
+    Cycle,
+    Shape,
+    Orientation,
+    Proper,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+#[value(rename_all = "lower")]
+pub enum LabelType {
+    None,
+    Order,
+    Shape,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+#[value(rename_all = "lower")]
+pub enum ArithType {
+    Big,
+    Auto,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+#[value(rename_all = "lower")]
+pub enum GraphFormat {
+    Json,
+    Dot,
+    Bin,
+    GraphMl,
+    Jsonl,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+#[value(rename_all = "lower")]
+pub enum OutputFormat {
+    Svg,
+    Png,
+    Text,
 }
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// The board to tile, and the tiles to tile it with
+#[derive(Args)]
+struct BoardArgs {
     #[arg(help = "The size of the board to tile")]
     board_size: usize,
 
     #[arg(help = "The size of the tile")]
     tile_size: usize,
 
-    #[arg(short, long, help = "The width of the board")]
+    #[arg(
+        short,
+        long,
+        help = "The width of the board (or the thickness of a Cross's arms)"
+    )]
     width: Option<usize>,
 
     #[arg(long, value_enum, default_value_t = BoardType::LBoard, help = "The type of board to use")]
     board_type: BoardType,
 
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "board_type",
+        help = "Read the board from a text file of '.' (cells to tile) and '#' (marked cells), ignoring the board size"
+    )]
+    board_file: Option<std::path::PathBuf>,
+
+    #[arg(
+        long = "hole",
+        value_name = "X,Y",
+        value_parser = parse_hole,
+        help = "Mark the cell in row X and column Y (counting from 0, before scaling the board) as unavailable - can be repeated"
+    )]
+    holes: Vec<(usize, usize)>,
+
+    #[arg(
+        long,
+        help = "Wrap the board around a torus, so that tiles can cross from each edge of the board to the opposite edge"
+    )]
+    wrap: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "wrap",
+        help = "Wrap the board around a cylinder, so that tiles can cross from the left edge of the board to the right edge"
+    )]
+    wrap_x: bool,
+
     #[arg(
         long = "scale",
         default_value_t = 1,
-        help = "The board scale ot use, if using an LBoard"
+        help = "Replace each cell of the board by a square of SCALE x SCALE cells"
     )]
     board_scale: usize,
 
@@ -49,118 +205,1788 @@ struct Cli {
     tile_type: TileType,
 
     #[arg(
-        short,
+        long = "tile",
+        value_name = "TILE_TYPE[:SIZE]",
+        value_parser = parse_tile,
+        conflicts_with = "tile_type",
+        help = "Tile using this tile (e.g. LTile:2), can be given more than once to mix tiles"
+    )]
+    tiles: Vec<Tile>,
+
+    #[arg(
+        long = "custom-tile",
+        value_name = "DIRECTIONS",
+        conflicts_with = "tile_type",
+        value_parser = parse_custom_tile,
+        help = "Use a custom tile, given as a comma separated list of directions (e.g. L,U,U,R) which doesn't revisit a cell"
+    )]
+    custom_tiles: Vec<Tile>,
+
+    #[arg(
         long,
-        help = "Compute a single tiling",
-        conflicts_with = "count",
-        conflicts_with = "graph"
+        help = "Only use rotations of each tile, not reflections (one-sided polyominoes)"
     )]
-    single: bool,
+    one_sided: bool,
 
     #[arg(
-        short,
         long,
-        help = "Render all tilings to a specified file in ZIP format",
-        conflicts_with = "single",
-        conflicts_with = "count",
-        conflicts_with = "graph",
-        conflicts_with = "scaling"
+        conflicts_with = "one_sided",
+        help = "Only use each tile exactly as given, without rotating or reflecting it"
     )]
-    all: Option<String>,
+    fixed: bool,
 
     #[arg(
-        short,
         long,
-        help = "Count all tilings",
-        conflicts_with = "single",
-        conflicts_with = "graph"
+        value_name = "N",
+        help = "Use each tile (in any orientation, and at each scale) at most N times"
     )]
-    count: bool,
+    tile_count: Option<usize>,
+
+    #[arg(
+        long = "tile-scale",
+        value_delimiter = ',',
+        help = "Include copies of the tile inflated by these scales (e.g. 1,2)"
+    )]
+    tile_scales: Vec<usize>,
+}
+
+/// How tilings are searched for and counted
+#[derive(Args)]
+struct SearchArgs {
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = BackendType::Search,
+        help = "The algorithm used to count, search for, and render tilings"
+    )]
+    backend: BackendType,
 
     #[arg(
-        short,
         long,
-        help = "Compute the full tilings graph",
-        conflicts_with = "count",
-        conflicts_with = "single"
+        value_enum,
+        default_value_t = ArithType::Big,
+        help = "The arithmetic used to count tilings (auto uses 128-bit integers until a count overflows)"
     )]
-    graph: bool,
+    arith: ArithType,
 
     #[arg(
         long,
-        help = "Compute the tiling count for different value of the scale parameter",
-        conflicts_with = "graph",
-        conflicts_with = "count",
-        conflicts_with = "single"
+        value_enum,
+        default_value_t = CellSelectionType::MostConstrained,
+        help = "How the search chooses the cell to cover next (fewest-fits tries every placement to find the cell with the fewest)"
     )]
-    scaling: bool,
+    cell_selection: CellSelectionType,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Generate the tiling graph and render tilings using this many threads (defaults to one per CPU)"
+    )]
+    threads: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Count (or render) tilings which are rotations or reflections of one another only once"
+    )]
+    up_to_symmetry: bool,
+
+    #[arg(
+        long,
+        help = "Only count, render or search for tilings with no fault lines - straight lines across the board which no tile crosses"
+    )]
+    fault_free: bool,
+
+    #[arg(
+        long,
+        value_name = "CELLS",
+        help = "Count, render or search for tilings covering at least this many cells, leaving the rest uncovered"
+    )]
+    cover_at_least: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "SEED",
+        help = "Seed the random choices of tiling and tile colours, so that output can be reproduced"
+    )]
+    seed: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Show a progress bar while counting tilings or generating the tiling graph"
+    )]
+    progress: bool,
+
+    #[arg(
+        long,
+        help = "Print statistics on the boards explored to stderr after counting tilings or generating the tiling graph"
+    )]
+    stats: bool,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        help = "Switch to a slower, memory-lean counting strategy before using this much memory"
+    )]
+    max_memory: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        help = "Move the frontier of breadth first counts into temporary files before it uses this much memory"
+    )]
+    memory_limit: Option<usize>,
 }
 
-mod tiler;
+/// How tilings are drawn
+#[derive(Args)]
+struct RenderArgs {
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Svg,
+        help = "The format to output tilings in (text is only supported for a single tiling)"
+    )]
+    format: OutputFormat,
 
-fn main() -> Result<()> {
-    let cli: Cli = Cli::parse();
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        help = "The side length of each cell in rendered tilings"
+    )]
+    cell_size: Option<f32>,
 
-    let board_width = cli.width.unwrap_or(cli.board_size);
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        conflicts_with = "cell_size",
+        help = "Scale rendered tilings to this width, choosing the cell size to suit the board"
+    )]
+    target_width: Option<f32>,
 
-    // Create a colletion of tiles based on the tile(s) specified by the user
-    let tile = match cli.tile_type {
-        TileType::LTile => Tile::l_tile(cli.tile_size),
-        TileType::TTile => Tile::t_tile(cli.tile_size),
-        TileType::BoxTile => Tile::box_tile(),
-    };
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        help = "The space between adjacent cells in rendered tilings"
+    )]
+    gap: Option<f32>,
 
-    let tiles = TileCollection::from(tile);
+    #[arg(
+        long,
+        value_name = "COLORS",
+        value_delimiter = ',',
+        value_parser = parse_color,
+        help = "The colours of tiles in rendered tilings, as comma separated hex codes (e.g. #1e3888,#f5e663)"
+    )]
+    palette: Vec<(u8, u8, u8)>,
 
-    // A closure to create a board based on specified options
-    let make_board =
-        |board_type: BoardType, board_size: usize, board_width: usize, board_scale: usize| {
-            match board_type {
-                BoardType::Rectangle => RectangularBoard::new(board_width, board_size),
-                BoardType::LBoard => RectangularBoard::l_board(board_size, board_scale),
-                BoardType::TBoard => RectangularBoard::t_board(board_size, board_scale),
-            }
-        };
+    #[arg(
+        long,
+        value_name = "COLOR",
+        value_parser = parse_color,
+        help = "Fill the background of rendered tilings with this hex colour"
+    )]
+    background: Option<(u8, u8, u8)>,
 
-    if cli.scaling {
-        // we deal with scaling separately to appease the borrow checker
-        let mut board_scale: usize = 1;
+    #[arg(
+        long,
+        value_name = "COLOR",
+        value_parser = parse_color,
+        num_args = 0..=1,
+        default_missing_value = "#e0e0e0",
+        help = "Outline the board in rendered tilings, shading cells marked on the initial board in this hex colour (light grey by default)"
+    )]
+    show_board: Option<(u8, u8, u8)>,
 
-        loop {
-            let mut tiler = Tiler::new(
-                tiles.clone(),
-                make_board(cli.board_type, cli.board_size, board_width, board_scale),
-            );
-            println!("scale({}), {} tilings", board_scale, tiler.count_tilings());
-            board_scale += 1;
-        }
-    } else {
-        let board = make_board(cli.board_type, cli.board_size, board_width, cli.board_scale);
-        let mut tiler = Tiler::new(tiles, board);
+    #[arg(
+        long,
+        help = "Draw each tile of rendered tilings as a single outlined shape, giving much smaller SVGs"
+    )]
+    outlines: bool,
 
-        if cli.count {
-            // just do a quick tilings count - no need to generate the tiling graph
-            println!("{} tilings found", tiler.count_tilings());
-        } else if cli.single {
-            let tiling = tiler.get_single_tiling(1000);
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ColoringType::Cycle,
+        help = "How tiles are coloured: cycle through the palette, by shape, by shape and orientation, or so that neighbouring tiles differ (proper)"
+    )]
+    coloring: ColoringType,
 
-            if let Some(tiling) = tiling {
-                println!("{}", render_single_tiling_from_vec(tiling.iter().collect()));
-            } else {
-                println!("No tilings found!");
-            }
-        } else if let Some(filename) = cli.all {
-            tiler.render_all_tilings(&filename)?;
-        } else if cli.graph {
-            let board_graph = tiler.graph();
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = LabelType::None,
+        help = "Label each tile of rendered tilings with the order it was placed in, or with its shape and orientation"
+    )]
+    labels: LabelType,
+}
 
-            {
-                let board_graph = board_graph.read().unwrap();
+#[derive(Args)]
+struct CountArgs {
+    #[command(flatten)]
+    board: BoardArgs,
 
-                println!("{}", serde_json::to_string(&*board_graph).unwrap());
-            }
-        }
-    }
+    #[command(flatten)]
+    search: SearchArgs,
 
-    Ok(())
+    #[arg(
+        long,
+        conflicts_with = "up_to_symmetry",
+        conflicts_with = "max_memory",
+        conflicts_with = "memory_limit",
+        help = "Count tilings using a depth first enumeration, which uses far less memory but is slower"
+    )]
+    dfs: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "dfs",
+        conflicts_with = "up_to_symmetry",
+        help = "Count tilings using broken-profile dynamic programming, which handles long boards well"
+    )]
+    profile: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "dfs",
+        conflicts_with = "profile",
+        conflicts_with = "up_to_symmetry",
+        conflicts_with = "by_orientation",
+        conflicts_with = "by_uncovered",
+        conflicts_with = "checkpoint",
+        conflicts_with = "resume",
+        help = "Count tilings by counting the separate regions that placed tiles cut the board into independently"
+    )]
+    split_regions: bool,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        requires = "split_regions",
+        help = "Save the counts of regions counted with --split-regions in this directory, and reuse them in later runs"
+    )]
+    cache_dir: Option<std::path::PathBuf>,
+
+    #[arg(
+        long,
+        conflicts_with = "dfs",
+        conflicts_with = "profile",
+        conflicts_with = "up_to_symmetry",
+        help = "Count tilings separately by the number of tiles they use in each orientation"
+    )]
+    by_orientation: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "dfs",
+        conflicts_with = "profile",
+        conflicts_with = "up_to_symmetry",
+        conflicts_with = "by_orientation",
+        help = "Count tilings separately by the number of cells they leave uncovered (see --cover-at-least)"
+    )]
+    by_uncovered: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "dfs",
+        conflicts_with = "profile",
+        conflicts_with = "up_to_symmetry",
+        help = "Save the progress of the count to this file after each layer of placements"
+    )]
+    checkpoint: Option<std::path::PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "dfs",
+        conflicts_with = "profile",
+        conflicts_with = "up_to_symmetry",
+        help = "Continue a count from a checkpoint file saved with --checkpoint"
+    )]
+    resume: Option<std::path::PathBuf>,
+
+    #[arg(
+        long,
+        conflicts_with = "dfs",
+        conflicts_with = "progress",
+        help = "Print the number of tilings found so far after each layer of placements"
+    )]
+    live: bool,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        requires = "dfs",
+        help = "The approximate amount of memory the depth first enumeration may use for its stack"
+    )]
+    stack_memory: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        conflicts_with = "dfs",
+        conflicts_with = "profile",
+        conflicts_with = "up_to_symmetry",
+        conflicts_with = "split_regions",
+        conflicts_with = "by_orientation",
+        conflicts_with = "by_uncovered",
+        help = "Stop the count after this long (e.g. 90s, 5m or 2h), printing the number of tilings found so far"
+    )]
+    timeout: Option<Duration>,
+}
+
+#[derive(Args)]
+struct SingleArgs {
+    #[command(flatten)]
+    board: BoardArgs,
+
+    #[command(flatten)]
+    search: SearchArgs,
+
+    #[command(flatten)]
+    render: RenderArgs,
+
+    #[arg(
+        long,
+        help = "Search for a single tiling using this many parallel randomized workers, taking the first tiling any of them finds (which can differ between runs, even with --seed)"
+    )]
+    workers: Option<usize>,
+
+    #[arg(
+        long,
+        conflicts_with = "workers",
+        help = "Search for a single tiling using a best-first search with lookahead"
+    )]
+    best_first: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "workers",
+        conflicts_with = "best_first",
+        help = "Choose the tiling uniformly at random from all tilings, using the tiling graph"
+    )]
+    uniform: bool,
+
+    #[arg(
+        long,
+        default_value_t = 1,
+        conflicts_with = "workers",
+        help = "Collect up to this many tilings and render one of them at random"
+    )]
+    max_solutions: usize,
+
+    #[arg(
+        long,
+        conflicts_with = "workers",
+        help = "Give up the search after exploring this many boards"
+    )]
+    max_nodes: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        conflicts_with = "workers",
+        help = "Give up the search after this many seconds"
+    )]
+    max_time: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "SEED",
+        help = "Break ties between equally constrained cells at random, using this seed"
+    )]
+    tie_break_seed: Option<u64>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = StrategyType::Dfs,
+        conflicts_with = "workers",
+        conflicts_with = "best_first",
+        conflicts_with = "uniform",
+        help = "The order in which to try placing tiles when searching for a tiling"
+    )]
+    strategy: StrategyType,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write an animated SVG of the tiling being built, one tile at a time, to this file"
+    )]
+    animate: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        requires = "animate",
+        help = "The time between placing one tile and the next in the animation"
+    )]
+    animate_step: Option<f32>,
+}
+
+#[derive(Args)]
+struct AllArgs {
+    #[command(flatten)]
+    board: BoardArgs,
+
+    #[command(flatten)]
+    search: SearchArgs,
+
+    #[command(flatten)]
+    render: RenderArgs,
+
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        help = "The ZIP file to render the tilings into"
+    )]
+    output: String,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Render at most N tilings (without --sample, the first N found, without generating the tiling graph)"
+    )]
+    limit: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "STRATEGY",
+        value_parser = parse_sample_strategy,
+        help = "Only render some tilings: first:N for the first N, random:N for N chosen uniformly at random, or every:K for every Kth tiling"
+    )]
+    sample: Option<SampleStrategy>,
+}
+
+#[derive(Args)]
+struct SheetArgs {
+    #[command(flatten)]
+    board: BoardArgs,
+
+    #[command(flatten)]
+    search: SearchArgs,
+
+    #[command(flatten)]
+    render: RenderArgs,
+
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        help = "The file to render the contact sheet into"
+    )]
+    output: String,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 24,
+        help = "The number of tilings on the contact sheet"
+    )]
+    count: usize,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "The number of tilings in each row of the contact sheet (by default the sheet is roughly square)"
+    )]
+    columns: Option<usize>,
+}
+
+#[derive(Args)]
+struct GraphArgs {
+    #[command(flatten)]
+    board: BoardArgs,
+
+    #[command(flatten)]
+    search: SearchArgs,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = GraphFormat::Json,
+        help = "The format to output the tilings graph in"
+    )]
+    format: GraphFormat,
+
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        help = "Give up generating the graph after this long (e.g. 90s, 5m or 2h)"
+    )]
+    timeout: Option<Duration>,
+}
+
+#[derive(Args)]
+struct ScalingArgs {
+    #[command(flatten)]
+    board: BoardArgs,
+
+    #[command(flatten)]
+    search: SearchArgs,
+
+    #[arg(
+        long,
+        value_name = "MAX",
+        help = "Stop once this scale (or board size, with sweep) has been counted"
+    )]
+    max: Option<usize>,
+
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        help = "Also write the scale (or board size), tiling count and wall time (in seconds) of each count to this CSV file"
+    )]
+    output: Option<std::path::PathBuf>,
+
+    #[arg(
+        long,
+        conflicts_with = "up_to_symmetry",
+        help = "Count tilings by counting the separate regions that placed tiles cut the board into independently"
+    )]
+    split_regions: bool,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        requires = "split_regions",
+        help = "Save the counts of regions counted with --split-regions in this directory, and reuse them in later runs"
+    )]
+    cache_dir: Option<std::path::PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "SAMPLES",
+        value_parser = clap::value_parser!(u64).range(2..),
+        conflicts_with = "up_to_symmetry",
+        conflicts_with = "split_regions",
+        help = "Estimate each count from this many random samples, rather than counting exactly"
+    )]
+    estimate: Option<u64>,
+}
+
+#[derive(Args)]
+struct SampleArgs {
+    #[command(flatten)]
+    board: BoardArgs,
+
+    #[command(flatten)]
+    search: SearchArgs,
+
+    #[command(flatten)]
+    render: RenderArgs,
+
+    #[arg(
+        long,
+        help = "The number of random 2x2 flips to make, starting from any tiling"
+    )]
+    steps: usize,
+}
+
+/// Creates a tile of the given type and size
+/// Returns the tiles of the given type, which is a single tile for every type except `AllN`.
+/// Every polyomino of `tile_size` cells is a different tile under the given symmetry.
+fn make_tiles(
+    tile_type: TileType,
+    tile_size: usize,
+    symmetry: TileSymmetry,
+) -> std::result::Result<Vec<Tile>, TilerError> {
+    let tile = match tile_type {
+        TileType::AllN => {
+            if tile_size == 0 {
+                return Err(TilerError::ZeroSize("tile size"));
+            }
+
+            return Ok(generate_polyominoes(tile_size, symmetry)
+                .iter()
+                .map(|polyomino| Tile::try_from(polyomino).expect("polyominoes are connected"))
+                .collect());
+        }
+        TileType::LTile => Tile::try_l_tile(tile_size)?,
+        TileType::TTile => Tile::try_t_tile(tile_size)?,
+        TileType::BoxTile => Tile::box_tile(),
+        TileType::Domino => Tile::domino(),
+        TileType::ITetromino => Tile::tetromino(TetrominoKind::I),
+        TileType::OTetromino => Tile::tetromino(TetrominoKind::O),
+        TileType::TTetromino => Tile::tetromino(TetrominoKind::T),
+        TileType::STetromino => Tile::tetromino(TetrominoKind::S),
+        TileType::ZTetromino => Tile::tetromino(TetrominoKind::Z),
+        TileType::LTetromino => Tile::tetromino(TetrominoKind::L),
+        TileType::JTetromino => Tile::tetromino(TetrominoKind::J),
+        TileType::FPentomino => Tile::pentomino(PentominoKind::F),
+        TileType::IPentomino => Tile::pentomino(PentominoKind::I),
+        TileType::LPentomino => Tile::pentomino(PentominoKind::L),
+        TileType::NPentomino => Tile::pentomino(PentominoKind::N),
+        TileType::PPentomino => Tile::pentomino(PentominoKind::P),
+        TileType::TPentomino => Tile::pentomino(PentominoKind::T),
+        TileType::UPentomino => Tile::pentomino(PentominoKind::U),
+        TileType::VPentomino => Tile::pentomino(PentominoKind::V),
+        TileType::WPentomino => Tile::pentomino(PentominoKind::W),
+        TileType::XPentomino => Tile::pentomino(PentominoKind::X),
+        TileType::YPentomino => Tile::pentomino(PentominoKind::Y),
+        TileType::ZPentomino => Tile::pentomino(PentominoKind::Z),
+    };
+
+    Ok(vec![tile])
+}
+
+/// Parses a tile of the form `TILE_TYPE[:SIZE]`, e.g. `LTile:2` or `XPentomino`
+fn parse_tile(s: &str) -> std::result::Result<Tile, String> {
+    let (name, size) = match s.split_once(':') {
+        Some((name, size)) => (
+            name,
+            Some(size.parse::<usize>().map_err(|e| e.to_string())?),
+        ),
+        None => (s, None),
+    };
+
+    let tile_type = TileType::from_str(name, true)?;
+
+    match (tile_type, size) {
+        (TileType::LTile, None) | (TileType::TTile, None) => {
+            Err(format!("{} requires a size, e.g. {}:2", name, name))
+        }
+        (TileType::AllN, _) => Err(format!("{} can only be used with --tile-type", name)),
+        (tile_type, size) => make_tiles(tile_type, size.unwrap_or(0), TileSymmetry::All)
+            .map(|mut tiles| tiles.remove(0))
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Parses a custom tile given as a comma separated list of directions, e.g. `L,U,U,R`,
+/// rejecting walks which revisit a cell
+fn parse_custom_tile(s: &str) -> std::result::Result<Tile, String> {
+    let tile = Tile::parse(s).map_err(|e| e.to_string())?;
+
+    Tile::try_new(tile.directions).map_err(|e| e.to_string())
+}
+
+/// Prints partial tiling counts to stderr as they are found
+struct LiveProgress;
+
+impl Progress for LiveProgress {
+    fn on_level(&self, depth: usize, frontier_size: usize, counted_so_far: &BigUint) {
+        eprintln!(
+            "layer {}: {} boards in frontier, {} tilings found so far",
+            depth, frontier_size, counted_so_far
+        );
+    }
+}
+
+/// Shows a progress bar on stderr, advancing by one step per layer of placements
+struct BarProgress {
+    bar: ProgressBar,
+}
+
+impl BarProgress {
+    fn new() -> Self {
+        let bar = ProgressBar::new(0);
+        bar.set_style(
+            ProgressStyle::with_template(
+                "{spinner} [{elapsed_precise}] {bar:40} {pos}/{len} layers, {msg}",
+            )
+            .unwrap(),
+        );
+
+        BarProgress { bar }
+    }
+}
+
+impl Progress for BarProgress {
+    fn on_start(&self, max_depth: usize) {
+        self.bar.set_length(max_depth as u64);
+        self.bar.set_position(0);
+    }
+
+    fn on_level(&self, depth: usize, frontier_size: usize, counted_so_far: &BigUint) {
+        self.bar.set_position(depth as u64);
+        self.bar.set_message(format!(
+            "{} boards in frontier, {} tilings found so far",
+            frontier_size, counted_so_far
+        ));
+    }
+
+    fn on_graph_level(&self, depth: usize, frontier_size: usize, nodes: usize) {
+        self.bar.set_position(depth as u64);
+        self.bar.set_message(format!(
+            "{} boards in frontier, {} nodes in graph",
+            frontier_size, nodes
+        ));
+    }
+
+    fn on_finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+/// What to do: find out about the tilings of a board, work with a tiling graph saved by
+/// `graph` without generating it again, or run a batch of jobs
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Count the tilings of a board")]
+    Count(CountArgs),
+
+    #[command(about = "Find a single tiling of a board, and write it to stdout")]
+    Single(SingleArgs),
+
+    #[command(about = "Render the tilings of a board into a ZIP file")]
+    All(AllArgs),
+
+    #[command(
+        about = "Render a contact sheet of the first tilings found, side by side in one image"
+    )]
+    Render(SheetArgs),
+
+    #[command(about = "Compute the full tilings graph, and write it to stdout")]
+    Graph(GraphArgs),
+
+    #[command(about = "Count the tilings of a board at scales 1, 2, 3, ...")]
+    Scaling(ScalingArgs),
+
+    #[command(
+        about = "Count the tilings of every board size from board_size upwards, keeping the tile fixed"
+    )]
+    Sweep(ScalingArgs),
+
+    #[command(
+        about = "Estimate the number of tilings of a board far too large to count, by sampling random placements"
+    )]
+    Estimate {
+        #[command(flatten)]
+        board: BoardArgs,
+
+        #[command(flatten)]
+        search: SearchArgs,
+
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 10000,
+            value_parser = clap::value_parser!(u64).range(2..),
+            help = "The number of random samples to take (more give a smaller standard error)"
+        )]
+        samples: u64,
+    },
+
+    #[command(about = "Determine whether at least one tiling exists")]
+    Exists {
+        #[command(flatten)]
+        board: BoardArgs,
+
+        #[command(flatten)]
+        search: SearchArgs,
+    },
+
+    #[command(
+        about = "Find a placement of tiles leaving as few cells uncovered as possible, and write it to stdout"
+    )]
+    Pack {
+        #[command(flatten)]
+        board: BoardArgs,
+
+        #[command(flatten)]
+        search: SearchArgs,
+
+        #[command(flatten)]
+        render: RenderArgs,
+    },
+
+    #[cfg(feature = "tui")]
+    #[command(
+        about = "Tile a board by hand in the terminal, with hints and counts from the solver"
+    )]
+    Play {
+        #[command(flatten)]
+        board: BoardArgs,
+
+        #[command(flatten)]
+        search: SearchArgs,
+    },
+
+    #[command(
+        name = "prove-impossible",
+        about = "Search for a colouring of the board which proves that it can't be tiled"
+    )]
+    ProveImpossible {
+        #[command(flatten)]
+        board: BoardArgs,
+
+        #[command(flatten)]
+        search: SearchArgs,
+    },
+
+    #[command(
+        name = "sample-mcmc",
+        about = "Sample a random domino tiling by making random 2x2 flips, starting from any tiling"
+    )]
+    SampleMcmc(SampleArgs),
+
+    #[command(
+        name = "graph-count",
+        about = "Count the tilings in a tiling graph saved by the graph command"
+    )]
+    GraphCount {
+        #[arg(help = "The file holding the tiling graph, in the JSON or bin format")]
+        file: std::path::PathBuf,
+    },
+
+    #[command(
+        name = "graph-render",
+        about = "Render the tilings in a tiling graph saved by the graph command into a ZIP file"
+    )]
+    GraphRender {
+        #[arg(help = "The file holding the tiling graph, in the JSON or bin format")]
+        file: std::path::PathBuf,
+
+        #[arg(help = "The ZIP file to render the tilings into")]
+        output: String,
+
+        #[arg(
+            long,
+            value_name = "STRATEGY",
+            value_parser = parse_sample_strategy,
+            help = "Only render some tilings: first:N for the first N, random:N for N chosen uniformly at random, or every:K for every Kth tiling"
+        )]
+        sample: Option<SampleStrategy>,
+
+        #[arg(long, value_name = "N", help = "Render at most N tilings")]
+        limit: Option<usize>,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = OutputFormat::Svg,
+            help = "The format to render tilings in (svg or png)"
+        )]
+        format: OutputFormat,
+    },
+
+    #[command(
+        name = "graph-stats",
+        about = "Print statistics about a tiling graph saved by the graph command"
+    )]
+    GraphStats {
+        #[arg(help = "The file holding the tiling graph, in the JSON or bin format")]
+        file: std::path::PathBuf,
+    },
+
+    #[command(
+        name = "batch",
+        about = "Run the counts described in a JSON file of jobs, writing their results to another JSON file"
+    )]
+    Batch {
+        #[arg(
+            help = "The JSON file holding a list of jobs, each taking the same options as the command line"
+        )]
+        jobs: std::path::PathBuf,
+
+        #[arg(help = "The JSON file to write the result of each job to")]
+        output: std::path::PathBuf,
+
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 1,
+            help = "Run up to N jobs at once"
+        )]
+        parallel: usize,
+    },
+
+    #[cfg(feature = "serve")]
+    #[command(
+        about = "Answer HTTP requests to count, render or graph the tilings of boards described like the jobs of batch"
+    )]
+    Serve {
+        #[arg(
+            long,
+            value_name = "ADDRESS",
+            default_value = "127.0.0.1:8080",
+            help = "The address to listen on"
+        )]
+        address: String,
+
+        #[arg(
+            long,
+            value_name = "DURATION",
+            default_value = "60s",
+            value_parser = parse_duration,
+            help = "Give up on any request which runs for longer than this (e.g. 90s, 5m or 2h), answering it with a 503"
+        )]
+        timeout: Duration,
+    },
+}
+
+/// Reads a tiling graph saved by the `graph` command, in either the JSON or the compact format
+fn load_graph(path: &std::path::Path) -> Result<BoardGraph> {
+    let bytes = std::fs::read(path)?;
+
+    // the JSON format is always an object
+    if bytes.first() == Some(&b'{') {
+        BoardGraph::from_reader(bytes.as_slice())
+    } else {
+        BoardGraph::from_compact_reader(bytes.as_slice())
+    }
+}
+
+/// Returns a tiler for the board at the root of a tiling graph, which uses the graph
+/// rather than generating it again
+fn graph_tiler(graph: BoardGraph) -> Result<Tiler> {
+    let initial = graph.get_node(0).cloned().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, "the tiling graph is empty")
+    })?;
+
+    Ok(Tiler::new(TileCollection::new(Vec::new()), initial).with_graph(graph))
+}
+
+/// Runs a command
+fn run_command(command: Command) -> Result<()> {
+    match command {
+        Command::Count(args) => run_count(args)?,
+        Command::Single(args) => run_single(args)?,
+        Command::All(args) => run_all(args)?,
+        Command::Render(args) => run_sheet(args)?,
+        Command::Graph(args) => run_graph(args)?,
+        Command::Scaling(args) => run_scaling(args, false)?,
+        Command::Sweep(args) => run_scaling(args, true)?,
+        Command::Estimate {
+            board,
+            search,
+            samples,
+        } => {
+            let initial = board.board()?;
+            let area = initial.unmarked_count() as f64;
+            let tiler = search.tiler(&board, initial)?;
+            let estimate = tiler.estimate_tilings(samples as usize);
+
+            println!(
+                "About {} tilings ({} of {} samples completed a tiling)",
+                estimate,
+                estimate.completed(),
+                estimate.samples()
+            );
+            if estimate.upper_bound().is_finite() {
+                println!(
+                    "Approximate 95% confidence interval: {:.4e} to {:.4e}",
+                    estimate.lower_bound(),
+                    estimate.upper_bound()
+                );
+            }
+            println!(
+                "Estimated entropy per cell: {:.6}",
+                estimate.ln_estimate() / area
+            );
+        }
+        Command::Exists { board, search } => {
+            let tiler = search.tiler(&board, board.board()?)?;
+            if tiler.exists_tiling() {
+                println!("A tiling exists");
+            } else {
+                println!("No tilings found!");
+            }
+            search.print_stats(&tiler);
+        }
+        Command::Pack {
+            board,
+            search,
+            render,
+        } => {
+            let tiler = search.tiler(&board, board.board()?)?;
+            let (tiling, uncovered) = tiler.max_packing();
+
+            eprintln!("{} cells left uncovered", uncovered);
+            print_tiling(&tiling, render.format, &render.options(search.seed))?;
+            search.print_stats(&tiler);
+        }
+        #[cfg(feature = "tui")]
+        Command::Play { board, search } => play::run(&board, &search)?,
+        Command::ProveImpossible { board, search } => {
+            let tiler = search.tiler(&board, board.board()?)?;
+            match tiler.prove_impossible() {
+                Some(coloring) => {
+                    println!(
+                        "No tilings exist: the cells covered by any tile have weights summing to a \
+                         multiple of {}, but the weights of the whole board sum to {} (mod {})",
+                        coloring.modulus(),
+                        coloring.total(),
+                        coloring.modulus()
+                    );
+                    print!("{}", coloring);
+                }
+                None => println!("No colouring proving that the board can't be tiled was found"),
+            }
+        }
+        Command::SampleMcmc(args) => run_sample(args)?,
+        Command::GraphCount { file } => {
+            let mut tiler = graph_tiler(load_graph(&file)?)?;
+            println!("{} tilings found", tiler.count_tilings());
+        }
+        Command::GraphRender {
+            file,
+            output,
+            sample,
+            limit,
+            format,
+        } => {
+            let mut tiler = graph_tiler(load_graph(&file)?)?
+                .with_image_format(image_format(format, "graph-render")?);
+
+            tiler.render_all_tilings_sampled(
+                &output,
+                limit,
+                sample.unwrap_or(SampleStrategy::EveryKth(1)),
+            )?;
+        }
+        Command::GraphStats { file } => {
+            let graph = load_graph(&file)?;
+            let tilings = graph.count_complete_paths(&graph.count_paths());
+
+            println!("nodes: {}", graph.len());
+            println!("edges: {}", graph.edge_count());
+            println!("complete nodes: {}", graph.get_complete_indices().len());
+            println!("tilings: {}", tilings);
+        }
+        Command::Batch {
+            jobs,
+            output,
+            parallel,
+        } => batch::run(&jobs, &output, parallel)?,
+        #[cfg(feature = "serve")]
+        Command::Serve { address, timeout } => serve::run(&address, timeout)?,
+    }
+
+    Ok(())
+}
+
+/// Parses a (row, column) cell such as `3,4`
+fn parse_hole(s: &str) -> std::result::Result<(usize, usize), String> {
+    let invalid = || format!("invalid hole '{}', expected a cell like 3,4", s);
+
+    let (x, y) = s.split_once(',').ok_or_else(invalid)?;
+    let x = x.trim().parse().map_err(|_| invalid())?;
+    let y = y.trim().parse().map_err(|_| invalid())?;
+
+    Ok((x, y))
+}
+
+/// Parses a sampling strategy such as `random:100`
+fn parse_sample_strategy(s: &str) -> std::result::Result<SampleStrategy, String> {
+    let invalid = || {
+        format!(
+            "invalid sampling strategy '{}', expected first:N, random:N or every:K",
+            s
+        )
+    };
+
+    let (kind, n) = s.split_once(':').ok_or_else(invalid)?;
+    let n = n.trim().parse().map_err(|_| invalid())?;
+
+    match kind.trim() {
+        "first" => Ok(SampleStrategy::First(n)),
+        "random" => Ok(SampleStrategy::RandomUniform(n)),
+        "every" if n > 0 => Ok(SampleStrategy::EveryKth(n)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parses a duration such as `90s`, `5m` or `2h`, where a plain number is in seconds
+fn parse_duration(s: &str) -> std::result::Result<Duration, String> {
+    let invalid = || {
+        format!(
+            "invalid duration '{}', expected a time like 90s, 5m or 2h",
+            s
+        )
+    };
+
+    let s = s.trim();
+    let (number, unit) = match s.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(index) => s.split_at(index),
+        None => (s, "s"),
+    };
+    let number: f64 = number.trim().parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        "ms" => number / 1000.0,
+        "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        _ => return Err(invalid()),
+    };
+
+    Duration::try_from_secs_f64(seconds).map_err(|_| invalid())
+}
+
+/// Parses an RGB colour from a hex code such as `#1e3888`
+fn parse_color(s: &str) -> std::result::Result<(u8, u8, u8), String> {
+    let hex = s.trim().trim_start_matches('#');
+
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "invalid colour '{}', expected a hex code like #1e3888",
+            s
+        ));
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+    Ok((channel(0), channel(2), channel(4)))
+}
+
+/// Marks the given (row, column) cells of the board as unavailable
+fn punch_holes(board: RectangularBoard, holes: &[(usize, usize)]) -> Result<RectangularBoard> {
+    if let Some(&(row, col)) = holes
+        .iter()
+        .find(|(row, col)| *row >= board.height || *col >= board.width)
+    {
+        return Err(invalid_input(TilerError::OutOfBounds {
+            row: row as isize,
+            col: col as isize,
+            width: board.width,
+            height: board.height,
+        }));
+    }
+
+    Ok(board.mark_cells(holes))
+}
+
+/// Reads a board from a text file of '.' (cells to tile) and '#' (marked cells)
+fn read_board_file(path: &std::path::Path) -> Result<RectangularBoard> {
+    RectangularBoard::from_ascii(&std::fs::read_to_string(path)?)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Where the cells of a board come from: a board read from a file, or a type of board
+/// with its size and width
+enum BoardShape<'a> {
+    File(&'a RectangularBoard),
+    Type(BoardType, usize, usize),
+}
+
+/// Builds a board, marking the given holes and then scaling it (holes and all)
+fn build_board(
+    shape: BoardShape,
+    holes: &[(usize, usize)],
+    scale: usize,
+    boundary: Boundary,
+) -> Result<RectangularBoard> {
+    let board = match shape {
+        BoardShape::File(board) => Ok(board.clone()),
+        BoardShape::Type(board_type, size, width) => match board_type {
+            BoardType::Rectangle => RectangularBoard::try_new(width, size),
+            BoardType::LBoard => RectangularBoard::try_l_board(size, 1),
+            BoardType::TBoard => RectangularBoard::try_t_board(size, 1),
+            BoardType::AztecDiamond => RectangularBoard::try_aztec_diamond(size),
+            BoardType::Staircase => RectangularBoard::try_staircase(size),
+            BoardType::Cross => RectangularBoard::try_cross(size, width),
+        },
+    }
+    .map_err(invalid_input)?;
+
+    // holes are scaled along with the rest of the board
+    punch_holes(board, holes)?
+        .try_scaled(scale)
+        .map(|board| board.with_boundary(boundary))
+        .map_err(invalid_input)
+}
+
+/// Collects every allowed rotation and reflection of each tile at each scale, where each
+/// tile may be used at most `tile_count` times if given
+fn make_tile_collection(
+    base_tiles: &[Tile],
+    scales: &[usize],
+    symmetry: TileSymmetry,
+    tile_count: Option<usize>,
+) -> Result<TileCollection> {
+    let scaled_tiles = base_tiles
+        .iter()
+        .flat_map(|tile| scales.iter().map(move |scale| tile.try_scaled(*scale)))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(invalid_input)?
+        .into_iter();
+
+    Ok(if let Some(count) = tile_count {
+        TileCollection::with_counts_and_symmetry(
+            scaled_tiles.map(|tile| (tile, count)).collect(),
+            symmetry,
+        )
+    } else {
+        scaled_tiles
+            .flat_map(|tile| {
+                TileCollection::from_tile_with(tile, symmetry)
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    })
+}
+
+/// Returns a token which is cancelled when Ctrl-C is pressed, so that long running counts
+/// stop cleanly (keeping their last checkpoint).  Pressing Ctrl-C again exits immediately.
+fn cancel_on_interrupt() -> Result<CancelToken> {
+    let token = CancelToken::new();
+    let handler_token = token.clone();
+
+    ctrlc::set_handler(move || {
+        if handler_token.is_cancelled() {
+            std::process::exit(130);
+        }
+        handler_token.cancel();
+    })
+    .map_err(std::io::Error::other)?;
+
+    Ok(token)
+}
+
+/// Writes a tiling to stdout in the given format
+fn print_tiling(tiling: &Tiling, format: OutputFormat, options: &RenderOptions) -> Result<()> {
+    match format {
+        OutputFormat::Svg => println!(
+            "{}",
+            render_single_tiling_from_vec(tiling.boards().iter().collect(), options)
+        ),
+        OutputFormat::Png => std::io::stdout().write_all(&render_single_tiling_png(
+            tiling.boards().iter().collect(),
+            options,
+        ))?,
+        OutputFormat::Text => println!("{}", tiling),
+    }
+
+    Ok(())
+}
+
+/// Reports a cancelled (or timed out) computation as an IO error
+fn interrupted(e: TilerError) -> std::io::Error {
+    match e {
+        TilerError::TimedOut { .. } => std::io::Error::new(std::io::ErrorKind::TimedOut, e),
+        e => std::io::Error::new(std::io::ErrorKind::Interrupted, e),
+    }
+}
+
+/// Reports invalid board or tile options as an IO error
+fn invalid_input(e: TilerError) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, e)
+}
+
+impl BoardArgs {
+    /// The rotations and reflections of each tile which can be used
+    fn symmetry(&self) -> TileSymmetry {
+        if self.one_sided {
+            TileSymmetry::RotationsOnly
+        } else if self.fixed {
+            TileSymmetry::None
+        } else {
+            TileSymmetry::All
+        }
+    }
+
+    /// Creates a collection of tiles based on the tile(s) specified by the user
+    fn tiles(&self) -> Result<TileCollection> {
+        let symmetry = self.symmetry();
+        let base_tiles = if self.tiles.is_empty() && self.custom_tiles.is_empty() {
+            make_tiles(self.tile_type, self.tile_size, symmetry).map_err(invalid_input)?
+        } else {
+            self.tiles
+                .iter()
+                .chain(self.custom_tiles.iter())
+                .cloned()
+                .collect()
+        };
+        let scales = if self.tile_scales.is_empty() {
+            vec![1]
+        } else {
+            self.tile_scales.clone()
+        };
+
+        make_tile_collection(&base_tiles, &scales, symmetry, self.tile_count)
+    }
+
+    /// Creates the board specified by the user
+    fn board(&self) -> Result<RectangularBoard> {
+        self.board_with(self.board_size, self.board_scale)
+    }
+
+    /// Creates the board specified by the user, but with the given size and scale
+    fn board_with(&self, board_size: usize, board_scale: usize) -> Result<RectangularBoard> {
+        let boundary = if self.wrap {
+            Boundary::Toroidal
+        } else if self.wrap_x {
+            Boundary::Cylindrical
+        } else {
+            Boundary::Bounded
+        };
+
+        match &self.board_file {
+            Some(path) => build_board(
+                BoardShape::File(&read_board_file(path)?),
+                &self.holes,
+                board_scale,
+                boundary,
+            ),
+            None => build_board(
+                BoardShape::Type(
+                    self.board_type,
+                    board_size,
+                    self.width.unwrap_or(board_size),
+                ),
+                &self.holes,
+                board_scale,
+                boundary,
+            ),
+        }
+    }
+}
+
+impl SearchArgs {
+    /// Creates a tiler for the user's tiles and the given board, set up with these options
+    fn tiler(&self, board_args: &BoardArgs, board: RectangularBoard) -> Result<Tiler> {
+        let mut tiler = Tiler::new(board_args.tiles()?, board);
+
+        if let Some(seed) = self.seed {
+            tiler = tiler.with_rng(StdRng::seed_from_u64(seed));
+        }
+        if self.up_to_symmetry {
+            tiler = tiler.with_symmetry_reduction();
+        }
+        if self.fault_free {
+            tiler = tiler.with_fault_free();
+        }
+        if let Some(cells) = self.cover_at_least {
+            tiler = tiler.with_target(Target::CoverAtLeast(cells));
+        }
+        if self.progress {
+            tiler = tiler.with_progress(BarProgress::new());
+        }
+        if self.stats {
+            tiler = tiler.with_stats();
+        }
+        if let Some(max_memory) = self.max_memory {
+            tiler = tiler.with_max_memory(max_memory);
+        }
+        if let Some(threads) = self.threads {
+            tiler = tiler.with_threads(threads);
+        }
+        if let Some(memory_limit) = self.memory_limit {
+            tiler = tiler.with_memory_limit(memory_limit);
+        }
+
+        Ok(tiler
+            .with_backend(match self.backend {
+                BackendType::Search => Backend::Search,
+                BackendType::Dlx => Backend::Dlx,
+                #[cfg(feature = "ilp")]
+                BackendType::Ilp => Backend::Ilp,
+            })
+            .with_arith(match self.arith {
+                ArithType::Big => Arith::Big,
+                ArithType::Auto => Arith::Auto,
+            })
+            .with_cell_selection(match self.cell_selection {
+                CellSelectionType::MostConstrained => CellSelection::MostConstrained,
+                CellSelectionType::Lexicographic => CellSelection::Lexicographic,
+                CellSelectionType::FewestFits => CellSelection::FewestFits,
+            }))
+    }
+
+    /// Prints the statistics kept by the tiler to stderr, if they were asked for
+    fn print_stats(&self, tiler: &Tiler) {
+        if self.stats {
+            match tiler.stats() {
+                Some(stats) => eprint!("{}", stats),
+                None => eprintln!("No statistics were kept, as no boards were searched"),
+            }
+        }
+    }
+}
+
+impl RenderArgs {
+    /// The render options specified by the user, where `seed` seeds the choice of colours
+    fn options(&self, seed: Option<u64>) -> RenderOptions {
+        let mut render_options = RenderOptions::new();
+        if let Some(seed) = seed {
+            render_options = render_options.seed(seed);
+        }
+        if let Some(cell_size) = self.cell_size {
+            render_options = render_options.cell_size(cell_size);
+        }
+        if let Some(target_width) = self.target_width {
+            render_options = render_options.target_width(target_width);
+        }
+        if let Some(gap) = self.gap {
+            render_options = render_options.gap(gap);
+        }
+        if !self.palette.is_empty() {
+            render_options = render_options.palette(self.palette.clone());
+        }
+        if let Some(background) = self.background {
+            render_options = render_options.background(background);
+        }
+        if self.outlines {
+            render_options = render_options.outlines(true);
+        }
+        if let Some(shade) = self.show_board {
+            render_options = render_options.show_board(shade);
+        }
+
+        render_options
+            .coloring(match self.coloring {
+                ColoringType::Cycle => Coloring::Cycle,
+                ColoringType::Shape => Coloring::Shape,
+                ColoringType::Orientation => Coloring::Orientation,
+                ColoringType::Proper => Coloring::Proper,
+            })
+            .labels(match self.labels {
+                LabelType::None => LabelKind::None,
+                LabelType::Order => LabelKind::PlacementOrder,
+                LabelType::Shape => LabelKind::TileShape,
+            })
+    }
+}
+
+/// The image format to render many tilings in, which can't be text
+fn image_format(format: OutputFormat, command: &str) -> Result<ImageFormat> {
+    match format {
+        OutputFormat::Svg => Ok(ImageFormat::Svg),
+        OutputFormat::Png => Ok(ImageFormat::Png),
+        OutputFormat::Text => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} only supports the svg and png formats", command),
+        )),
+    }
+}
+
+fn run_count(args: CountArgs) -> Result<()> {
+    let search = &args.search;
+    let mut tiler = search.tiler(&args.board, args.board.board()?)?;
+
+    if let Some(path) = &args.checkpoint {
+        tiler = tiler.with_checkpoint(path);
+    }
+    if let Some(path) = &args.cache_dir {
+        tiler = tiler.with_cache_dir(path);
+    }
+    if let Some(path) = &args.resume {
+        tiler.load_checkpoint(path)?;
+    }
+    if args.live {
+        tiler = tiler.with_progress(LiveProgress);
+    }
+    if let Some(limit) = args.timeout {
+        tiler = tiler.with_time_limit(limit);
+    }
+
+    if args.by_orientation {
+        println!("Orientations:");
+        for (index, tile) in args.board.tiles()?.cell_tiles().enumerate() {
+            println!("  {}: {:?}", index, tile.cells());
+        }
+
+        println!("Tilings by number of tiles in each orientation:");
+        for (profile, count) in tiler.count_tilings_by_orientation() {
+            let profile: Vec<String> = profile.iter().map(|n| n.to_string()).collect();
+            println!("  {}: {}", profile.join(" "), count);
+        }
+    } else if args.by_uncovered {
+        println!("Tilings by number of cells left uncovered:");
+        for (uncovered, count) in tiler.count_tilings_by_uncovered().iter().enumerate() {
+            println!("  {}: {}", uncovered, count);
+        }
+    } else if args.dfs {
+        println!(
+            "{} tilings found",
+            tiler.count_tilings_dfs(args.stack_memory)
+        );
+    } else if search.up_to_symmetry {
+        println!(
+            "{} tilings found up to symmetry",
+            tiler.count_tilings_up_to_symmetry()
+        );
+    } else if args.split_regions {
+        println!("{} tilings found", tiler.count_tilings_by_regions());
+    } else if args.profile {
+        println!("{} tilings found", tiler.count_tilings_profile());
+    } else {
+        // just do a quick tilings count - no need to generate the tiling graph
+        match tiler.count_tilings_cancellable(cancel_on_interrupt()?) {
+            Ok(count) => println!("{} tilings found", count),
+            Err(TilerError::TimedOut {
+                lower_bound,
+                explored,
+            }) => {
+                print!(
+                    "At least {} tilings found before the time limit",
+                    lower_bound
+                );
+                if explored > 0 {
+                    print!(", after exploring {} boards", explored);
+                }
+                println!();
+            }
+            Err(e) => return Err(interrupted(e)),
+        }
+    }
+
+    search.print_stats(&tiler);
+    Ok(())
+}
+
+fn run_single(args: SingleArgs) -> Result<()> {
+    let render_options = args.render.options(args.search.seed);
+    let mut tiler = args
+        .search
+        .tiler(&args.board, args.board.board()?)?
+        .with_search_strategy(match args.strategy {
+            StrategyType::Dfs => SearchStrategy::Dfs,
+            StrategyType::Iddfs => SearchStrategy::Iddfs,
+            StrategyType::LeastFreedom => SearchStrategy::LeastFreedom,
+        });
+    if let Some(seed) = args.tie_break_seed {
+        tiler = tiler.with_tie_breaking(seed);
+    }
+
+    let mut budget = SearchBudget::solutions(args.max_solutions);
+    if let Some(nodes) = args.max_nodes {
+        budget = budget.with_node_limit(nodes);
+    }
+    if let Some(seconds) = args.max_time {
+        budget = budget.with_time_limit(Duration::from_secs(seconds));
+    }
+
+    let tiling = if let Some(workers) = args.workers {
+        tiler.get_single_tiling_parallel(workers)
+    } else if args.best_first {
+        tiler.get_single_tiling_best_first(budget)
+    } else if args.uniform {
+        tiler.sample_uniform_tiling()
+    } else {
+        tiler.get_single_tiling(budget)
+    };
+
+    if let Some(tiling) = tiling {
+        if let Some(filename) = &args.animate {
+            let mut options = AnimationOptions::new().render_options(render_options);
+            if let Some(step) = args.animate_step {
+                options = options.step(step);
+            }
+
+            std::fs::write(
+                filename,
+                render_tiling_animation(&tiling, &options).as_str(),
+            )?;
+        } else {
+            print_tiling(&tiling, args.render.format, &render_options)?;
+        }
+    } else {
+        println!("No tilings found!");
+    }
+
+    args.search.print_stats(&tiler);
+    Ok(())
+}
+
+fn run_all(args: AllArgs) -> Result<()> {
+    let mut tiler = args
+        .search
+        .tiler(&args.board, args.board.board()?)?
+        .with_render_options(args.render.options(args.search.seed))
+        .with_image_format(image_format(args.render.format, "all")?);
+
+    match (args.limit, args.sample) {
+        (Some(limit), None) => tiler.render_first_tilings(&args.output, limit)?,
+        (None, None) => tiler.render_all_tilings(&args.output)?,
+        (limit, Some(sample)) => tiler.render_all_tilings_sampled(&args.output, limit, sample)?,
+    }
+
+    args.search.print_stats(&tiler);
+    Ok(())
+}
+
+fn run_sheet(args: SheetArgs) -> Result<()> {
+    let render_options = args.render.options(args.search.seed);
+    let tiler = args.search.tiler(&args.board, args.board.board()?)?;
+
+    let tilings: Vec<Tiling> = tiler.tilings().take(args.count).collect();
+    if tilings.is_empty() {
+        println!("No tilings found!");
+        return Ok(());
+    }
+
+    let columns = args
+        .columns
+        .unwrap_or_else(|| (tilings.len() as f64).sqrt().ceil() as usize)
+        .max(1);
+
+    match image_format(args.render.format, "render")? {
+        ImageFormat::Svg => std::fs::write(
+            &args.output,
+            render_tiling_grid(&tilings, columns, &render_options).as_str(),
+        )?,
+        ImageFormat::Png => std::fs::write(
+            &args.output,
+            render_tiling_grid_png(&tilings, columns, &render_options),
+        )?,
+    }
+
+    args.search.print_stats(&tiler);
+    Ok(())
+}
+
+fn run_graph(args: GraphArgs) -> Result<()> {
+    let mut tiler = args.search.tiler(&args.board, args.board.board()?)?;
+    if let Some(limit) = args.timeout {
+        tiler = tiler.with_time_limit(limit);
+    }
+    let board_graph = tiler
+        .graph_cancellable(cancel_on_interrupt()?)
+        .map_err(interrupted)?;
+
+    {
+        let board_graph = board_graph.read().unwrap();
+
+        match args.format {
+            GraphFormat::Json => println!("{}", serde_json::to_string(&*board_graph).unwrap()),
+            GraphFormat::Dot => print!("{}", board_graph.to_dot()),
+            GraphFormat::Bin => board_graph.write_compact(std::io::stdout().lock())?,
+            GraphFormat::GraphMl => {
+                board_graph.write_graphml(BufWriter::new(std::io::stdout().lock()))?
+            }
+            GraphFormat::Jsonl => {
+                board_graph.write_edge_lines(BufWriter::new(std::io::stdout().lock()))?
+            }
+        }
+    }
+
+    args.search.print_stats(&tiler);
+    Ok(())
+}
+
+/// Counts tilings at scales 1, 2, 3, ... of the board or, for a sweep, for board sizes from
+/// `board_size` upwards
+fn run_scaling(args: ScalingArgs, sweep: bool) -> Result<()> {
+    if sweep && args.board.board_file.is_some() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "sweep can't be used with --board-file",
+        ));
+    }
+
+    let (name, start) = if sweep {
+        ("size", args.board.board_size)
+    } else {
+        ("scale", 1)
+    };
+    let mut output = match &args.output {
+        Some(path) => {
+            let mut file = BufWriter::new(std::fs::File::create(path)?);
+            if args.estimate.is_some() {
+                writeln!(
+                    file,
+                    "{},estimate,standard_error,entropy_per_cell,wall_time",
+                    name
+                )?;
+            } else {
+                writeln!(file, "{},count,entropy_per_cell,wall_time", name)?;
+            }
+            file.flush()?;
+            Some(file)
+        }
+        None => None,
+    };
+
+    let mut value = start;
+    while args.max.is_none_or(|max| value <= max) {
+        let start = Instant::now();
+        let board = if sweep {
+            args.board.board_with(value, args.board.board_scale)?
+        } else {
+            args.board.board_with(args.board.board_size, value)?
+        };
+        let area = board.unmarked_count() as f64;
+        let mut tiler = args.search.tiler(&args.board, board)?;
+        if let Some(path) = &args.cache_dir {
+            tiler = tiler.with_cache_dir(path);
+        }
+
+        // the count is printed, and written to the CSV file, along with the entropy per cell
+        let (count, csv_count, ln_count) = if let Some(samples) = args.estimate {
+            let estimate = tiler.estimate_tilings(samples as usize);
+            (
+                format!("about {}", estimate),
+                format!("{},{}", estimate.estimate(), estimate.standard_error()),
+                estimate.ln_estimate(),
+            )
+        } else {
+            let count = if args.search.up_to_symmetry {
+                tiler.count_tilings_up_to_symmetry()
+            } else if args.split_regions {
+                tiler.count_tilings_by_regions()
+            } else {
+                tiler.count_tilings()
+            };
+            (count.to_string(), count.to_string(), arith::ln(&count))
+        };
+        let entropy = ln_count / area;
+        let wall_time = start.elapsed();
+
+        println!(
+            "{}({}), {} tilings, entropy per cell {:.6}",
+            name, value, count, entropy
+        );
+        args.search.print_stats(&tiler);
+        if let Some(file) = &mut output {
+            // flush each row, so that the results so far survive the run being stopped
+            writeln!(
+                file,
+                "{},{},{},{:.3}",
+                value,
+                csv_count,
+                entropy,
+                wall_time.as_secs_f64()
+            )?;
+            file.flush()?;
+        }
+        value += 1;
+    }
+
+    Ok(())
+}
+
+fn run_sample(args: SampleArgs) -> Result<()> {
+    // the flip dynamics can start from any tiling, so take the first one found by
+    // dancing links, which copes with boards far larger than the default search can
+    let mut tiler = args
+        .search
+        .tiler(&args.board, args.board.board()?)?
+        .with_backend(Backend::Dlx);
+    let start = match tiler.get_single_tiling(SearchBudget::first_solution()) {
+        Some(tiling) => tiling,
+        None => {
+            println!("No tilings found!");
+            return Ok(());
+        }
+    };
+
+    let mut sampler = GlauberSampler::new(&start).map_err(invalid_input)?;
+    let mut rng = match args.search.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    sampler.run(args.steps, &mut rng);
+
+    print_tiling(
+        &sampler.tiling(),
+        args.render.format,
+        &args.render.options(args.search.seed),
+    )
+}
+
+fn main() {
+    let cli = Cli::parse_from(legacy::rewrite_args(std::env::args_os()));
+
+    // errors are reported by their message, rather than the `Debug` output `main` would give
+    if let Err(e) = run_command(cli.command) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
 }
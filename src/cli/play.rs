@@ -0,0 +1,300 @@
+//! The `play` command, a terminal game of tiling a board by hand.
+//!
+//! The cursor picks a cell, and the tiles which could be placed over it (see
+//! `RectangularBoard::placements_at`) are shown one at a time.  Placements can be undone, and
+//! the solver can be asked for a hint or for the number of ways of finishing the board.
+
+use crate::{BoardArgs, SearchArgs};
+use dcc_tiler::board::{Position, RectangularBoard, TilePosition};
+use dcc_tiler::tile::TileCollection;
+use dcc_tiler::tiler::SearchBudget;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::io::Result;
+
+/// The colours given to placed tiles, in turn
+const COLORS: &[Color] = &[
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+];
+
+const HELP: &str =
+    "arrows: move  tab: next tile  enter: place  u: undo  h: hint  c: count  q: quit";
+
+/// A game in progress
+struct Game<'a> {
+    board_args: &'a BoardArgs,
+    search: &'a SearchArgs,
+    tiles: TileCollection,
+
+    // the initial board followed by the board after each placement, so that placements
+    // are undone by going back a board
+    boards: Vec<RectangularBoard>,
+    placed: Vec<Vec<(usize, usize)>>,
+
+    cursor: (usize, usize),
+    // the placements over the cursor, and which of them is shown
+    placements: Vec<TilePosition>,
+    choice: usize,
+
+    message: String,
+}
+
+impl<'a> Game<'a> {
+    fn new(board_args: &'a BoardArgs, search: &'a SearchArgs) -> Result<Self> {
+        let tiles = board_args.tiles()?;
+        // placing tiles by hand doesn't keep track of how many of each have been used
+        if tiles.has_counts() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "the play command doesn't support --tile-count",
+            ));
+        }
+
+        let mut game = Game {
+            board_args,
+            search,
+            tiles,
+            boards: vec![board_args.board()?],
+            placed: Vec::new(),
+            cursor: (0, 0),
+            placements: Vec::new(),
+            choice: 0,
+            message: String::new(),
+        };
+        game.refresh();
+
+        Ok(game)
+    }
+
+    /// The board as it stands
+    fn board(&self) -> &RectangularBoard {
+        self.boards.last().unwrap()
+    }
+
+    /// Finds the placements over the cursor again, after it moves or the board changes
+    fn refresh(&mut self) {
+        self.placements = self.board().placements_at(self.cursor, &self.tiles);
+        self.choice = 0;
+    }
+
+    fn move_cursor(&mut self, rows: isize, columns: isize) {
+        let board = self.board();
+        let (i, j) = self.cursor;
+
+        self.cursor = (
+            i.saturating_add_signed(rows).min(board.height - 1),
+            j.saturating_add_signed(columns).min(board.width - 1),
+        );
+        self.message.clear();
+        self.refresh();
+    }
+
+    /// Shows the next (or with `step` -1, the previous) placement over the cursor
+    fn cycle(&mut self, step: isize) {
+        if !self.placements.is_empty() {
+            let count = self.placements.len() as isize;
+            self.choice = (self.choice as isize + step).rem_euclid(count) as usize;
+        }
+    }
+
+    fn place(&mut self) {
+        let tp = match self.placements.get(self.choice) {
+            Some(tp) => tp.clone(),
+            None => {
+                self.message = "No tile fits over this cell".into();
+                return;
+            }
+        };
+
+        let cells = tp
+            .covered()
+            .iter()
+            .map(|p| (p.x() as usize, p.y() as usize))
+            .collect();
+        let mut board = self.board().clone();
+        board.mark_tile_at_position(tp);
+
+        self.message = if board.is_all_marked() {
+            "The board is tiled!".into()
+        } else {
+            String::new()
+        };
+        self.boards.push(board);
+        self.placed.push(cells);
+        self.refresh();
+    }
+
+    fn undo(&mut self) {
+        if self.boards.len() > 1 {
+            self.boards.pop();
+            self.placed.pop();
+            self.message.clear();
+            self.refresh();
+        } else {
+            self.message = "Nothing to undo".into();
+        }
+    }
+
+    /// Finds a tiling finishing the board, and shows its tile over the cursor (or its first
+    /// tile, if none of its tiles covers the cursor)
+    fn hint(&mut self) -> Result<()> {
+        let mut tiler = self.search.tiler(self.board_args, self.board().clone())?;
+
+        let tiling = match tiler.get_single_tiling(SearchBudget::first_solution()) {
+            Some(tiling) => tiling,
+            None => {
+                self.message = "The board can't be finished from here".into();
+                return Ok(());
+            }
+        };
+
+        let placement = tiling
+            .placements()
+            .iter()
+            .find(|placement| placement.cells().contains(&self.cursor))
+            .or_else(|| tiling.placements().first());
+        match placement {
+            Some(placement) => {
+                self.placements = vec![TilePosition::new(
+                    placement.cells().iter().map(|&cell| cell.into()).collect(),
+                )];
+                self.choice = 0;
+                self.message = "Press enter to place the hinted tile".into();
+            }
+            None => self.message = "The board is tiled!".into(),
+        }
+
+        Ok(())
+    }
+
+    fn count(&mut self) -> Result<()> {
+        let mut tiler = self.search.tiler(self.board_args, self.board().clone())?;
+
+        self.message = format!("{} ways to finish the board", tiler.count_tilings());
+        Ok(())
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let board = self.board();
+        let initial = &self.boards[0];
+
+        let mut owner = vec![vec![None; board.width]; board.height];
+        for (index, cells) in self.placed.iter().enumerate() {
+            for &(i, j) in cells {
+                owner[i][j] = Some(index);
+            }
+        }
+        let shown = self.placements.get(self.choice).map(TilePosition::covered);
+
+        let lines: Vec<Line> = (0..board.height)
+            .map(|i| {
+                Line::from(
+                    (0..board.width)
+                        .map(|j| {
+                            let position = Position::from((i, j));
+                            let (text, mut style) = if initial.is_marked(position) {
+                                ("  ", Style::default())
+                            } else if shown.is_some_and(|shown| shown.contains(&position)) {
+                                ("[]", Style::default().bg(Color::White).fg(Color::Black))
+                            } else if let Some(index) = owner[i][j] {
+                                ("  ", Style::default().bg(COLORS[index % COLORS.len()]))
+                            } else {
+                                ("· ", Style::default())
+                            };
+
+                            if (i, j) == self.cursor {
+                                style = style.add_modifier(Modifier::REVERSED);
+                            }
+                            Span::styled(text, style)
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+
+        let [board_area, status_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(4)]).areas(frame.area());
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" dcc-tiler ")),
+            board_area,
+        );
+
+        let status = vec![
+            Line::from(format!(
+                "cell ({}, {}): tile {} of {}, {} tiles placed",
+                self.cursor.0,
+                self.cursor.1,
+                if self.placements.is_empty() {
+                    0
+                } else {
+                    self.choice + 1
+                },
+                self.placements.len(),
+                self.placed.len()
+            )),
+            Line::from(self.message.as_str()),
+            Line::from(HELP),
+        ];
+        frame.render_widget(Paragraph::new(status).block(Block::bordered()), status_area);
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+            match key.code {
+                KeyCode::Up => self.move_cursor(-1, 0),
+                KeyCode::Down => self.move_cursor(1, 0),
+                KeyCode::Left => self.move_cursor(0, -1),
+                KeyCode::Right => self.move_cursor(0, 1),
+                KeyCode::Tab | KeyCode::Char(' ') => self.cycle(1),
+                KeyCode::BackTab => self.cycle(-1),
+                KeyCode::Enter => self.place(),
+                KeyCode::Char('u') | KeyCode::Backspace => self.undo(),
+                KeyCode::Char('h') => {
+                    self.message = "Searching for a tiling...".into();
+                    terminal.draw(|frame| self.draw(frame))?;
+                    self.hint()?
+                }
+                KeyCode::Char('c') => {
+                    self.message = "Counting...".into();
+                    terminal.draw(|frame| self.draw(frame))?;
+                    self.count()?
+                }
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Plays a game on the user's board with their tiles, until they quit
+pub fn run(board_args: &BoardArgs, search: &SearchArgs) -> Result<()> {
+    let mut game = Game::new(board_args, search)?;
+
+    let mut terminal = ratatui::init();
+    let result = game.run(&mut terminal);
+    ratatui::restore();
+
+    result
+}
@@ -0,0 +1,128 @@
+//! The `serve` command, an HTTP service answering questions about the tilings of boards
+//! described in the body of each request, in the same JSON as a job of the `batch` command:
+//!
+//! - `POST /count` counts the tilings (or decides whether there are any, depending on the
+//!   job's `mode`), answering like the results file of `batch`, e.g. `{"count": "36"}`
+//! - `POST /single.svg` renders a single tiling as an SVG image
+//! - `POST /graph.json` answers with the tiling graph, as written by `graph --format json`
+//!
+//! Jobs which can't be understood are answered with a 400 status and a message saying why,
+//! and jobs which run for longer than the time limit with a 503 status.  Boards can't be
+//! read from files, as that would let requests read the server's files.
+
+use crate::batch::Job;
+use crate::interrupted;
+use axum::extract::State;
+use axum::http::header::CONTENT_TYPE;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use dcc_tiler::render::{render_single_tiling_from_vec, RenderOptions};
+use dcc_tiler::solver::CancelToken;
+use dcc_tiler::tiler::SearchBudget;
+use std::io::{ErrorKind, Result};
+use std::time::{Duration, Instant};
+
+/// The answer to a request, or the status and message of an error
+type Reply = std::result::Result<Response, (StatusCode, String)>;
+
+/// Runs `work` on a thread which may block, as counts can take a long time
+async fn blocking<T, F>(work: F) -> std::result::Result<T, (StatusCode, String)>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(work).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) if e.kind() == ErrorKind::TimedOut => Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "the time limit passed before the job was finished".into(),
+        )),
+        Ok(Err(e)) => Err((StatusCode::BAD_REQUEST, e.to_string())),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+/// Turns away jobs which the service won't run
+fn check(job: &Job) -> std::result::Result<(), (StatusCode, String)> {
+    if job.reads_board_file() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "board_file can't be used with serve".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+async fn count(State(time_limit): State<Duration>, Json(job): Json<Job>) -> Reply {
+    check(&job)?;
+    let answer = blocking(move || job.answer(Some(time_limit))).await?;
+
+    Ok(Json(answer).into_response())
+}
+
+async fn single(State(time_limit): State<Duration>, Json(job): Json<Job>) -> Reply {
+    check(&job)?;
+    let tiling = blocking(move || {
+        let start = Instant::now();
+        let tiling = job
+            .tiler()?
+            .get_single_tiling(SearchBudget::first_solution().with_time_limit(time_limit));
+
+        // a search which ran out of time might have found a tiling given longer
+        if tiling.is_none() && start.elapsed() >= time_limit {
+            return Err(ErrorKind::TimedOut.into());
+        }
+        Ok(tiling)
+    })
+    .await?;
+
+    match tiling {
+        Some(tiling) => {
+            let svg = render_single_tiling_from_vec(
+                tiling.boards().iter().collect(),
+                &RenderOptions::default(),
+            );
+            Ok(([(CONTENT_TYPE, "image/svg+xml")], svg.to_string()).into_response())
+        }
+        None => Err((StatusCode::NOT_FOUND, "No tilings found!".into())),
+    }
+}
+
+async fn graph(State(time_limit): State<Duration>, Json(job): Json<Job>) -> Reply {
+    check(&job)?;
+    let json = blocking(move || {
+        let graph = job
+            .tiler()?
+            .with_time_limit(time_limit)
+            .graph_cancellable(CancelToken::new())
+            .map_err(interrupted)?;
+        let json = serde_json::to_string(&*graph.read().unwrap())?;
+        Ok(json)
+    })
+    .await?;
+
+    Ok(([(CONTENT_TYPE, "application/json")], json).into_response())
+}
+
+/// Serves requests on the given address until the process is stopped, giving up on any
+/// request which runs for longer than `time_limit`
+pub fn run(address: &str, time_limit: Duration) -> Result<()> {
+    let app = Router::new()
+        .route("/count", post(count))
+        .route("/single.svg", post(single))
+        .route("/graph.json", post(graph))
+        .with_state(time_limit);
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(async {
+            let listener = tokio::net::TcpListener::bind(address).await?;
+            eprintln!("Listening on http://{}", listener.local_addr()?);
+
+            axum::serve(listener, app).await
+        })
+}
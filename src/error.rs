@@ -0,0 +1,109 @@
+use num::{BigUint, Zero};
+use std::error::Error;
+use std::fmt;
+
+/// The error returned by the fallible (`try_`) board and tile constructors when given
+/// invalid input, and by computations which are cancelled or given unsuitable tilings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TilerError {
+    /// The board would have no rows or no columns
+    EmptyBoard { width: usize, height: usize },
+
+    /// A size or scale which must be positive was zero
+    ZeroSize(&'static str),
+
+    /// The (row, column) cell lies outside of a `width x height` board
+    OutOfBounds {
+        row: isize,
+        col: isize,
+        width: usize,
+        height: usize,
+    },
+
+    /// A tile was given no cells
+    EmptyTile,
+
+    /// A tile was anchored at a cell it doesn't have
+    InvalidAnchor { anchor: usize, cells: usize },
+
+    /// The computation was stopped early by cancelling its `CancelToken`
+    Cancelled,
+
+    /// The computation was stopped by its time limit, after exploring `explored` boards and
+    /// finding `lower_bound` tilings, so that there are at least that many
+    TimedOut {
+        lower_bound: BigUint,
+        explored: usize,
+    },
+
+    /// A tiling which should consist of dominoes had a tile of another shape
+    NotDominoes,
+
+    /// A tiling graph has an edge to or from a node it doesn't have
+    MissingNode { node: usize, nodes: usize },
+
+    /// A board on the triangular lattice was given to something which needs square cells
+    NotSquareLattice,
+
+    /// The walk describing a tile returned to a block it had already covered, at the given
+    /// step (counting from 1)
+    OverlappingWalk { step: usize },
+}
+
+impl fmt::Display for TilerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TilerError::EmptyBoard { width, height } => write!(
+                f,
+                "a {}x{} board has no cells, the width and height must be positive",
+                width, height
+            ),
+            TilerError::ZeroSize(name) => write!(f, "the {} must be positive", name),
+            TilerError::OutOfBounds {
+                row,
+                col,
+                width,
+                height,
+            } => write!(
+                f,
+                "the cell at row {}, column {} lies outside of the {}x{} board",
+                row, col, width, height
+            ),
+            TilerError::EmptyTile => write!(f, "a tile must cover at least one cell"),
+            TilerError::InvalidAnchor { anchor, cells } => write!(
+                f,
+                "cannot anchor a tile of {} cells at its cell {}",
+                cells, anchor
+            ),
+            TilerError::Cancelled => write!(f, "the computation was cancelled"),
+            TilerError::TimedOut {
+                lower_bound,
+                explored,
+            } => {
+                write!(
+                    f,
+                    "the time limit was reached after exploring {} boards",
+                    explored
+                )?;
+                if !lower_bound.is_zero() {
+                    write!(f, ", having found at least {} tilings", lower_bound)?;
+                }
+                Ok(())
+            }
+            TilerError::NotDominoes => write!(f, "every tile of the tiling must be a domino"),
+            TilerError::MissingNode { node, nodes } => write!(
+                f,
+                "the tiling graph refers to node {}, but only has {} nodes",
+                node, nodes
+            ),
+            TilerError::NotSquareLattice => write!(f, "the board must be made of square cells"),
+            TilerError::OverlappingWalk { step } => write!(
+                f,
+                "step {} of the tile's walk returns to a block it has already covered",
+                step
+            ),
+        }
+    }
+}
+
+impl Error for TilerError {}
@@ -1,15 +1,35 @@
-use crate::board::RectangularBoard;
-use serde_derive::Serialize;
-use std::collections::{HashMap, HashSet};
+#[cfg(feature = "fs")]
+use crate::board::Lattice;
+use crate::board::{Board, RectangularBoard};
+use crate::error::TilerError;
+#[cfg(feature = "fs")]
+use bincode::Options;
+use num::{BigUint, One, Zero};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+#[cfg(feature = "fs")]
+use std::io::{Error, ErrorKind, Read};
+use std::io::{Result, Write};
 
-#[derive(Debug, Serialize, Default)]
-pub struct BoardGraph {
+/// The bytes at the start of a graph written by `BoardGraph::write_compact`
+#[cfg(feature = "fs")]
+const COMPACT_MAGIC: &[u8] = b"DCCG\x01";
+
+/// The graph of partial tilings of a board, whose nodes are boards and whose edges join a
+/// board to each board obtained from it by placing down a tile
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(
+    try_from = "SerializedGraph<B>",
+    bound(deserialize = "B: serde::Deserialize<'de>")
+)]
+pub struct BoardGraph<B: Board = RectangularBoard> {
     // The nodes in our graph are boards - we store there here inside a vec
     //// so that we dont have Rc<RefCell<..>> all over the place
-    nodes_arena: Vec<RectangularBoard>,
+    nodes_arena: Vec<B>,
 
-    #[serde(skip_serializing)]
-    nodes_arena_index: usize,
+    // The index of the first node holding each board, so that boards can be found quickly
+    #[serde(skip)]
+    node_index: HashMap<B, usize>,
 
     // An edge in our graph indicates that it is possible to get from one board state
     // to another by placing down a tile.
@@ -18,40 +38,81 @@ pub struct BoardGraph {
     // This hashmap keeps track of edges "going backwards"
     rev_edges: HashMap<usize, HashSet<usize>>,
 
-    // If a complete tiling of an initial board (index 0 in nodes_arena)
-    complete_indices: HashSet<usize>,
+    // The nodes holding a completely tiled board.  Placing the same tiles in a different
+    // order can end at a different node (when limited tiles are in use, say), so there
+    // can be more than one of these.
+    complete_indices: BTreeSet<usize>,
 }
 
-impl BoardGraph {
+impl<B: Board> Default for BoardGraph<B> {
+    fn default() -> Self {
+        BoardGraph::new()
+    }
+}
+
+impl<B: Board> BoardGraph<B> {
     pub fn new() -> Self {
         BoardGraph {
             nodes_arena: Vec::new(),
-            nodes_arena_index: 0,
+            node_index: HashMap::new(),
             edges: HashMap::new(),
             rev_edges: HashMap::new(),
-            complete_indices: HashSet::new(),
+            complete_indices: BTreeSet::new(),
         }
     }
 
-    #[allow(clippy::never_loop)]
+    /// Returns the first of the nodes holding a completely tiled board, if there are any.
+    /// Use `get_complete_indices` to find all of them.
     pub fn get_complete_index(&self) -> Option<usize> {
-        for index in &self.complete_indices {
-            return Some(*index);
-        }
-        None
+        self.complete_indices.iter().next().copied()
+    }
+
+    /// Returns the nodes holding a completely tiled board, in increasing order.  Every
+    /// tiling is a path from the initial board (node 0) to exactly one of these nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    ///
+    /// let mut graph = BoardGraph::new();
+    /// for _ in 0..3 {
+    ///     graph.add_node(RectangularBoard::new(1, 1));
+    /// }
+    /// graph.add_edge(0, 1);
+    /// graph.add_edge(0, 2);
+    /// graph.mark_node_as_complete(2);
+    /// graph.mark_node_as_complete(1);
+    ///
+    /// assert_eq!(graph.get_complete_indices(), vec![1, 2]);
+    /// assert_eq!(graph.get_complete_index(), Some(1));
+    /// ```
+    pub fn get_complete_indices(&self) -> Vec<usize> {
+        self.complete_indices.iter().copied().collect()
     }
 
     pub fn mark_node_as_complete(&mut self, i: usize) {
         self.complete_indices.insert(i);
     }
 
-    pub fn find_node(&self, v: &RectangularBoard) -> Option<usize> {
-        for (i, node) in self.nodes_arena.iter().enumerate() {
-            if node == v {
-                return Some(i);
-            }
-        }
-        None
+    /// Returns the index of the first node holding the given board, if there is one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    ///
+    /// let mut graph = BoardGraph::new();
+    /// graph.add_node(RectangularBoard::new(2, 2));
+    /// graph.add_node(RectangularBoard::new(1, 1));
+    ///
+    /// assert_eq!(graph.find_node(&RectangularBoard::new(1, 1)), Some(1));
+    /// assert_eq!(graph.find_node(&RectangularBoard::new(3, 3)), None);
+    /// ```
+    pub fn find_node(&self, v: &B) -> Option<usize> {
+        self.node_index.get(v).copied()
     }
 
     pub fn get_edges(&self, i: usize) -> Option<&HashSet<usize>> {
@@ -62,24 +123,742 @@ impl BoardGraph {
         self.rev_edges.get(&i)
     }
 
-    pub fn get_node(&self, i: usize) -> Option<&RectangularBoard> {
+    /// Returns the number of nodes in the graph
+    pub fn len(&self) -> usize {
+        self.nodes_arena.len()
+    }
+
+    /// Determines whether the graph has no nodes
+    pub fn is_empty(&self) -> bool {
+        self.nodes_arena.is_empty()
+    }
+
+    /// Returns the number of edges in the graph
+    pub fn edge_count(&self) -> usize {
+        self.edges.values().map(HashSet::len).sum()
+    }
+
+    pub fn get_node(&self, i: usize) -> Option<&B> {
         self.nodes_arena.get(i)
     }
 
-    pub fn add_node(&mut self, v: RectangularBoard) -> usize {
+    pub fn add_node(&mut self, v: B) -> usize {
+        let index = self.nodes_arena.len();
+
+        self.node_index.entry(v.clone()).or_insert(index);
         self.nodes_arena.push(v);
 
-        self.nodes_arena_index += 1;
-        self.nodes_arena_index - 1
+        index
+    }
+
+    /// Returns, for each node, the number of paths to it from the initial board (node 0).
+    /// For a complete board this is the number of tilings of the initial board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    ///
+    /// // two ways of getting from node 0 to node 3
+    /// let mut graph = BoardGraph::new();
+    /// for _ in 0..4 {
+    ///     graph.add_node(RectangularBoard::new(1, 1));
+    /// }
+    /// graph.add_edge(0, 1);
+    /// graph.add_edge(0, 2);
+    /// graph.add_edge(1, 3);
+    /// graph.add_edge(2, 3);
+    ///
+    /// assert_eq!(graph.count_paths()[3], 2u32.into());
+    /// ```
+    pub fn count_paths(&self) -> Vec<BigUint> {
+        self.count_paths_from(0)
+    }
+
+    /// Returns the number of paths from node `from` to node `to`.  When `from` is the initial
+    /// board (node 0) and `to` is a complete board, this is the number of tilings ending at `to`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    ///
+    /// let mut graph = BoardGraph::new();
+    /// for _ in 0..4 {
+    ///     graph.add_node(RectangularBoard::new(1, 1));
+    /// }
+    /// graph.add_edge(0, 1);
+    /// graph.add_edge(0, 2);
+    /// graph.add_edge(1, 3);
+    /// graph.add_edge(2, 3);
+    ///
+    /// assert_eq!(graph.count_paths_between(0, 3), 2u32.into());
+    /// assert_eq!(graph.count_paths_between(1, 3), 1u32.into());
+    /// assert_eq!(graph.count_paths_between(3, 0), 0u32.into());
+    /// ```
+    pub fn count_paths_between(&self, from: usize, to: usize) -> BigUint {
+        if to < self.nodes_arena.len() {
+            self.count_paths_from(from).swap_remove(to)
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    /// Returns, for each node, the number of paths to it from node `from`
+    fn count_paths_from(&self, from: usize) -> Vec<BigUint> {
+        let mut counts: Vec<Option<BigUint>> = vec![None; self.nodes_arena.len()];
+        if let Some(initial) = counts.get_mut(from) {
+            *initial = Some(BigUint::one());
+        }
+
+        // the graph is acyclic, so a node's count is known once the counts of all of its
+        // parents are known
+        for start in 0..counts.len() {
+            let mut stack = vec![start];
+
+            while let Some(&index) = stack.last() {
+                if counts[index].is_some() {
+                    stack.pop();
+                    continue;
+                }
+
+                let parents = self.rev_edges.get(&index);
+                let pending: Vec<usize> = parents
+                    .into_iter()
+                    .flatten()
+                    .copied()
+                    .filter(|parent| counts[*parent].is_none())
+                    .collect();
+
+                if pending.is_empty() {
+                    counts[index] = Some(
+                        parents
+                            .into_iter()
+                            .flatten()
+                            .map(|parent| counts[*parent].as_ref().unwrap())
+                            .sum(),
+                    );
+                    stack.pop();
+                } else {
+                    stack.extend(pending);
+                }
+            }
+        }
+
+        counts.into_iter().map(Option::unwrap).collect()
+    }
+
+    /// Returns the nodes along the path from the initial board (node 0) to `target` which
+    /// comes `rank`th when the paths are ordered by the indices of their nodes, read from
+    /// `target` backwards.  `counts` must be the result of `count_paths`, and None is
+    /// returned if there are no more than `rank` such paths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    ///
+    /// let mut graph = BoardGraph::new();
+    /// for _ in 0..4 {
+    ///     graph.add_node(RectangularBoard::new(1, 1));
+    /// }
+    /// graph.add_edge(0, 1);
+    /// graph.add_edge(0, 2);
+    /// graph.add_edge(1, 3);
+    /// graph.add_edge(2, 3);
+    ///
+    /// let counts = graph.count_paths();
+    /// assert_eq!(graph.nth_path(&counts, 3, 0u32.into()), Some(vec![0, 1, 3]));
+    /// assert_eq!(graph.nth_path(&counts, 3, 1u32.into()), Some(vec![0, 2, 3]));
+    /// assert_eq!(graph.nth_path(&counts, 3, 2u32.into()), None);
+    /// ```
+    pub fn nth_path(
+        &self,
+        counts: &[BigUint],
+        target: usize,
+        mut rank: BigUint,
+    ) -> Option<Vec<usize>> {
+        if rank >= counts[target] {
+            return None;
+        }
+
+        let mut path = vec![target];
+        let mut index = target;
+
+        while index != 0 {
+            let mut parents: Vec<usize> = self.rev_edges[&index].iter().copied().collect();
+            parents.sort_unstable();
+
+            for parent in parents {
+                if rank < counts[parent] {
+                    index = parent;
+                    break;
+                }
+                rank -= &counts[parent];
+            }
+            path.push(index);
+        }
+
+        path.reverse();
+        debug_assert!(rank.is_zero());
+
+        Some(path)
+    }
+
+    /// Returns an iterator over the paths from node `from` to node `to`, each given by the
+    /// nodes along it (including both ends).  The paths are found one at a time by a depth
+    /// first search which only visits nodes leading to `to`, taking the edges leaving each
+    /// node in increasing order.  Paths from the initial board (node 0) to a complete board
+    /// are the tilings of the initial board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    ///
+    /// let mut graph = BoardGraph::new();
+    /// for _ in 0..5 {
+    ///     graph.add_node(RectangularBoard::new(1, 1));
+    /// }
+    /// graph.add_edge(0, 1);
+    /// graph.add_edge(0, 2);
+    /// graph.add_edge(0, 4);
+    /// graph.add_edge(1, 3);
+    /// graph.add_edge(2, 3);
+    ///
+    /// let paths: Vec<Vec<usize>> = graph.enumerate_paths(0, 3).collect();
+    /// assert_eq!(paths, vec![vec![0, 1, 3], vec![0, 2, 3]]);
+    /// assert_eq!(graph.enumerate_paths(3, 3).collect::<Vec<_>>(), vec![vec![3]]);
+    /// assert_eq!(graph.enumerate_paths(4, 3).count(), 0);
+    /// ```
+    pub fn enumerate_paths(&self, from: usize, to: usize) -> impl Iterator<Item = Vec<usize>> + '_ {
+        // the nodes from which `to` can be reached, so that dead ends are never explored
+        let mut leading = HashSet::new();
+        let mut stack = vec![to];
+        while let Some(index) = stack.pop() {
+            if index < self.nodes_arena.len() && leading.insert(index) {
+                stack.extend(self.rev_edges.get(&index).into_iter().flatten());
+            }
+        }
+
+        // the path walked so far, and the children of each node along it still to be walked
+        let mut path = Vec::new();
+        let mut unwalked = vec![Vec::new()];
+        if leading.contains(&from) {
+            unwalked[0].push(from);
+        }
+
+        let children = move |index: usize| {
+            let mut children: Vec<usize> = self
+                .edges
+                .get(&index)
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|child| leading.contains(child))
+                .collect();
+
+            // the children are taken from the back, so they're sorted in decreasing order
+            children.sort_unstable_by(|a, b| b.cmp(a));
+            children
+        };
+
+        std::iter::from_fn(move || loop {
+            match unwalked.last_mut()?.pop() {
+                Some(index) => {
+                    path.push(index);
+                    if index == to {
+                        unwalked.push(Vec::new());
+                        return Some(path.clone());
+                    }
+                    unwalked.push(children(index));
+                }
+                None => {
+                    unwalked.pop();
+                    path.pop();
+                }
+            }
+        })
+    }
+
+    /// Returns the number of paths from the initial board to any complete node, given the
+    /// path counts returned by `count_paths`.  This is the number of tilings in the graph.
+    pub fn count_complete_paths(&self, counts: &[BigUint]) -> BigUint {
+        self.complete_indices.iter().map(|i| &counts[*i]).sum()
+    }
+
+    /// Returns the path numbered `rank` (counting from zero) among the paths to any complete
+    /// node, taking the paths to each complete node in turn, in the order given by
+    /// `get_complete_indices`.  Returns `None` if `rank` is at least `count_complete_paths`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    ///
+    /// let mut graph = BoardGraph::new();
+    /// for _ in 0..4 {
+    ///     graph.add_node(RectangularBoard::new(1, 1));
+    /// }
+    /// graph.add_edge(0, 1);
+    /// graph.add_edge(0, 2);
+    /// graph.add_edge(1, 3);
+    /// graph.add_edge(2, 3);
+    /// graph.mark_node_as_complete(1);
+    /// graph.mark_node_as_complete(3);
+    ///
+    /// let counts = graph.count_paths();
+    /// assert_eq!(graph.count_complete_paths(&counts), 3u32.into());
+    /// assert_eq!(graph.nth_complete_path(&counts, 0u32.into()), Some(vec![0, 1]));
+    /// assert_eq!(graph.nth_complete_path(&counts, 2u32.into()), Some(vec![0, 2, 3]));
+    /// assert_eq!(graph.nth_complete_path(&counts, 3u32.into()), None);
+    /// ```
+    pub fn nth_complete_path(&self, counts: &[BigUint], mut rank: BigUint) -> Option<Vec<usize>> {
+        for &complete in &self.complete_indices {
+            if rank < counts[complete] {
+                return self.nth_path(counts, complete, rank);
+            }
+            rank -= &counts[complete];
+        }
+
+        None
+    }
+
+    /// Returns the graph in Graphviz DOT format.  Each node is labelled with a picture of
+    /// its board (`#` for marked cells and `.` for unmarked cells), and complete boards are
+    /// highlighted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let board = RectangularBoard::new(2, 1);
+    /// let tiled = board.place_tile(&TileCollection::from(Tile::domino())).remove(0);
+    ///
+    /// let mut graph = BoardGraph::new();
+    /// let s = graph.add_node(board);
+    /// let t = graph.add_node(tiled);
+    /// graph.add_edge(s, t);
+    /// graph.mark_node_as_complete(t);
+    ///
+    /// let dot = graph.to_dot();
+    /// assert!(dot.contains("0 -> 1;"));
+    /// assert!(dot.contains(r###"1 [label="##\l", style=filled, fillcolor=palegreen];"###));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot =
+            String::from("digraph tilings {\n    node [shape=box, fontname=monospace];\n");
+
+        for (i, node) in self.nodes_arena.iter().enumerate() {
+            // draw the board one row per line, left justified
+            let label: String = board_rows(node).map(|row| row + "\\l").collect();
+
+            if self.complete_indices.contains(&i) {
+                dot += &format!(
+                    "    {} [label=\"{}\", style=filled, fillcolor=palegreen];\n",
+                    i, label
+                );
+            } else {
+                dot += &format!("    {} [label=\"{}\"];\n", i, label);
+            }
+        }
+
+        let mut edges: Vec<(usize, usize)> = self
+            .edges
+            .iter()
+            .flat_map(|(s, targets)| targets.iter().map(move |t| (*s, *t)))
+            .collect();
+        edges.sort_unstable();
+
+        for (s, t) in edges {
+            dot += &format!("    {} -> {};\n", s, t);
+        }
+
+        dot + "}\n"
+    }
+
+    /// Writes the graph in [GraphML](http://graphml.graphdrawing.org/) format, as read by
+    /// network tools such as Gephi and NetworkX.  Each node has a `board` attribute picturing
+    /// its board one row at a time, separated by `/` (`#` for marked cells and `.` for
+    /// unmarked cells), and a `complete` attribute saying whether the board is completely
+    /// tiled.  The graph is written as it is walked, so even very large graphs never need
+    /// to be held in memory twice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let board = RectangularBoard::new(2, 1);
+    /// let tiled = board.place_tile(&TileCollection::from(Tile::domino())).remove(0);
+    ///
+    /// let mut graph = BoardGraph::new();
+    /// let s = graph.add_node(board);
+    /// let t = graph.add_node(tiled);
+    /// graph.add_edge(s, t);
+    /// graph.mark_node_as_complete(t);
+    ///
+    /// let mut graphml = Vec::new();
+    /// graph.write_graphml(&mut graphml).unwrap();
+    /// let graphml = String::from_utf8(graphml).unwrap();
+    ///
+    /// assert!(graphml.contains(r#"<edge source="n0" target="n1"/>"#));
+    /// assert!(graphml.contains(r#"<data key="board">##</data><data key="complete">true</data>"#));
+    /// ```
+    pub fn write_graphml<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+        )?;
+        writeln!(
+            writer,
+            r#"  <key id="board" for="node" attr.name="board" attr.type="string"/>"#
+        )?;
+        writeln!(
+            writer,
+            r#"  <key id="complete" for="node" attr.name="complete" attr.type="boolean"><default>false</default></key>"#
+        )?;
+        writeln!(writer, r#"  <graph id="tilings" edgedefault="directed">"#)?;
+
+        for (i, node) in self.nodes_arena.iter().enumerate() {
+            let board = board_rows(node).collect::<Vec<_>>().join("/");
+            let complete = if self.complete_indices.contains(&i) {
+                r#"<data key="complete">true</data>"#
+            } else {
+                ""
+            };
+
+            writeln!(
+                writer,
+                r#"    <node id="n{}"><data key="board">{}</data>{}</node>"#,
+                i, board, complete
+            )?;
+        }
+
+        for s in 0..self.nodes_arena.len() {
+            for t in self.sorted_edges(s) {
+                writeln!(writer, r#"    <edge source="n{}" target="n{}"/>"#, s, t)?;
+            }
+        }
+
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</graphml>")?;
+        writer.flush()
+    }
+
+    /// Writes the edges of the graph as [JSON Lines](https://jsonlines.org/), one
+    /// `{"source": s, "target": t}` object per line, in increasing order.  Each line is written
+    /// as soon as it's found, so graphs with millions of edges can be exported (and read back
+    /// in by tools such as NetworkX, a line at a time) without building one giant JSON value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    ///
+    /// let mut graph = BoardGraph::new();
+    /// for _ in 0..3 {
+    ///     graph.add_node(RectangularBoard::new(1, 1));
+    /// }
+    /// graph.add_edge(0, 2);
+    /// graph.add_edge(0, 1);
+    ///
+    /// let mut lines = Vec::new();
+    /// graph.write_edge_lines(&mut lines).unwrap();
+    ///
+    /// assert_eq!(
+    ///     String::from_utf8(lines).unwrap(),
+    ///     "{\"source\": 0, \"target\": 1}\n{\"source\": 0, \"target\": 2}\n"
+    /// );
+    /// ```
+    pub fn write_edge_lines<W: Write>(&self, mut writer: W) -> Result<()> {
+        for s in 0..self.nodes_arena.len() {
+            for t in self.sorted_edges(s) {
+                writeln!(writer, r#"{{"source": {}, "target": {}}}"#, s, t)?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    /// Returns the targets of the edges leaving node `s`, in increasing order
+    fn sorted_edges(&self, s: usize) -> Vec<usize> {
+        let mut targets: Vec<usize> = self.edges.get(&s).into_iter().flatten().copied().collect();
+        targets.sort_unstable();
+        targets
     }
 
     pub fn add_edge(&mut self, s: usize, t: usize) {
-        assert!(s < self.nodes_arena_index && t < self.nodes_arena_index);
+        assert!(s < self.nodes_arena.len() && t < self.nodes_arena.len());
+
+        self.edges.entry(s).or_default().insert(t);
+        self.rev_edges.entry(t).or_default().insert(s);
+    }
+}
+
+impl<B: Board + serde::de::DeserializeOwned> BoardGraph<B> {
+    /// Reads a graph in the JSON format written by serializing it (such as the output of
+    /// `dcc_tiler_cli graph`), so that it can be counted or rendered again without being
+    /// regenerated.  Boards only store their cells (see `RectangularBoard`), so boards on a
+    /// torus or limited tiles can't be placed on the boards of a graph read back in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(2));
+    /// let mut tiler = Tiler::new(tiles, RectangularBoard::l_board(2, 3));
+    /// let json = serde_json::to_string(&*tiler.graph().read().unwrap()).unwrap();
+    ///
+    /// let graph: BoardGraph = BoardGraph::from_reader(json.as_bytes()).unwrap();
+    /// let complete = graph.get_complete_index().unwrap();
+    /// assert_eq!(graph.count_paths()[complete], 4u32.into());
+    ///
+    /// assert!(BoardGraph::<RectangularBoard>::from_reader("{}".as_bytes()).is_err());
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+/// Pictures each row of a board, with `#` for marked cells and `.` for unmarked cells
+fn board_rows<B: Board>(board: &B) -> impl Iterator<Item = String> + '_ {
+    board.grid().board.iter().map(|row| {
+        row.iter()
+            .map(|marked| if *marked { '#' } else { '.' })
+            .collect()
+    })
+}
+
+/// The serialized form of a `BoardGraph`.  The edges going backwards are left out, as they
+/// are the edges going forwards turned around.
+#[derive(Deserialize)]
+struct SerializedGraph<B> {
+    nodes_arena: Vec<B>,
+    edges: HashMap<usize, HashSet<usize>>,
+    complete_indices: Vec<usize>,
+}
+
+impl<B: Board> std::convert::TryFrom<SerializedGraph<B>> for BoardGraph<B> {
+    type Error = TilerError;
+
+    fn try_from(serialized: SerializedGraph<B>) -> std::result::Result<Self, Self::Error> {
+        let nodes = serialized.nodes_arena.len();
+        let check = |node: usize| {
+            if node < nodes {
+                Ok(node)
+            } else {
+                Err(TilerError::MissingNode { node, nodes })
+            }
+        };
+
+        let mut graph = BoardGraph::new();
+        for node in serialized.nodes_arena {
+            graph.add_node(node);
+        }
+
+        for (s, targets) in serialized.edges {
+            for t in targets {
+                graph.add_edge(check(s)?, check(t)?);
+            }
+        }
+
+        for i in serialized.complete_indices {
+            graph.mark_node_as_complete(check(i)?);
+        }
+
+        Ok(graph)
+    }
+}
+
+impl BoardGraph<RectangularBoard> {
+    /// Writes the graph in a compact binary format, which can be read back in with
+    /// `from_compact_reader`.  Each board is stored as one bit per cell, and the edges
+    /// leaving each node as the (variable length) gaps between their targets, which is
+    /// usually dozens of times smaller than the JSON format.
+    ///
+    /// Like the JSON format, only the cells of the boards (and the lattice they lie on) are
+    /// kept, and the boards must all be the same size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(2));
+    /// let mut tiler = Tiler::new(tiles, RectangularBoard::l_board(2, 4));
+    /// let graph = tiler.graph();
+    /// let graph = graph.read().unwrap();
+    ///
+    /// let mut bytes = Vec::new();
+    /// graph.write_compact(&mut bytes).unwrap();
+    /// assert!(bytes.len() * 20 < serde_json::to_vec(&*graph).unwrap().len());
+    ///
+    /// let read = BoardGraph::from_compact_reader(bytes.as_slice()).unwrap();
+    /// assert_eq!(read.len(), graph.len());
+    /// assert_eq!(read.get_node(5).unwrap().board(), graph.get_node(5).unwrap().board());
+    /// assert_eq!(read.count_paths()[read.get_complete_index().unwrap()], 409u32.into());
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn write_compact<W: Write>(&self, mut writer: W) -> Result<()> {
+        let (width, height, lattice) = match self.nodes_arena.first() {
+            Some(board) => (board.width, board.height, board.lattice()),
+            None => (0, 0, Lattice::Square),
+        };
+
+        let mut nodes = Vec::with_capacity(self.nodes_arena.len());
+        for board in &self.nodes_arena {
+            if (board.width, board.height) != (width, height) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "the boards of a compact graph must all be the same size",
+                ));
+            }
+
+            let mut bits = vec![0u8; (width * height).div_ceil(8)];
+            for (k, _) in board
+                .board
+                .iter()
+                .flatten()
+                .enumerate()
+                .filter(|(_, c)| **c)
+            {
+                bits[k / 8] |= 1 << (k % 8);
+            }
+            nodes.push(bits);
+        }
+
+        let edges = (0..self.nodes_arena.len())
+            .map(|s| {
+                // the gaps between sorted targets are much smaller than the targets themselves
+                let mut previous = 0;
+                self.sorted_edges(s)
+                    .into_iter()
+                    .map(|t| t - std::mem::replace(&mut previous, t))
+                    .collect()
+            })
+            .collect();
+
+        let compact = CompactGraph {
+            width,
+            height,
+            lattice,
+            nodes,
+            edges,
+            complete_indices: self.get_complete_indices(),
+        };
+
+        writer.write_all(COMPACT_MAGIC)?;
+        bincode::DefaultOptions::new()
+            .serialize_into(writer, &compact)
+            .map_err(Error::other)
+    }
+
+    /// Reads a graph written by `write_compact`
+    #[cfg(feature = "fs")]
+    pub fn from_compact_reader<R: Read>(mut reader: R) -> Result<Self> {
+        let mut magic = [0; COMPACT_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != COMPACT_MAGIC {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "not a compact tiling graph",
+            ));
+        }
 
-        self.edges.entry(s).or_insert_with(HashSet::new).insert(t);
-        self.rev_edges
-            .entry(t)
-            .or_insert_with(HashSet::new)
-            .insert(s);
+        let compact: CompactGraph = bincode::DefaultOptions::new()
+            .deserialize_from(reader)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+        let mut graph = BoardGraph::new();
+        for bits in &compact.nodes {
+            if bits.len() != (compact.width * compact.height).div_ceil(8) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "a board of the compact graph has the wrong number of cells",
+                ));
+            }
+
+            let cells: Vec<Vec<bool>> = (0..compact.height)
+                .map(|i| {
+                    (0..compact.width)
+                        .map(|j| {
+                            let k = i * compact.width + j;
+                            bits[k / 8] & (1 << (k % 8)) != 0
+                        })
+                        .collect()
+                })
+                .collect();
+
+            graph.add_node(
+                RectangularBoard::from_cells(&cells, compact.lattice)
+                    .map_err(|e| Error::new(ErrorKind::InvalidData, e))?,
+            );
+        }
+
+        let nodes = graph.len();
+        let check = |node: usize| {
+            if node < nodes {
+                Ok(node)
+            } else {
+                Err(Error::new(
+                    ErrorKind::InvalidData,
+                    TilerError::MissingNode { node, nodes },
+                ))
+            }
+        };
+
+        for (s, gaps) in compact.edges.iter().enumerate() {
+            let mut t = 0usize;
+            for gap in gaps {
+                t = t.saturating_add(*gap);
+                graph.add_edge(check(s)?, check(t)?);
+            }
+        }
+
+        for i in compact.complete_indices {
+            graph.mark_node_as_complete(check(i)?);
+        }
+
+        Ok(graph)
     }
 }
+
+/// The format written by `BoardGraph::write_compact`, encoded by bincode with variable
+/// length integers
+#[cfg(feature = "fs")]
+#[derive(Serialize, Deserialize)]
+struct CompactGraph {
+    width: usize,
+    height: usize,
+    lattice: Lattice,
+
+    // the cells of each board, one bit per cell (set for marked cells) read row by row
+    nodes: Vec<Vec<u8>>,
+
+    // for each node, the gaps between the sorted targets of the edges leaving it, starting
+    // from zero
+    edges: Vec<Vec<usize>>,
+
+    complete_indices: Vec<usize>,
+}
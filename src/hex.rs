@@ -0,0 +1,261 @@
+//! Hexagons, rhombi and triangles of the triangular lattice, which are tiled by lozenges
+//! and other polyiamonds.
+//!
+//! A lozenge covers two unit triangles sharing an edge, so a lozenge tiling of a region is a
+//! perfect matching of its triangles.  Looked at the right way up a lozenge tiling of a
+//! hexagon is a picture of unit cubes stacked in the corner of a box, which is how MacMahon
+//! counted them (see `macmahon`).
+
+use crate::board::{check_positive, Board, Lattice, Position, RectangularBoard};
+use crate::error::TilerError;
+use num::{BigUint, One};
+use serde_derive::{Deserialize, Serialize};
+
+/// A region of the triangular lattice bounded by straight lines, stored as a grid of
+/// triangles (see `Lattice::Triangular`) with the triangles outside of the region marked
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::hex::{macmahon, HexBoard};
+/// use dcc_tiler::tile::{CellTile, TileCollection};
+/// use dcc_tiler::tiler::Tiler;
+///
+/// let board = HexBoard::hexagon(2, 2, 2);
+/// let lozenges = TileCollection::from(CellTile::lozenge());
+///
+/// let mut tiler = Tiler::new(lozenges, board.clone());
+/// assert_eq!(tiler.count_tilings(), 20u32.into());
+/// assert_eq!(macmahon(2, 2, 2), 20u32.into());
+///
+/// // the tiling graph is made of hexagons with some of their triangles covered
+/// let graph = tiler.graph();
+/// let graph = graph.read().unwrap();
+/// let initial: &HexBoard = graph.get_node(0).unwrap();
+/// assert_eq!(initial, &board);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HexBoard {
+    grid: RectangularBoard,
+}
+
+impl HexBoard {
+    /// Generates a hexagon whose sides have lengths a, b, c, a, b and c, going clockwise
+    /// from the horizontal side at the top
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a, b or c is zero
+    pub fn hexagon(a: usize, b: usize, c: usize) -> Self {
+        HexBoard::try_hexagon(a, b, c).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `hexagon`, but returns an error if a, b or c is zero
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::error::TilerError;
+    /// use dcc_tiler::hex::HexBoard;
+    ///
+    /// assert_eq!(HexBoard::try_hexagon(1, 0, 1), Err(TilerError::ZeroSize("b")));
+    /// ```
+    pub fn try_hexagon(a: usize, b: usize, c: usize) -> Result<Self, TilerError> {
+        check_positive(a, "a")?;
+        check_positive(b, "b")?;
+        check_positive(c, "c")?;
+
+        Ok(HexBoard::with_sides(a, b, c))
+    }
+
+    /// Generates a rhombus with horizontal sides of length a and sloping sides of length
+    /// b, leaning to the right.  It has a single lozenge tiling.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a or b is zero
+    pub fn rhombus(a: usize, b: usize) -> Self {
+        HexBoard::try_rhombus(a, b).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `rhombus`, but returns an error if a or b is zero
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::hex::HexBoard;
+    ///
+    /// let board = HexBoard::try_rhombus(3, 2).unwrap();
+    /// assert_eq!(board.cells(), 12);
+    /// ```
+    pub fn try_rhombus(a: usize, b: usize) -> Result<Self, TilerError> {
+        check_positive(a, "a")?;
+        check_positive(b, "b")?;
+
+        // a rhombus is a hexagon whose lower right and upper left sides have no length
+        Ok(HexBoard::with_sides(a, b, 0))
+    }
+
+    /// Generates a triangle pointing up, with sides of length n.  It is made of n * n
+    /// triangles, n * (n + 1) / 2 of which point up.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if n is zero
+    pub fn triangle(n: usize) -> Self {
+        HexBoard::try_triangle(n).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `triangle`, but returns an error if n is zero
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::hex::HexBoard;
+    /// use dcc_tiler::tile::{CellTile, TetriamondKind, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let board = HexBoard::try_triangle(4).unwrap();
+    /// assert_eq!(board.cells(), 16);
+    ///
+    /// // the corners are forced, which leaves the middle to be covered by another T
+    /// let tiles = TileCollection::from(CellTile::tetriamond(TetriamondKind::T));
+    /// assert_eq!(Tiler::new(tiles, board).count_tilings(), 1u32.into());
+    /// ```
+    pub fn try_triangle(n: usize) -> Result<Self, TilerError> {
+        check_positive(n, "n")?;
+
+        // as for hexagons, the apex must be a corner of a triangle
+        let n = n as isize;
+        let margin = (n + 1) % 2;
+        let apex = n + margin;
+
+        Ok(HexBoard::with_bounds(
+            2 * n - 1 + 2 * margin,
+            n,
+            |y| apex - y,
+            |y| apex + y,
+        ))
+    }
+
+    /// Generates the hexagon with sides a, b, c, a, b and c, which is a rhombus if c is zero
+    fn with_sides(a: usize, b: usize, c: usize) -> Self {
+        let (a, b, c) = (a as isize, b as isize, c as isize);
+
+        // Positions are measured across in half sides, and down in rows.  The top left
+        // corner must be a corner of a triangle, as close to the left edge as possible.  If
+        // that leaves the first column empty, the last column is left empty too, so that
+        // the hexagon is still symmetric within its grid.
+        let margin = (c + 1) % 2;
+        let top_left = c + margin;
+        let left = |y: isize| {
+            if y <= c {
+                top_left - y
+            } else {
+                top_left - 2 * c + y
+            }
+        };
+        let right = |y: isize| {
+            if y <= b {
+                top_left + 2 * a + y
+            } else {
+                top_left + 2 * a + 2 * b - y
+            }
+        };
+
+        HexBoard::with_bounds(top_left + 2 * a + b - 1 + margin, b + c, left, right)
+    }
+
+    /// Generates the region of a width by height grid of triangles lying between the
+    /// boundaries `left` and `right`, which give the leftmost and rightmost positions (in
+    /// half sides) inside the region on each horizontal line of the lattice
+    fn with_bounds<L, R>(width: isize, height: isize, left: L, right: R) -> Self
+    where
+        L: Fn(isize) -> isize,
+        R: Fn(isize) -> isize,
+    {
+        let grid = RectangularBoard::new_triangular(width as usize, height as usize);
+
+        let mut outside = Vec::new();
+        for row in 0..height {
+            for col in 0..width {
+                // the corners of the triangle, which are all inside the hexagon exactly
+                // when the triangle is
+                let corners = if Lattice::points_up(row, col) {
+                    [(col + 1, row), (col, row + 1), (col + 2, row + 1)]
+                } else {
+                    [(col, row), (col + 2, row), (col + 1, row + 1)]
+                };
+
+                if corners.iter().any(|&(x, y)| x < left(y) || x > right(y)) {
+                    outside.push((row as usize, col as usize));
+                }
+            }
+        }
+
+        HexBoard {
+            grid: grid.mark_cells(&outside),
+        }
+    }
+
+    /// Returns the number of triangles in this region
+    pub fn cells(&self) -> usize {
+        self.grid
+            .board
+            .iter()
+            .flatten()
+            .filter(|marked| !**marked)
+            .count()
+    }
+
+    /// Determines whether the (row, column) triangle is part of this region
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        let p = Position::from((row, col));
+        self.grid.is_valid(p) && !self.grid.is_marked(p)
+    }
+}
+
+impl Board for HexBoard {
+    fn grid(&self) -> &RectangularBoard {
+        &self.grid
+    }
+
+    fn with_grid(&self, grid: RectangularBoard) -> Self {
+        HexBoard { grid }
+    }
+}
+
+impl From<HexBoard> for RectangularBoard {
+    fn from(board: HexBoard) -> Self {
+        board.grid
+    }
+}
+
+/// Returns the number of lozenge tilings of `HexBoard::hexagon(a, b, c)`, which MacMahon
+/// showed is the product of (i + j + k - 1) / (i + j + k - 2) over 1 <= i <= a, 1 <= j <= b
+/// and 1 <= k <= c
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::hex::macmahon;
+///
+/// assert_eq!(macmahon(1, 1, 1), 2u32.into());
+/// assert_eq!(macmahon(3, 3, 3), 980u32.into());
+/// assert_eq!(macmahon(4, 0, 2), 1u32.into());
+/// ```
+pub fn macmahon(a: usize, b: usize, c: usize) -> BigUint {
+    let mut numerator = BigUint::one();
+    let mut denominator = BigUint::one();
+
+    for i in 1..=a {
+        for j in 1..=b {
+            for k in 1..=c {
+                numerator *= i + j + k - 1;
+                denominator *= i + j + k - 2;
+            }
+        }
+    }
+
+    numerator / denominator
+}
@@ -0,0 +1,148 @@
+use crate::board::{Boundary, Lattice, RectangularBoard};
+use crate::solver::CancelToken;
+use crate::tile::{CellTile, Tile, TileCollection};
+use num::{BigInt, BigUint, One, Signed, Zero};
+use std::collections::HashSet;
+
+/// Determines whether the given tiles are exactly the horizontal and vertical dominoes
+pub fn is_dominoes(tiles: &TileCollection) -> bool {
+    let dominoes: HashSet<CellTile> = TileCollection::from(Tile::domino())
+        .cell_tiles()
+        .cloned()
+        .collect();
+
+    tiles.cell_tiles().cloned().collect::<HashSet<_>>() == dominoes
+}
+
+/// Determines whether Kasteleyn's method can be used to count the tilings of the given
+/// board by the given tiles - that is, the tiles are dominoes which can be used any
+/// number of times, and the board is a square grid with no cells marked which doesn't wrap
+/// around.
+pub fn applies_to(board: &RectangularBoard, tiles: &TileCollection) -> bool {
+    board.lattice() == Lattice::Square
+        && board.boundary() == Boundary::Bounded
+        && board.board.iter().flatten().all(|marked| !marked)
+        && is_dominoes(tiles)
+        && !tiles.has_counts()
+}
+
+/// Counts the domino tilings of a `width x height` rectangle.
+///
+/// Colouring the cells like a chessboard, a domino tiling is a perfect matching between
+/// the black and white cells.  Kasteleyn showed that giving the vertical edges in every
+/// other column a weight of -1 makes every term of the determinant of the weighted
+/// black-white adjacency matrix have the same sign, so the absolute value of this
+/// determinant is the number of tilings.  The determinant is computed exactly using
+/// fraction free (Bareiss) elimination.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::kasteleyn::count_domino_tilings;
+///
+/// assert_eq!(count_domino_tilings(8, 8), 12988816u32.into());
+/// assert_eq!(count_domino_tilings(3, 3), 0u32.into());
+/// ```
+pub fn count_domino_tilings(width: usize, height: usize) -> BigUint {
+    count_domino_tilings_cancellable(width, height, None).unwrap()
+}
+
+/// Like `count_domino_tilings`, but gives up (returning `None`) once the given token is
+/// cancelled, as the determinant of a large board can take a long time
+pub(crate) fn count_domino_tilings_cancellable(
+    width: usize,
+    height: usize,
+    cancel: Option<&CancelToken>,
+) -> Option<BigUint> {
+    if (width * height) % 2 == 1 {
+        return Some(BigUint::zero());
+    }
+    if width == 0 || height == 0 {
+        return Some(BigUint::one());
+    }
+
+    // index the black and white cells separately
+    let mut index = vec![vec![0; width]; height];
+    let (mut blacks, mut whites) = (0, 0);
+
+    for (i, row) in index.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            if (i + j) % 2 == 0 {
+                *cell = blacks;
+                blacks += 1;
+            } else {
+                *cell = whites;
+                whites += 1;
+            }
+        }
+    }
+
+    let mut matrix = vec![vec![BigInt::zero(); whites]; blacks];
+
+    for i in 0..height {
+        for j in 0..width {
+            if (i + j) % 2 == 1 {
+                continue;
+            }
+            let black = index[i][j];
+
+            if j > 0 {
+                matrix[black][index[i][j - 1]] = BigInt::one();
+            }
+            if j + 1 < width {
+                matrix[black][index[i][j + 1]] = BigInt::one();
+            }
+
+            let vertical = if j % 2 == 0 {
+                BigInt::one()
+            } else {
+                -BigInt::one()
+            };
+            if i > 0 {
+                matrix[black][index[i - 1][j]] = vertical.clone();
+            }
+            if i + 1 < height {
+                matrix[black][index[i + 1][j]] = vertical;
+            }
+        }
+    }
+
+    determinant(matrix, cancel).map(|det| det.abs().to_biguint().unwrap())
+}
+
+/// Computes the determinant of a square matrix using Bareiss' algorithm, in which every
+/// intermediate entry is itself a determinant of a submatrix (and hence an integer).
+/// Returns `None` if the given token is cancelled before the elimination finishes.
+fn determinant(mut matrix: Vec<Vec<BigInt>>, cancel: Option<&CancelToken>) -> Option<BigInt> {
+    let n = matrix.len();
+    let mut sign = BigInt::one();
+    let mut previous_pivot = BigInt::one();
+
+    for k in 0..n {
+        if cancel.is_some_and(CancelToken::is_cancelled) {
+            return None;
+        }
+
+        // find a row with a nonzero pivot
+        let pivot_row = match (k..n).find(|&r| !matrix[r][k].is_zero()) {
+            Some(r) => r,
+            None => return Some(BigInt::zero()),
+        };
+        if pivot_row != k {
+            matrix.swap(pivot_row, k);
+            sign = -sign;
+        }
+
+        for i in (k + 1)..n {
+            for j in (k + 1)..n {
+                let value = &matrix[i][j] * &matrix[k][k] - &matrix[i][k] * &matrix[k][j];
+                matrix[i][j] = value / &previous_pivot;
+            }
+            matrix[i][k] = BigInt::zero();
+        }
+
+        previous_pivot = matrix[k][k].clone();
+    }
+
+    Some(sign * previous_pivot)
+}
@@ -1,4 +1,19 @@
+pub mod analysis;
+pub mod arith;
 pub mod board;
+pub mod error;
 pub mod graph;
+pub mod hex;
+pub mod kasteleyn;
 pub mod render;
+pub mod sampler;
+pub mod solver;
 pub mod tile;
+pub mod tiler;
+pub mod tiling;
+pub mod weight;
+
+mod parallel;
+
+#[cfg(feature = "python")]
+mod python;
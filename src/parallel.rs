@@ -0,0 +1,100 @@
+//! The parallel iterators used by the tiler.  With the `parallel` feature these are rayon's,
+//! and otherwise they are a sequential stand-in with the same interface, so that the tiler
+//! also builds for targets without threads (such as `wasm32-unknown-unknown`).
+
+#[cfg(feature = "parallel")]
+pub use rayon::prelude::*;
+
+#[cfg(feature = "parallel")]
+pub use rayon::current_num_threads;
+
+#[cfg(not(feature = "parallel"))]
+pub use self::sequential::*;
+
+#[cfg(not(feature = "parallel"))]
+mod sequential {
+    /// Returns the number of threads work is split between, which is always one
+    pub fn current_num_threads() -> usize {
+        1
+    }
+
+    /// A sequential iterator offering the parts of rayon's `ParallelIterator` interface
+    /// used by the tiler
+    pub struct SequentialIterator<I>(I);
+
+    impl<I: Iterator> Iterator for SequentialIterator<I> {
+        type Item = I::Item;
+
+        fn next(&mut self) -> Option<I::Item> {
+            self.0.next()
+        }
+    }
+
+    impl<I: Iterator> SequentialIterator<I> {
+        pub fn map<B, F: FnMut(I::Item) -> B>(
+            self,
+            f: F,
+        ) -> SequentialIterator<std::iter::Map<I, F>> {
+            SequentialIterator(self.0.map(f))
+        }
+
+        pub fn enumerate(self) -> SequentialIterator<std::iter::Enumerate<I>> {
+            SequentialIterator(self.0.enumerate())
+        }
+
+        #[cfg_attr(not(feature = "fs"), allow(dead_code))]
+        pub fn flat_map_iter<U, F>(self, f: F) -> SequentialIterator<std::iter::FlatMap<I, U, F>>
+        where
+            U: IntoIterator,
+            F: FnMut(I::Item) -> U,
+        {
+            SequentialIterator(self.0.flat_map(f))
+        }
+
+        /// Folds every item into a single accumulator, yielding just that accumulator
+        pub fn fold<T, ID, F>(self, identity: ID, f: F) -> SequentialIterator<std::iter::Once<T>>
+        where
+            ID: Fn() -> T,
+            F: FnMut(T, I::Item) -> T,
+        {
+            SequentialIterator(std::iter::once(self.0.fold(identity(), f)))
+        }
+
+        pub fn reduce<ID, F>(self, identity: ID, f: F) -> I::Item
+        where
+            ID: Fn() -> I::Item,
+            F: FnMut(I::Item, I::Item) -> I::Item,
+        {
+            self.0.fold(identity(), f)
+        }
+
+        pub fn find_map_any<B, F: FnMut(I::Item) -> Option<B>>(mut self, f: F) -> Option<B> {
+            self.0.find_map(f)
+        }
+    }
+
+    pub trait IntoParallelIterator: IntoIterator + Sized {
+        fn into_par_iter(self) -> SequentialIterator<Self::IntoIter> {
+            SequentialIterator(self.into_iter())
+        }
+    }
+
+    impl<T: IntoIterator> IntoParallelIterator for T {}
+
+    pub trait IntoParallelRefIterator<'a> {
+        type Iter: Iterator;
+
+        fn par_iter(&'a self) -> SequentialIterator<Self::Iter>;
+    }
+
+    impl<'a, T: 'a + ?Sized> IntoParallelRefIterator<'a> for T
+    where
+        &'a T: IntoIterator,
+    {
+        type Iter = <&'a T as IntoIterator>::IntoIter;
+
+        fn par_iter(&'a self) -> SequentialIterator<Self::Iter> {
+            SequentialIterator(self.into_iter())
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Python bindings for counting and rendering tilings, enabled by the `python` feature.
+//!
+//! Build the `dcc_tiler` Python module with `maturin develop --features python`, and then
+//!
+//! ```python
+//! from dcc_tiler import RectangularBoard, Tile, Tiler
+//!
+//! tiler = Tiler(RectangularBoard(8, 8), [Tile.t_tile(1)])
+//! print(tiler.count_tilings())
+//! print(tiler.get_single_tiling().to_svg(seed=1))
+//! ```
+
+use crate::board::RectangularBoard;
+use crate::render::{render_tiling_svg, RenderOptions};
+use crate::tile::{Tile, TileCollection};
+use crate::tiler::{SearchBudget, Tiler};
+use crate::tiling::Tiling;
+use num::BigUint;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::fmt::Display;
+
+/// Reports invalid input from Python as a `ValueError`
+fn value_error<E: Display>(e: E) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// A tile, which may be placed on a board in any of its rotations and reflections
+#[pyclass(name = "Tile", module = "dcc_tiler")]
+struct PyTile {
+    tile: Tile,
+}
+
+#[pymethods]
+impl PyTile {
+    /// Parses a tile from a comma separated list of directions, such as `"L,U,U,R"`, which
+    /// doesn't revisit a block
+    #[new]
+    fn new(directions: &str) -> PyResult<Self> {
+        let tile = Tile::parse(directions).map_err(value_error)?;
+        let tile = Tile::try_new(tile.directions).map_err(value_error)?;
+
+        Ok(PyTile { tile })
+    }
+
+    #[staticmethod]
+    fn l_tile(length: usize) -> PyResult<Self> {
+        let tile = Tile::try_l_tile(length).map_err(value_error)?;
+
+        Ok(PyTile { tile })
+    }
+
+    #[staticmethod]
+    fn t_tile(length: usize) -> PyResult<Self> {
+        let tile = Tile::try_t_tile(length).map_err(value_error)?;
+
+        Ok(PyTile { tile })
+    }
+
+    #[staticmethod]
+    fn domino() -> Self {
+        PyTile {
+            tile: Tile::domino(),
+        }
+    }
+
+    #[staticmethod]
+    fn box_tile() -> Self {
+        PyTile {
+            tile: Tile::box_tile(),
+        }
+    }
+
+    /// Returns this tile with each cell replaced by a `scale x scale` block of cells
+    fn scaled(&self, scale: usize) -> PyResult<Self> {
+        let tile = self.tile.try_scaled(scale).map_err(value_error)?;
+
+        Ok(PyTile { tile })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Tile({:?})", self.tile.directions)
+    }
+}
+
+/// A rectangular board, some of whose cells may already be marked
+#[pyclass(name = "RectangularBoard", module = "dcc_tiler")]
+struct PyBoard {
+    board: RectangularBoard,
+}
+
+#[pymethods]
+impl PyBoard {
+    /// Creates a `width x height` board, with the given (row, column) cells marked
+    #[new]
+    #[pyo3(signature = (width, height, holes = Vec::new()))]
+    fn new(width: usize, height: usize, holes: Vec<(usize, usize)>) -> PyResult<Self> {
+        let board = RectangularBoard::try_with_holes(width, height, &holes).map_err(value_error)?;
+
+        Ok(PyBoard { board })
+    }
+
+    #[staticmethod]
+    fn l_board(n: usize, scale: usize) -> PyResult<Self> {
+        let board = RectangularBoard::try_l_board(n, scale).map_err(value_error)?;
+
+        Ok(PyBoard { board })
+    }
+
+    #[staticmethod]
+    fn t_board(n: usize, scale: usize) -> PyResult<Self> {
+        let board = RectangularBoard::try_t_board(n, scale).map_err(value_error)?;
+
+        Ok(PyBoard { board })
+    }
+
+    #[staticmethod]
+    fn aztec_diamond(n: usize) -> PyResult<Self> {
+        let board = RectangularBoard::try_aztec_diamond(n).map_err(value_error)?;
+
+        Ok(PyBoard { board })
+    }
+
+    /// Parses a board from rows of `.` (cells to be tiled) and `#` (marked cells)
+    #[staticmethod]
+    fn from_ascii(mask: &str) -> PyResult<Self> {
+        let board = RectangularBoard::from_ascii(mask).map_err(value_error)?;
+
+        Ok(PyBoard { board })
+    }
+
+    #[getter]
+    fn width(&self) -> usize {
+        self.board.width
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.board.height
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.board)
+    }
+}
+
+/// A tiling of a board, as found by `Tiler.get_single_tiling`
+#[pyclass(name = "Tiling", module = "dcc_tiler")]
+struct PyTiling {
+    tiling: Tiling,
+}
+
+#[pymethods]
+impl PyTiling {
+    /// Returns the (row, column) cells covered by each tile, in the order they were placed
+    fn placements(&self) -> Vec<Vec<(usize, usize)>> {
+        self.tiling
+            .placements()
+            .iter()
+            .map(|placement| placement.cells().to_vec())
+            .collect()
+    }
+
+    /// Returns the ID of the tile covering each cell of the board, see `Tiling::to_grid`
+    fn to_grid(&self) -> Vec<Vec<u32>> {
+        self.tiling.to_grid()
+    }
+
+    /// Renders this tiling as an SVG image, optionally seeding the choice of tile colours
+    #[pyo3(signature = (seed = None))]
+    fn to_svg(&self, seed: Option<u64>) -> String {
+        let mut options = RenderOptions::new();
+        if let Some(seed) = seed {
+            options = options.seed(seed);
+        }
+
+        render_tiling_svg(&self.tiling, &options).into_string()
+    }
+
+    fn __str__(&self) -> String {
+        self.tiling.to_text()
+    }
+}
+
+/// Counts and finds the tilings of a board by the given tiles, each of which may be used
+/// any number of times and placed in any of its rotations and reflections
+#[pyclass(name = "Tiler", module = "dcc_tiler")]
+struct PyTiler {
+    board: RectangularBoard,
+    tiles: TileCollection,
+}
+
+impl PyTiler {
+    /// Returns a tiler for the board and tiles
+    fn tiler(&self) -> Tiler {
+        Tiler::new(self.tiles.clone(), self.board.clone())
+    }
+}
+
+#[pymethods]
+impl PyTiler {
+    #[new]
+    fn new(board: PyRef<'_, PyBoard>, tiles: Vec<PyRef<'_, PyTile>>) -> PyResult<Self> {
+        if tiles.is_empty() {
+            return Err(PyValueError::new_err("at least one tile must be given"));
+        }
+
+        let tiles = tiles
+            .iter()
+            .flat_map(|tile| {
+                TileCollection::from(tile.tile.clone())
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        Ok(PyTiler {
+            board: board.board.clone(),
+            tiles,
+        })
+    }
+
+    /// Returns the number of tilings of the board.  The GIL is released while counting.
+    fn count_tilings(&self, py: Python<'_>) -> BigUint {
+        py.detach(|| self.tiler().count_tilings())
+    }
+
+    /// Returns some tiling of the board, or `None` if the board can't be tiled
+    fn get_single_tiling(&self, py: Python<'_>) -> Option<PyTiling> {
+        py.detach(|| {
+            self.tiler()
+                .get_single_tiling(SearchBudget::first_solution())
+        })
+        .map(|tiling| PyTiling { tiling })
+    }
+}
+
+#[pymodule]
+fn dcc_tiler(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyTile>()?;
+    module.add_class::<PyBoard>()?;
+    module.add_class::<PyTiling>()?;
+    module.add_class::<PyTiler>()?;
+
+    Ok(())
+}
@@ -1,61 +1,881 @@
-use crate::board::RectangularBoard;
+use crate::board::{Board, Lattice, Position, RectangularBoard};
+use crate::tile::{CellTile, TileSymmetry};
+use crate::tiling::Tiling;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::Rng;
-use simplesvg::{Attr, Color, Fig, Svg};
-use std::collections::{HashMap, HashSet};
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "png")]
+use resvg::{tiny_skia, usvg};
+use simplesvg::{Attr, Color, ColorAttr, Fig};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
 
-pub fn render_single_tiling_from_vec(boards: Vec<&RectangularBoard>) -> String {
+/// Options controlling how tilings are rendered
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::board::RectangularBoard;
+/// use dcc_tiler::render::{render_single_tiling_from_vec, RenderOptions};
+/// use dcc_tiler::tile::{Tile, TileCollection};
+///
+/// let board = RectangularBoard::new(2, 2);
+/// let tiles = TileCollection::from(Tile::domino());
+/// let first = board.place_tile(&tiles).remove(0);
+/// let second = first.place_tile(&tiles).remove(0);
+/// let boards = vec![&board, &first, &second];
+///
+/// // rendering with the same seed always gives the same colours
+/// let options = RenderOptions::new().seed(7);
+/// assert_eq!(
+///     render_single_tiling_from_vec(boards.clone(), &options),
+///     render_single_tiling_from_vec(boards, &options)
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    seed: Option<u64>,
+    palette: Vec<(u8, u8, u8)>,
+    cell_size: f32,
+    gap: f32,
+    padding: f32,
+    border_color: (u8, u8, u8),
+    inner_border_color: (u8, u8, u8),
+    stroke_width: f32,
+    background: Option<(u8, u8, u8)>,
+    coloring: Coloring,
+    board_shade: Option<(u8, u8, u8)>,
+    target_width: Option<f32>,
+    outlines: bool,
+    labels: LabelKind,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            seed: None,
+            palette: vec![
+                (30, 56, 136),
+                (71, 115, 170),
+                (245, 230, 99),
+                (255, 173, 105),
+                (156, 56, 72),
+                (124, 178, 135),
+                (251, 219, 136),
+            ],
+            cell_size: 50.0,
+            gap: 0.0,
+            padding: 10.0,
+            border_color: (0, 0, 0),
+            inner_border_color: (211, 211, 211),
+            stroke_width: 0.5,
+            background: None,
+            coloring: Coloring::default(),
+            board_shade: None,
+            target_width: None,
+            outlines: false,
+            labels: LabelKind::default(),
+        }
+    }
+}
+
+impl RenderOptions {
+    pub fn new() -> Self {
+        RenderOptions::default()
+    }
+
+    /// Sets the RGB colours used for tiles, which are cycled through in placement order
+    ///
+    /// # Panics
+    ///
+    /// Will panic if palette is empty
+    pub fn palette(mut self, palette: Vec<(u8, u8, u8)>) -> Self {
+        assert!(!palette.is_empty());
+
+        self.palette = palette;
+        self
+    }
+
+    /// Sets the side length of each cell, in pixels
+    pub fn cell_size(mut self, cell_size: f32) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Chooses the side length of each cell so that rendered images are the given width, in
+    /// pixels, whatever the size of the board.  This replaces the cell size, while the gap and
+    /// padding stay as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::render::{render_tiling_svg, RenderOptions};
+    /// use dcc_tiler::tiling::{TilePlacement, Tiling};
+    ///
+    /// let board = RectangularBoard::new(60, 1);
+    /// let tiling = Tiling::from_placements(
+    ///     board,
+    ///     (0..30)
+    ///         .map(|i| TilePlacement::new(vec![(0, 2 * i), (0, 2 * i + 1)]))
+    ///         .collect(),
+    /// );
+    ///
+    /// let svg = render_tiling_svg(&tiling, &RenderOptions::new().target_width(800.0));
+    /// assert!(svg.as_str().starts_with(r#"<svg width="800""#));
+    /// ```
+    pub fn target_width(mut self, target_width: f32) -> Self {
+        self.target_width = Some(target_width);
+        self
+    }
+
+    /// Sets the space left between adjacent cells, in pixels
+    pub fn gap(mut self, gap: f32) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Sets the space left around the edge of the image, in pixels
+    pub fn padding(mut self, padding: f32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets the colours of the borders drawn around each tile, and between the cells
+    /// within a tile
+    pub fn border_colors(mut self, border: (u8, u8, u8), inner_border: (u8, u8, u8)) -> Self {
+        self.border_color = border;
+        self.inner_border_color = inner_border;
+        self
+    }
+
+    /// Sets the width of the borders, in pixels
+    pub fn stroke_width(mut self, stroke_width: f32) -> Self {
+        self.stroke_width = stroke_width;
+        self
+    }
+
+    /// Fills the background of the image with the given colour, rather than leaving
+    /// it transparent
+    pub fn background(mut self, background: (u8, u8, u8)) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    /// Seeds the RNG used to choose tile colours, so that renders are reproducible
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Draws the board being tiled underneath the tiles: the cells already marked on the
+    /// initial board (such as the missing corner of an L board) are shaded in the given
+    /// colour, and the region being tiled is outlined in the border colour.  Cells which
+    /// haven't been tiled yet are left unfilled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::render::{render_tiling_svg, RenderOptions};
+    /// use dcc_tiler::tiling::{TilePlacement, Tiling};
+    ///
+    /// let board = RectangularBoard::with_holes(2, 2, &[(1, 1)]);
+    /// let tiling = Tiling::from_placements(board, vec![TilePlacement::new(vec![(0, 0), (0, 1)])]);
+    ///
+    /// let options = RenderOptions::new().show_board((224, 224, 224));
+    /// assert!(render_tiling_svg(&tiling, &options).as_str().contains("fill:#e0e0e0;"));
+    /// ```
+    pub fn show_board(mut self, shade: (u8, u8, u8)) -> Self {
+        self.board_shade = Some(shade);
+        self
+    }
+
+    /// Draws each tile as a single shape (an SVG path around its outline) rather than as
+    /// each of its cells with a border around each of them.  The borders between the cells
+    /// of a tile aren't drawn, and gaps are only left between tiles, but the images are many
+    /// times smaller and easier to edit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::render::{render_tiling_svg, RenderOptions};
+    /// use dcc_tiler::tiling::{TilePlacement, Tiling};
+    ///
+    /// let board = RectangularBoard::new(2, 2);
+    /// let tiling = Tiling::from_placements(
+    ///     board,
+    ///     vec![TilePlacement::new(vec![(0, 0), (0, 1), (1, 0), (1, 1)])],
+    /// );
+    ///
+    /// let svg = render_tiling_svg(&tiling, &RenderOptions::new().outlines(true));
+    /// assert!(svg.as_str().contains(r#"<path d="M10 10L110 10L110 110L10 110Z""#));
+    /// ```
+    pub fn outlines(mut self, outlines: bool) -> Self {
+        self.outlines = outlines;
+        self
+    }
+
+    /// Sets the text label drawn in the middle of each tile
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::render::{render_tiling_svg, LabelKind, RenderOptions};
+    /// use dcc_tiler::tiling::{TilePlacement, Tiling};
+    ///
+    /// let board = RectangularBoard::new(2, 2);
+    /// let tiling = Tiling::from_placements(
+    ///     board,
+    ///     vec![
+    ///         TilePlacement::new(vec![(0, 0), (0, 1)]),
+    ///         TilePlacement::new(vec![(1, 0), (1, 1)]),
+    ///     ],
+    /// );
+    ///
+    /// let svg = render_tiling_svg(&tiling, &RenderOptions::new().labels(LabelKind::PlacementOrder));
+    /// assert!(svg.as_str().contains(">1</text>") && svg.as_str().contains(">2</text>"));
+    ///
+    /// // both dominoes lie the same way round
+    /// let svg = render_tiling_svg(&tiling, &RenderOptions::new().labels(LabelKind::TileShape));
+    /// assert_eq!(svg.as_str().matches(">A1</text>").count(), 2);
+    /// ```
+    pub fn labels(mut self, labels: LabelKind) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Sets how the colours of the palette are given to tiles
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::render::{render_tiling_svg, Coloring, RenderOptions};
+    /// use dcc_tiler::tiling::{TilePlacement, Tiling};
+    ///
+    /// let board = RectangularBoard::new(2, 2);
+    /// let tiling = Tiling::from_placements(
+    ///     board,
+    ///     vec![
+    ///         TilePlacement::new(vec![(0, 0), (0, 1)]),
+    ///         TilePlacement::new(vec![(1, 0), (1, 1)]),
+    ///     ],
+    /// );
+    ///
+    /// // both dominoes lie the same way, so they're the same colour whatever the seed
+    /// let options = RenderOptions::new().palette(vec![(255, 0, 0), (0, 0, 255)]);
+    /// let svg = render_tiling_svg(&tiling, &options.coloring(Coloring::Orientation));
+    /// assert!(svg.as_str().contains("#ff0000") && !svg.as_str().contains("#0000ff"));
+    /// ```
+    pub fn coloring(mut self, coloring: Coloring) -> Self {
+        self.coloring = coloring;
+        self
+    }
+
+    /// Returns the side length of each cell of the given board, in pixels
+    fn cell_size_for(&self, grid: &RectangularBoard) -> f32 {
+        let target_width = match self.target_width {
+            Some(target_width) => target_width - 2.0 * self.padding,
+            None => return self.cell_size,
+        };
+
+        let cell_size = match grid.lattice() {
+            Lattice::Square => (target_width + self.gap) / grid.width as f32 - self.gap,
+            // each row is half a triangle wider than its number of cells
+            Lattice::Triangular => 2.0 * target_width / (grid.width + 1) as f32,
+        };
+
+        // the image can't be made any narrower than its padding and gaps
+        cell_size.max(0.0)
+    }
+
+    fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+}
+
+/// Options controlling how the animations drawn by `render_tiling_animation` play
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::render::{AnimationOptions, RenderOptions};
+///
+/// // place a tile every quarter of a second, then play the animation just once
+/// let options = AnimationOptions::new()
+///     .render_options(RenderOptions::new().seed(7))
+///     .step(0.25)
+///     .repeat(false);
+/// ```
+#[derive(Clone, Debug)]
+pub struct AnimationOptions {
+    render: RenderOptions,
+    step: f32,
+    hold: f32,
+    repeat: bool,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        AnimationOptions {
+            render: RenderOptions::default(),
+            step: 0.5,
+            hold: 2.0,
+            repeat: true,
+        }
+    }
+}
+
+impl AnimationOptions {
+    pub fn new() -> Self {
+        AnimationOptions::default()
+    }
+
+    /// Sets the options used to draw the tiles
+    pub fn render_options(mut self, render: RenderOptions) -> Self {
+        self.render = render;
+        self
+    }
+
+    /// Sets the time between placing one tile and the next, in seconds
+    pub fn step(mut self, step: f32) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Sets how long the complete tiling is shown before the animation starts again, in seconds
+    pub fn hold(mut self, hold: f32) -> Self {
+        self.hold = hold;
+        self
+    }
+
+    /// Sets whether the animation starts again once it's finished, rather than stopping at
+    /// the complete tiling
+    pub fn repeat(mut self, repeat: bool) -> Self {
+        self.repeat = repeat;
+        self
+    }
+}
+
+/// The ways of giving the colours of the palette to the tiles of a rendered tiling
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Coloring {
+    /// Colours are taken from the palette in turn, in the order the tiles were placed,
+    /// starting from a colour chosen at random
+    #[default]
+    Cycle,
+
+    /// Tiles with the same shape share a colour, whichever way round they lie
+    Shape,
+
+    /// Tiles with the same shape share a colour only if they lie the same way round
+    Orientation,
+
+    /// Tiles are coloured greedily so that no two tiles sharing an edge have the same colour.
+    /// At most six colours are needed on a board without wrap around, so this is guaranteed
+    /// with palettes of six or more colours (the default palette has seven).
+    Proper,
+}
+
+/// The text labels that can be drawn on the tiles of a rendered tiling
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LabelKind {
+    /// Tiles aren't labelled
+    #[default]
+    None,
+
+    /// Tiles are numbered from 1 in the order they were placed
+    PlacementOrder,
+
+    /// Tiles are labelled with a letter for their shape, whichever way round they lie,
+    /// followed by a number for the way round they lie
+    TileShape,
+}
+
+/// The image formats that tilings can be rendered to
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ImageFormat {
+    #[default]
+    Svg,
+    #[cfg(feature = "png")]
+    Png,
+}
+
+impl ImageFormat {
+    /// Returns the file extension used for images in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Svg => "svg",
+            #[cfg(feature = "png")]
+            ImageFormat::Png => "png",
+        }
+    }
+
+    /// Renders a tiling in this format, see `render_single_tiling_from_vec`
+    pub fn render<B: Board>(&self, boards: Vec<&B>, options: &RenderOptions) -> Vec<u8> {
+        match self {
+            ImageFormat::Svg => render_single_tiling_from_vec(boards, options).into_bytes(),
+            #[cfg(feature = "png")]
+            ImageFormat::Png => render_single_tiling_png(boards, options),
+        }
+    }
+}
+
+/// An SVG image of a rendered tiling, which can be written to a file or embedded directly
+/// into a web page
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SvgString(String);
+
+impl SvgString {
+    /// Returns the SVG markup
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the SVG markup, consuming the image
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl AsRef<str> for SvgString {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<SvgString> for String {
+    fn from(svg: SvgString) -> Self {
+        svg.0
+    }
+}
+
+impl fmt::Display for SvgString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Renders a tiling as an SVG image.  This needs neither the filesystem nor any threads,
+/// so it can be used from a browser.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::board::RectangularBoard;
+/// use dcc_tiler::render::{render_tiling_svg, RenderOptions};
+/// use dcc_tiler::tiling::{TilePlacement, Tiling};
+///
+/// let board = RectangularBoard::new(2, 1);
+/// let tiling = Tiling::from_placements(board, vec![TilePlacement::new(vec![(0, 0), (0, 1)])]);
+///
+/// let svg = render_tiling_svg(&tiling, &RenderOptions::new());
+/// assert!(svg.as_str().starts_with("<svg"));
+/// ```
+pub fn render_tiling_svg(tiling: &Tiling, options: &RenderOptions) -> SvgString {
+    let boards = tiling.boards();
+
+    SvgString(render_single_tiling_from_vec(
+        boards.iter().collect(),
+        options,
+    ))
+}
+
+/// Renders an animated SVG image of a tiling being built, with its tiles appearing one at a
+/// time in the order they were placed.  The animation uses SMIL, so it plays in browsers
+/// without any scripts, and ends on the same image as `render_tiling_svg` draws with the
+/// same render options.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::board::RectangularBoard;
+/// use dcc_tiler::render::{render_tiling_animation, AnimationOptions};
+/// use dcc_tiler::tiling::{TilePlacement, Tiling};
+///
+/// let board = RectangularBoard::new(2, 2);
+/// let tiling = Tiling::from_placements(
+///     board,
+///     vec![
+///         TilePlacement::new(vec![(0, 0), (0, 1)]),
+///         TilePlacement::new(vec![(1, 0), (1, 1)]),
+///     ],
+/// );
+///
+/// let options = AnimationOptions::new().step(1.0).hold(0.0);
+/// let svg = render_tiling_animation(&tiling, &options);
+///
+/// // the second tile appears halfway through the two second animation
+/// assert_eq!(svg.as_str().matches("<animate ").count(), 2);
+/// assert!(svg.as_str().contains(r#"keyTimes="0;0.5" dur="2s""#));
+/// ```
+pub fn render_tiling_animation(tiling: &Tiling, options: &AnimationOptions) -> SvgString {
+    let boards = tiling.boards();
+    let tile_hashmap = tile_sources(boards.iter().collect());
+    let drawing = draw_tiling(boards.last().unwrap(), &tile_hashmap, &options.render);
+
+    let duration = drawing.tiles.len() as f32 * options.step + options.hold;
+    let repeat = if options.repeat { "indefinite" } else { "1" };
+
+    // each tile is hidden until its turn comes, and then stays in place until the end
+    let content = drawing
+        .tiles
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(placed, tile)| {
+            let appears = placed as f32 * options.step;
+
+            format!(
+                "<g opacity=\"0\">\n<animate attributeName=\"opacity\" values=\"0;1\" keyTimes=\"0;{}\" dur=\"{}s\" calcMode=\"discrete\" repeatCount=\"{}\" fill=\"freeze\"/>\n{}</g>\n",
+                if duration > 0.0 { appears / duration } else { 0.0 },
+                duration,
+                repeat,
+                tile
+            )
+        })
+        .collect();
+
+    SvgString(drawing.to_svg(content))
+}
+
+/// Renders a contact sheet of many tilings side by side in one SVG image, laid out in rows
+/// of `columns` tilings (in the given order), so that they can be looked over at a glance.
+/// Each tiling is drawn as `render_tiling_svg` would, in a space as large as the largest of
+/// them.
+///
+/// # Panics
+///
+/// Will panic if `columns` is zero
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::board::RectangularBoard;
+/// use dcc_tiler::render::{render_tiling_grid, RenderOptions};
+/// use dcc_tiler::tile::{Tile, TileCollection};
+/// use dcc_tiler::tiler::Tiler;
+///
+/// let tiler = Tiler::new(TileCollection::from(Tile::domino()), RectangularBoard::new(4, 2));
+/// let tilings: Vec<_> = tiler.tilings().collect();
+/// assert_eq!(tilings.len(), 5);
+///
+/// // each 4x2 tiling is 220x120 pixels, so three columns of two rows is 660x240 pixels
+/// let sheet = render_tiling_grid(&tilings, 3, &RenderOptions::new());
+/// assert!(sheet.as_str().starts_with(r#"<svg width="660" height="240""#));
+/// assert_eq!(sheet.as_str().matches("<g transform=").count(), 5);
+/// ```
+pub fn render_tiling_grid(
+    tilings: &[Tiling],
+    columns: usize,
+    options: &RenderOptions,
+) -> SvgString {
+    assert!(columns > 0);
+
+    let drawings: Vec<Drawing> = tilings
+        .iter()
+        .map(|tiling| {
+            let boards = tiling.boards();
+            draw_tiling(
+                boards.last().unwrap(),
+                &tile_sources(boards.iter().collect()),
+                options,
+            )
+        })
+        .collect();
+
+    let width = drawings.iter().map(|d| d.width.ceil()).fold(0.0, f32::max);
+    let height = drawings.iter().map(|d| d.height.ceil()).fold(0.0, f32::max);
+    let rows = drawings.len().div_ceil(columns);
+
+    let content: String = drawings
+        .iter()
+        .enumerate()
+        .map(|(i, drawing)| {
+            format!(
+                "<g transform=\"translate({},{})\">\n{}{}{}</g>\n",
+                (i % columns) as f32 * width,
+                (i / columns) as f32 * height,
+                drawing.background,
+                drawing.board,
+                drawing.tiles.concat()
+            )
+        })
+        .collect();
+
+    let sheet = Drawing {
+        width: columns.min(drawings.len()) as f32 * width,
+        height: rows as f32 * height,
+        background: String::new(),
+        board: String::new(),
+        tiles: Vec::new(),
+    };
+
+    SvgString(sheet.to_svg(content))
+}
+
+/// Renders a contact sheet of many tilings as a PNG image, see `render_tiling_grid`
+#[cfg(feature = "png")]
+pub fn render_tiling_grid_png(
+    tilings: &[Tiling],
+    columns: usize,
+    options: &RenderOptions,
+) -> Vec<u8> {
+    svg_to_png(render_tiling_grid(tilings, columns, options).as_str())
+}
+
+/// Renders a tiling as a PNG image, returning the encoded image.  The image has the
+/// same size (in pixels) as the SVG rendered with the same options.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::board::RectangularBoard;
+/// use dcc_tiler::render::{render_single_tiling_png, RenderOptions};
+/// use dcc_tiler::tile::{Tile, TileCollection};
+///
+/// let board = RectangularBoard::new(2, 1);
+/// let tiled = board.place_tile(&TileCollection::from(Tile::domino())).remove(0);
+///
+/// let png = render_single_tiling_png(vec![&board, &tiled], &RenderOptions::new());
+/// assert_eq!(&png[1..4], b"PNG");
+/// ```
+#[cfg(feature = "png")]
+pub fn render_single_tiling_png<B: Board>(boards: Vec<&B>, options: &RenderOptions) -> Vec<u8> {
+    svg_to_png(&render_single_tiling_from_vec(boards, options))
+}
+
+/// Draws an SVG image as a PNG image of the same size (in pixels)
+#[cfg(feature = "png")]
+fn svg_to_png(svg: &str) -> Vec<u8> {
+    // finding the system fonts takes a while, so only do so for labelled tiles
+    let mut options = usvg::Options::default();
+    if svg.contains("<text") {
+        let fonts = options.fontdb_mut();
+        fonts.load_system_fonts();
+
+        // labels are drawn in the generic sans-serif font, which is Arial unless told otherwise,
+        // so fall back to another installed font rather than leaving the labels out
+        let query = usvg::fontdb::Query {
+            families: &[usvg::fontdb::Family::SansSerif],
+            ..Default::default()
+        };
+        if fonts.query(&query).is_none() {
+            let families: Vec<&String> = fonts
+                .faces()
+                .filter_map(|face| face.families.first().map(|(name, _)| name))
+                .collect();
+            let fallback = families
+                .iter()
+                .find(|name| name.contains("Sans"))
+                .or(families.first())
+                .map(|name| name.to_string());
+
+            if let Some(fallback) = fallback {
+                fonts.set_sans_serif_family(fallback);
+            }
+        }
+    }
+
+    let tree = usvg::Tree::from_str(svg, &options).expect("Unable to parse rendered SVG");
+    let size = tree.size().to_int_size();
+
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size.width(), size.height()).expect("Unable to create image");
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    pixmap.encode_png().expect("Unable to encode PNG")
+}
+
+pub fn render_single_tiling_from_vec<B: Board>(boards: Vec<&B>, options: &RenderOptions) -> String {
+    let last = *boards.last().unwrap();
+
+    render_single_tiling(last, &tile_sources(boards), options)
+}
+
+/// Maps each board after the first to the board before it, as `render_single_tiling` expects
+fn tile_sources<B: Board>(boards: Vec<&B>) -> HashMap<B, Vec<B>> {
     let mut tile_hashmap = HashMap::new();
 
     for i in (1..boards.len()).rev() {
         tile_hashmap.insert(boards[i].clone(), vec![boards[i - 1].clone()]);
     }
 
-    render_single_tiling(boards.last().unwrap(), &tile_hashmap)
+    tile_hashmap
 }
 
-pub fn render_single_tiling<S: ::std::hash::BuildHasher>(
-    board: &RectangularBoard,
-    tile_hashmap: &HashMap<RectangularBoard, Vec<RectangularBoard>, S>,
+pub fn render_single_tiling<B: Board, S: ::std::hash::BuildHasher>(
+    board: &B,
+    tile_hashmap: &HashMap<B, Vec<B>, S>,
+    options: &RenderOptions,
 ) -> String {
-    // TODO: maybe remove gap_size now that we've implemented borders
-    let gap_size = 0.0;
-    let box_size = 50.0;
-    let padding = 10.0;
-
-    // TODO: make these configurable
-    let colors = vec![
-        Color(30, 56, 136),
-        Color(71, 115, 170),
-        Color(245, 230, 99),
-        Color(255, 173, 105),
-        Color(156, 56, 72),
-        Color(124, 178, 135),
-        Color(251, 219, 136),
-    ];
+    let drawing = draw_tiling(board, tile_hashmap, options);
 
-    let mut boxes = Vec::new();
-    let mut rng = rand::thread_rng();
+    drawing.to_svg(drawing.tiles.concat())
+}
+
+/// A rendered tiling, before being put together into an SVG image
+struct Drawing {
+    width: f32,
+    height: f32,
+
+    // the SVG markup filling in the background, if there is one
+    background: String,
+
+    // the SVG markup drawing the board being tiled, if it's shown
+    board: String,
+
+    // the SVG markup drawing each tile, starting with the last tile placed
+    tiles: Vec<String>,
+}
+
+impl Drawing {
+    /// Returns an SVG image of the background and the board followed by the given markup
+    fn to_svg(&self, content: String) -> String {
+        format!(
+            "<svg width=\"{}\" height=\"{}\" xmlns=\"http://www.w3.org/2000/svg\">\n{}{}{}</svg>\n",
+            self.width.ceil() as u32,
+            self.height.ceil() as u32,
+            self.background,
+            self.board,
+            content
+        )
+    }
+}
+
+/// Draws each tile of a tiling, see `render_single_tiling`
+fn draw_tiling<B: Board, S: ::std::hash::BuildHasher>(
+    board: &B,
+    tile_hashmap: &HashMap<B, Vec<B>, S>,
+    options: &RenderOptions,
+) -> Drawing {
+    if board.grid().lattice() == Lattice::Triangular {
+        return draw_triangles(board, tile_hashmap, options);
+    }
+
+    let gap_size = options.gap;
+    let box_size = options.cell_size_for(board.grid());
+    let padding = options.padding;
+
+    let color = |(r, g, b): (u8, u8, u8)| Color(r, g, b);
+    let colors: Vec<ColorAttr> = options.palette.iter().copied().map(color).collect();
+
+    let grid = board.grid();
+    let width = grid.width as f32 * (box_size + gap_size) - gap_size + 2.0 * padding;
+    let height = grid.height as f32 * (box_size + gap_size) - gap_size + 2.0 * padding;
+
+    let mut tiles = Vec::new();
+    let background = match options.background {
+        Some(background) => Fig::Rect(0.0, 0.0, width, height)
+            .styled(Attr::default().fill(color(background)))
+            .to_string(),
+        None => String::new(),
+    };
+    let mut rng = options.rng();
 
     // choose a random initial colour
     // we do this so that when you render a single tile, it won't always be the first colour in the colors vector
-    let mut color_index = rng.gen_range(0..colors.len());
-    let mut current = board;
+    let start = rng.gen_range(0..colors.len());
+    let placed = placed_tiles(board, tile_hashmap, &mut rng);
+    let tile_colors = tile_colors(grid, &placed, options.coloring, start, colors.len());
 
-    while let Some(board) = tile_hashmap.get(current) {
-        // choose a random source for this board state
-        let next_board = board.choose(&mut rng).unwrap();
-        let mut tiled_positions = HashSet::new();
+    let mut board_figures = Vec::new();
+    if let Some(shade) = options.board_shade {
+        let (region, marked) = initial_region(grid, &placed);
 
-        // compute the tile that was placed here
-        for y in 0..next_board.height {
-            for x in 0..next_board.width {
-                if next_board.board[y][x] ^ current.board[y][x] {
-                    // we just tiled this position
-                    tiled_positions.insert((x, y));
+        for (x, y) in marked {
+            board_figures.push(
+                Fig::Rect(
+                    (x as f32) * (box_size + gap_size) + padding,
+                    (y as f32) * (box_size + gap_size) + padding,
+                    box_size,
+                    box_size,
+                )
+                .styled(Attr::default().fill(color(shade))),
+            );
+        }
+
+        // outline each side of the region, running along the sides of its cells
+        for &(x, y) in &region {
+            let (left, bottom) = (
+                (x as f32) * (box_size + gap_size) + padding,
+                (y as f32) * (box_size + gap_size) + padding,
+            );
+            let (right, top) = (left + box_size, bottom + box_size);
+
+            let sides = [
+                (left, bottom, left, top, x.checked_sub(1).map(|x| (x, y))),
+                (right, bottom, right, top, Some((x + 1, y))),
+                (left, top, right, top, Some((x, y + 1))),
+                (
+                    left,
+                    bottom,
+                    right,
+                    bottom,
+                    y.checked_sub(1).map(|y| (x, y)),
+                ),
+            ];
+            for (xs, ys, xe, ye, neighbour) in sides.iter() {
+                if !neighbour.is_some_and(|cell| region.contains(&cell)) {
+                    board_figures.push(
+                        Fig::Line(*xs, *ys, *xe, *ye).styled(
+                            Attr::default()
+                                .stroke(color(options.border_color))
+                                .stroke_width(options.stroke_width),
+                        ),
+                    );
                 }
             }
         }
+    }
+
+    for (tiled_positions, color_index) in placed.iter().zip(tile_colors) {
+        if options.outlines {
+            // the sides of each cell which aren't shared with another cell of the tile, going
+            // clockwise around the cell, between corners numbered like the cells
+            let mut sides = Vec::new();
+            for &(x, y) in tiled_positions.iter() {
+                let (x, y) = (x as i32, y as i32);
+                let corners = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)];
+                let neighbours = [(x, y - 1), (x + 1, y), (x, y + 1), (x - 1, y)];
+
+                for i in 0..4 {
+                    let (nx, ny) = neighbours[i];
+                    if nx < 0 || ny < 0 || !tiled_positions.contains(&(nx as usize, ny as usize)) {
+                        sides.push((corners[i], corners[(i + 1) % 4]));
+                    }
+                }
+            }
+
+            // the corners lie in the middle of the gaps between cells, so move them half a
+            // gap into the tile to line up with the sides of its cells
+            let point = |(x, y): (i32, i32), inwards: (i32, i32)| {
+                (
+                    x as f32 * (box_size + gap_size) + padding - gap_size / 2.0
+                        + inwards.0 as f32 * gap_size / 2.0,
+                    y as f32 * (box_size + gap_size) + padding - gap_size / 2.0
+                        + inwards.1 as f32 * gap_size / 2.0,
+                )
+            };
+
+            tiles.push(outline_path(
+                sides,
+                point,
+                Attr::default()
+                    .fill(colors[color_index])
+                    .stroke(color(options.border_color))
+                    .stroke_width(options.stroke_width),
+            ));
+            continue;
+        }
+
+        let mut boxes = Vec::new();
 
         for (x, y) in tiled_positions.iter() {
             // draw the underlying box
@@ -99,11 +919,11 @@ pub fn render_single_tiling<S: ::std::hash::BuildHasher>(
                 b = b.styled(
                     Attr::default()
                         .stroke(if gray {
-                            Color(211, 211, 211)
+                            color(options.inner_border_color)
                         } else {
-                            Color(0, 0, 0)
+                            color(options.border_color)
                         })
-                        .stroke_width(0.5),
+                        .stroke_width(options.stroke_width),
                 );
 
                 b
@@ -139,16 +959,550 @@ pub fn render_single_tiling<S: ::std::hash::BuildHasher>(
             ));
         }
 
-        // increment the color index by 1
-        color_index = (color_index + 1) % colors.len();
+        tiles.push(boxes.iter().map(ToString::to_string).collect());
+    }
 
+    let centre = |(x, y): (usize, usize)| {
+        (
+            (x as f32) * (box_size + gap_size) + padding + box_size / 2.0,
+            (y as f32) * (box_size + gap_size) + padding + box_size / 2.0,
+        )
+    };
+    let labels = tile_labels(grid, &placed, options.labels);
+    for ((markup, cells), label) in tiles.iter_mut().zip(&placed).zip(labels) {
+        let spacing = box_size + gap_size;
+        markup.push_str(&label_text(&label, cells, centre, spacing, box_size * 0.4));
+    }
+
+    Drawing {
+        width,
+        height,
+        background,
+        board: board_figures.iter().map(ToString::to_string).collect(),
+        tiles,
+    }
+}
+
+/// A set of (column, row) cells
+type Cells = BTreeSet<(usize, usize)>;
+
+/// Returns the (column, row) cells of the region being tiled, which are either covered by
+/// one of the given tiles or not yet marked, and the cells which were already marked on the
+/// initial board
+fn initial_region(grid: &RectangularBoard, tiles: &[Cells]) -> (Cells, Vec<(usize, usize)>) {
+    let mut region: Cells = tiles.iter().flatten().copied().collect();
+    let mut marked = Vec::new();
+
+    for (y, row) in grid.board.iter().enumerate() {
+        for (x, is_marked) in row.iter().enumerate() {
+            if !is_marked {
+                region.insert((x, y));
+            } else if !region.contains(&(x, y)) {
+                marked.push((x, y));
+            }
+        }
+    }
+
+    (region, marked)
+}
+
+/// Walks back from `board` to the initial board, choosing a random source for each board
+/// state, and returns the (column, row) cells covered by each tile along the way
+fn placed_tiles<B: Board, S: ::std::hash::BuildHasher, R: Rng>(
+    board: &B,
+    tile_hashmap: &HashMap<B, Vec<B>, S>,
+    rng: &mut R,
+) -> Vec<BTreeSet<(usize, usize)>> {
+    let mut tiles = Vec::new();
+    let mut current = board;
+
+    while let Some(board) = tile_hashmap.get(current) {
+        // choose a random source for this board state
+        let next_board = board.choose(rng).unwrap();
+
+        // compute the tile that was placed here
+        let tiled_positions = next_board.grid().diff(current.grid());
+        tiles.push(tiled_positions.into_iter().map(|(y, x)| (x, y)).collect());
         current = next_board;
     }
 
-    Svg(
-        vec![Fig::Multiple(boxes)],
-        (50 * board.width) as u32 + 2 * (padding as u32),
-        (50 * board.height) as u32 + 2 * (padding as u32),
+    tiles
+}
+
+/// Chooses the colour (as an index into a palette of `palette` colours) of each of the
+/// (column, row) cells covered by the tiles returned by `placed_tiles`.  Cycling colours
+/// starts from the colour numbered `start`.
+fn tile_colors(
+    grid: &RectangularBoard,
+    tiles: &[BTreeSet<(usize, usize)>],
+    coloring: Coloring,
+    start: usize,
+    palette: usize,
+) -> Vec<usize> {
+    let cell_tile = |cells| cell_tile(grid, cells);
+
+    let keys: Vec<Vec<(i32, i32)>> = match coloring {
+        Coloring::Cycle => return (0..tiles.len()).map(|i| (start + i) % palette).collect(),
+        Coloring::Proper => return proper_colors(grid, tiles, palette),
+        Coloring::Shape => tiles
+            .iter()
+            .map(|cells| shape_key(&cell_tile(cells)))
+            .collect(),
+        Coloring::Orientation => tiles
+            .iter()
+            .map(|cells| cell_tile(cells).cells().to_vec())
+            .collect(),
+    };
+
+    // number the shapes in sorted order, so that each shape gets the same colour in every
+    // tiling it appears in alongside the same other shapes
+    let shapes: Vec<&Vec<(i32, i32)>> = keys.iter().collect::<BTreeSet<_>>().into_iter().collect();
+
+    keys.iter()
+        .map(|key| shapes.binary_search(&key).unwrap() % palette)
+        .collect()
+}
+
+/// Returns the tile covering the given (column, row) cells of a board
+fn cell_tile(grid: &RectangularBoard, cells: &Cells) -> CellTile {
+    CellTile::with_lattice(
+        cells.iter().map(|&(x, y)| (y as i32, x as i32)),
+        grid.lattice(),
+    )
+}
+
+/// Returns the label of each of the given tiles, which are in the reverse of the order they
+/// were placed in
+fn tile_labels(grid: &RectangularBoard, tiles: &[Cells], kind: LabelKind) -> Vec<String> {
+    let count = tiles.len();
+
+    match kind {
+        LabelKind::None => vec![String::new(); count],
+        LabelKind::PlacementOrder => (0..count).map(|i| (count - i).to_string()).collect(),
+        LabelKind::TileShape => {
+            let keys: Vec<_> = tiles
+                .iter()
+                .map(|cells| {
+                    let tile = cell_tile(grid, cells);
+                    (shape_key(&tile), tile.cells().to_vec())
+                })
+                .collect();
+
+            // shapes and their orientations are numbered in sorted order, as for colours
+            let shapes: Vec<&Vec<(i32, i32)>> = keys
+                .iter()
+                .map(|(shape, _)| shape)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            let orientations: Vec<_> = keys.iter().collect::<BTreeSet<_>>().into_iter().collect();
+
+            keys.iter()
+                .map(|key| {
+                    let shape = shapes.binary_search(&&key.0).unwrap();
+                    let first = orientations.partition_point(|(other, _)| *other < key.0);
+                    let orientation = orientations.binary_search(&key).unwrap() - first;
+
+                    format!("{}{}", shape_letters(shape), orientation + 1)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Returns the letters naming the `n`th shape: A to Z, then AA, AB and so on
+fn shape_letters(mut n: usize) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push(b'A' + (n % 26) as u8);
+        if n < 26 {
+            break;
+        }
+        n = n / 26 - 1;
+    }
+
+    letters.iter().rev().map(|&b| b as char).collect()
+}
+
+/// Returns an SVG text element with the given label, centred on whichever centre of a cell of
+/// a tile, or middle of a side between two of its cells, is nearest to the middle of the tile,
+/// so that it lies inside the tile whatever its shape.  Cells are positioned by `centre`, and
+/// the centres of neighbouring cells are `spacing` apart.
+fn label_text<F>(label: &str, cells: &Cells, centre: F, spacing: f32, font_size: f32) -> String
+where
+    F: Fn((usize, usize)) -> (f32, f32),
+{
+    if label.is_empty() || cells.is_empty() {
+        return String::new();
+    }
+
+    let centres: Vec<(f32, f32)> = cells.iter().map(|&cell| centre(cell)).collect();
+    let count = centres.len() as f32;
+    let middle = (
+        centres.iter().map(|c| c.0).sum::<f32>() / count,
+        centres.iter().map(|c| c.1).sum::<f32>() / count,
+    );
+    let distance = |c: &(f32, f32)| (c.0 - middle.0).powi(2) + (c.1 - middle.1).powi(2);
+
+    // cells are neighbours if their centres are `spacing` apart, allowing for rounding
+    let mut places = centres.clone();
+    for (i, a) in centres.iter().enumerate() {
+        for b in &centres[i + 1..] {
+            if (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) <= spacing * spacing * 1.01 {
+                places.push(((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0));
+            }
+        }
+    }
+
+    let (x, y) = places
+        .into_iter()
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+        .unwrap();
+
+    format!(
+        "<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"sans-serif\" \
+         text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+        x, y, font_size, label
     )
-    .to_string()
+}
+
+/// Returns the same cells for every rotation and reflection of a tile
+fn shape_key(tile: &CellTile) -> Vec<(i32, i32)> {
+    tile.canonical_form(TileSymmetry::All).cells().to_vec()
+}
+
+/// Colours the tiles so that tiles sharing an edge have different colours, wrapping around
+/// the palette if it runs out.  The tiles are coloured in smallest last order (repeatedly
+/// setting aside a tile with the fewest neighbours not yet set aside, and colouring them in
+/// the reverse order), so that a planar map never needs more than six colours.
+fn proper_colors(
+    grid: &RectangularBoard,
+    tiles: &[BTreeSet<(usize, usize)>],
+    palette: usize,
+) -> Vec<usize> {
+    let mut owners = HashMap::new();
+    for (i, cells) in tiles.iter().enumerate() {
+        for cell in cells {
+            owners.insert(*cell, i);
+        }
+    }
+
+    let neighbours: Vec<BTreeSet<usize>> = tiles
+        .iter()
+        .enumerate()
+        .map(|(i, cells)| {
+            cells
+                .iter()
+                .flat_map(|&(x, y)| grid.neighbours(Position::new(y as isize, x as isize)))
+                .filter_map(|p| owners.get(&(p.y() as usize, p.x() as usize)).copied())
+                .filter(|j| *j != i)
+                .collect()
+        })
+        .collect();
+
+    let mut degrees: Vec<usize> = neighbours.iter().map(BTreeSet::len).collect();
+    let mut remaining: BTreeSet<(usize, usize)> =
+        degrees.iter().enumerate().map(|(i, d)| (*d, i)).collect();
+    let mut order = Vec::with_capacity(tiles.len());
+
+    while let Some(&(degree, i)) = remaining.iter().next() {
+        remaining.remove(&(degree, i));
+        order.push(i);
+
+        for &j in &neighbours[i] {
+            if remaining.remove(&(degrees[j], j)) {
+                degrees[j] -= 1;
+                remaining.insert((degrees[j], j));
+            }
+        }
+    }
+
+    let mut colors: Vec<Option<usize>> = vec![None; tiles.len()];
+    for &i in order.iter().rev() {
+        let taken: BTreeSet<usize> = neighbours[i].iter().filter_map(|j| colors[*j]).collect();
+        colors[i] = (0..).find(|color| !taken.contains(color));
+    }
+
+    colors
+        .into_iter()
+        .map(|color| color.unwrap() % palette)
+        .collect()
+}
+
+/// Draws a tiling of a board on the triangular lattice, see `render_single_tiling`.  Each
+/// cell is drawn as a triangle whose sides are the cell size, and the gap is ignored.
+fn draw_triangles<B: Board, S: ::std::hash::BuildHasher>(
+    board: &B,
+    tile_hashmap: &HashMap<B, Vec<B>, S>,
+    options: &RenderOptions,
+) -> Drawing {
+    let side = options.cell_size_for(board.grid());
+    let row_height = side * 3f32.sqrt() / 2.0;
+    let padding = options.padding;
+
+    let color = |(r, g, b): (u8, u8, u8)| Color(r, g, b);
+    let colors: Vec<ColorAttr> = options.palette.iter().copied().map(color).collect();
+
+    // each row is half a triangle wider than its number of cells
+    let grid = board.grid();
+    let width = (grid.width + 1) as f32 * side / 2.0 + 2.0 * padding;
+    let height = grid.height as f32 * row_height + 2.0 * padding;
+
+    let mut tiles = Vec::new();
+    let background = match options.background {
+        Some(background) => Fig::Rect(0.0, 0.0, width, height)
+            .styled(Attr::default().fill(color(background)))
+            .to_string(),
+        None => String::new(),
+    };
+    let mut rng = options.rng();
+
+    // as for square cells, start from a random colour
+    let start = rng.gen_range(0..colors.len());
+    let placed = placed_tiles(board, tile_hashmap, &mut rng);
+    let tile_colors = tile_colors(grid, &placed, options.coloring, start, colors.len());
+
+    let mut board_markup = String::new();
+    if let Some(shade) = options.board_shade {
+        let (region, marked) = initial_region(grid, &placed);
+
+        let mut shaded = String::new();
+        for (x, y) in marked {
+            shaded.push_str(&polygon(&triangle(x, y, side, padding).0));
+        }
+        board_markup.push_str(&format!(
+            "<g style=\"{}\">\n{}</g>\n",
+            Attr::default().fill(color(shade)),
+            shaded
+        ));
+
+        for &(x, y) in &region {
+            for (start, end, neighbour) in triangle(x, y, side, padding).1.iter() {
+                if !neighbour.is_some_and(|cell| region.contains(&cell)) {
+                    board_markup.push_str(
+                        &Fig::Line(start.0, start.1, end.0, end.1)
+                            .styled(
+                                Attr::default()
+                                    .stroke(color(options.border_color))
+                                    .stroke_width(options.stroke_width),
+                            )
+                            .to_string(),
+                    );
+                }
+            }
+        }
+    }
+
+    for (tiled_positions, color_index) in placed.iter().zip(tile_colors) {
+        if options.outlines {
+            // the corners of the triangles are numbered by half sides across and rows down
+            let mut sides = Vec::new();
+            for &(x, y) in tiled_positions.iter() {
+                let corners = triangle_corners(x, y);
+
+                for (i, (corner, neighbour)) in corners.iter().enumerate() {
+                    if !neighbour.is_some_and(|cell| tiled_positions.contains(&cell)) {
+                        sides.push((*corner, corners[(i + 1) % 3].0));
+                    }
+                }
+            }
+
+            let point = |(x, y): (i32, i32), _| {
+                (
+                    x as f32 * side / 2.0 + padding,
+                    y as f32 * row_height + padding,
+                )
+            };
+
+            tiles.push(outline_path(
+                sides,
+                point,
+                Attr::default()
+                    .fill(colors[color_index])
+                    .stroke(color(options.border_color))
+                    .stroke_width(options.stroke_width),
+            ));
+            continue;
+        }
+
+        let mut triangles = String::new();
+        let mut borders = Vec::new();
+
+        for &(x, y) in tiled_positions.iter() {
+            let (corners, sides) = triangle(x, y, side, padding);
+            triangles.push_str(&polygon(&corners));
+
+            // the sides between two cells of the same tile are drawn in grey
+            for (start, end, neighbour) in sides.iter() {
+                let inner = neighbour.is_some_and(|cell| tiled_positions.contains(&cell));
+
+                borders.push(
+                    Fig::Line(start.0, start.1, end.0, end.1)
+                        .styled(
+                            Attr::default()
+                                .stroke(if inner {
+                                    color(options.inner_border_color)
+                                } else {
+                                    color(options.border_color)
+                                })
+                                .stroke_width(options.stroke_width),
+                        )
+                        .to_string(),
+                );
+            }
+        }
+
+        tiles.push(format!(
+            "<g style=\"{}\">\n{}</g>\n{}",
+            Attr::default().fill(colors[color_index]),
+            triangles,
+            borders.concat()
+        ));
+    }
+
+    let centre = |(x, y): (usize, usize)| {
+        let corners = triangle(x, y, side, padding).0;
+        (
+            corners.iter().map(|c| c.0).sum::<f32>() / 3.0,
+            corners.iter().map(|c| c.1).sum::<f32>() / 3.0,
+        )
+    };
+    let labels = tile_labels(grid, &placed, options.labels);
+    for ((markup, cells), label) in tiles.iter_mut().zip(&placed).zip(labels) {
+        // twice the distance from the centre of a triangle to its sides
+        let spacing = side / 3f32.sqrt();
+        markup.push_str(&label_text(&label, cells, centre, spacing, side * 0.25));
+    }
+
+    Drawing {
+        width,
+        height,
+        background,
+        board: board_markup,
+        tiles,
+    }
+}
+
+/// A side of a triangle, given by its ends and the (column, row) cell on its other side
+type TriangleSide = ((f32, f32), (f32, f32), Option<(usize, usize)>);
+
+/// Returns the corners of the (column, row) triangle drawn with sides of length `side`, its
+/// horizontal side first, together with its sides
+fn triangle(x: usize, y: usize, side: f32, padding: f32) -> ([(f32, f32); 3], [TriangleSide; 3]) {
+    let row_height = side * 3f32.sqrt() / 2.0;
+    let left = x as f32 * side / 2.0 + padding;
+    let (top, bottom) = (
+        y as f32 * row_height + padding,
+        (y + 1) as f32 * row_height + padding,
+    );
+
+    // the cell on the other side of the horizontal side is below a triangle pointing up,
+    // and above a triangle pointing down
+    let up = Lattice::points_up(y as isize, x as isize);
+    let (level, apex, across) = if up {
+        (bottom, top, Some((x, y + 1)))
+    } else {
+        (top, bottom, y.checked_sub(1).map(|y| (x, y)))
+    };
+    let corners = [
+        (left, level),
+        (left + side, level),
+        (left + side / 2.0, apex),
+    ];
+
+    let sides = [
+        (corners[0], corners[1], across),
+        (corners[0], corners[2], x.checked_sub(1).map(|x| (x, y))),
+        (corners[1], corners[2], Some((x + 1, y))),
+    ];
+
+    (corners, sides)
+}
+
+/// A corner of the cells of a board, numbered along each axis
+type Corner = (i32, i32);
+
+/// Returns the corners of the (column, row) triangle going clockwise, numbered by half sides
+/// across and rows down, each together with the cell on the other side of the side starting
+/// at that corner
+fn triangle_corners(x: usize, y: usize) -> [(Corner, Option<(usize, usize)>); 3] {
+    let (left, top) = (x as i32, y as i32);
+    let left_neighbour = x.checked_sub(1).map(|x| (x, y));
+
+    if Lattice::points_up(y as isize, x as isize) {
+        [
+            ((left + 1, top), Some((x + 1, y))),
+            ((left + 2, top + 1), Some((x, y + 1))),
+            ((left, top + 1), left_neighbour),
+        ]
+    } else {
+        [
+            ((left, top), y.checked_sub(1).map(|y| (x, y))),
+            ((left + 2, top), Some((x + 1, y))),
+            ((left + 1, top + 1), left_neighbour),
+        ]
+    }
+}
+
+/// Returns an SVG path around the outline of a tile, given the sides of its cells which lie
+/// on its outline, each going clockwise around its cell.  The corners of the outline are
+/// positioned by `point`, which is also given the direction (one step along each axis)
+/// pointing into the tile from each corner of a tile of square cells.
+fn outline_path<F>(sides: Vec<(Corner, Corner)>, point: F, style: Attr) -> String
+where
+    F: Fn(Corner, (i32, i32)) -> (f32, f32),
+{
+    let mut leaving: HashMap<Corner, Vec<Corner>> = HashMap::new();
+    for (start, end) in &sides {
+        leaving.entry(*start).or_default().push(*end);
+    }
+
+    let direction =
+        |(ax, ay): (i32, i32), (bx, by): (i32, i32)| ((bx - ax).signum(), (by - ay).signum());
+    let mut path = String::new();
+
+    // follow the sides around each loop of the outline (a tile with holes has several)
+    for &(first, _) in &sides {
+        let mut corners = vec![first];
+        while let Some(next) = leaving.get_mut(corners.last().unwrap()).and_then(Vec::pop) {
+            if next == first {
+                break;
+            }
+            corners.push(next);
+        }
+        if corners.len() < 3 {
+            continue;
+        }
+
+        let mut started = false;
+        for i in 0..corners.len() {
+            let previous = corners[(i + corners.len() - 1) % corners.len()];
+            let (corner, next) = (corners[i], corners[(i + 1) % corners.len()]);
+            let (into, out) = (direction(previous, corner), direction(corner, next));
+
+            // skip corners where the outline goes straight on
+            if into.0 * out.1 == into.1 * out.0 {
+                continue;
+            }
+
+            // the inside of the tile is to the right of each side (going clockwise)
+            let inwards = (-into.1 - out.1, into.0 + out.0);
+            let (x, y) = point(corner, inwards);
+            path += &format!("{}{} {}", if started { "L" } else { "M" }, x, y);
+            started = true;
+        }
+        path += "Z";
+    }
+
+    format!("<path d=\"{}\" style=\"{}\"/>\n", path, style)
+}
+
+/// Returns the SVG markup for a polygon with the given corners
+fn polygon(corners: &[(f32, f32)]) -> String {
+    let points: Vec<String> = corners
+        .iter()
+        .map(|(x, y)| format!("{},{}", x, y))
+        .collect();
+
+    format!("<polygon points=\"{}\"/>\n", points.join(" "))
 }
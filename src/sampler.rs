@@ -0,0 +1,3 @@
+//! Random samplers for tilings of boards which are too large to enumerate
+
+pub mod glauber;
@@ -0,0 +1,148 @@
+//! Glauber (flip) dynamics for domino tilings.
+//!
+//! Two dominoes covering a 2x2 block, either both horizontal or both vertical, can be
+//! rotated to cover the same block the other way.  Any two domino tilings of a simply
+//! connected region are joined by a sequence of such flips, so repeatedly choosing a random
+//! block and flipping it (if possible) is a Markov chain whose stationary distribution is
+//! uniform over the tilings.  Running the chain for long enough samples a random tiling of
+//! boards such as large Aztec diamonds, which have far too many tilings to enumerate.
+
+use crate::board::{Boundary, Lattice, RectangularBoard};
+use crate::error::TilerError;
+use crate::tiling::{TilePlacement, Tiling};
+use rand::Rng;
+
+/// A domino tiling evolving under random 2x2 flips
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::board::RectangularBoard;
+/// use dcc_tiler::sampler::glauber::GlauberSampler;
+/// use dcc_tiler::solver::ExactCover;
+/// use dcc_tiler::tile::{Tile, TileCollection};
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+///
+/// let board = RectangularBoard::aztec_diamond(4);
+/// let tiles = TileCollection::from(Tile::domino());
+///
+/// let mut start = None;
+/// ExactCover::new(&board, &tiles).for_each_tiling(|tiling| {
+///     start = Some(tiling);
+///     false
+/// });
+///
+/// let mut sampler = GlauberSampler::new(&start.unwrap()).unwrap();
+/// let flips = sampler.run(1000, &mut StdRng::seed_from_u64(1));
+/// assert!(flips > 0);
+///
+/// // the sample is still a tiling of the Aztec diamond
+/// assert!(sampler.tiling().to_board().is_all_marked());
+/// ```
+#[derive(Clone, Debug)]
+pub struct GlauberSampler {
+    initial: RectangularBoard,
+
+    // for each cell (numbered row by row), the other cell covered by the same domino, or
+    // None if the cell isn't covered by the tiling
+    partner: Vec<Option<usize>>,
+}
+
+impl GlauberSampler {
+    /// Starts the chain from the given tiling
+    ///
+    /// # Errors
+    ///
+    /// Returns `TilerError::NotDominoes` if any tile of the tiling isn't a domino, or the
+    /// board wraps around (where flips don't connect every tiling)
+    pub fn new(tiling: &Tiling) -> Result<Self, TilerError> {
+        let initial = tiling.initial_board().clone();
+        if initial.lattice() != Lattice::Square || initial.boundary() != Boundary::Bounded {
+            return Err(TilerError::NotDominoes);
+        }
+        let width = initial.width;
+        let mut partner = vec![None; width * initial.height];
+
+        for placement in tiling.placements() {
+            match placement.cells() {
+                &[(r1, c1), (r2, c2)] if r1.abs_diff(r2) + c1.abs_diff(c2) == 1 => {
+                    partner[r1 * width + c1] = Some(r2 * width + c2);
+                    partner[r2 * width + c2] = Some(r1 * width + c1);
+                }
+                _ => return Err(TilerError::NotDominoes),
+            }
+        }
+
+        Ok(GlauberSampler { initial, partner })
+    }
+
+    /// Makes a single step of the chain, returning whether the tiling changed.
+    ///
+    /// A random 2x2 block is chosen, and if it is covered by two parallel dominoes they
+    /// are rotated with probability 1/2.  (Leaving the tiling unchanged half the time
+    /// keeps the chain from alternating between two tilings.)
+    pub fn step<R: Rng>(&mut self, rng: &mut R) -> bool {
+        let (width, height) = (self.initial.width, self.initial.height);
+        if width < 2 || height < 2 {
+            return false;
+        }
+
+        let row = rng.gen_range(0..height - 1);
+        let col = rng.gen_range(0..width - 1);
+        if !rng.gen_bool(0.5) {
+            return false;
+        }
+
+        let top_left = row * width + col;
+        let (top_right, bottom_left, bottom_right) =
+            (top_left + 1, top_left + width, top_left + width + 1);
+
+        let (first, second) = if self.partner[top_left] == Some(top_right)
+            && self.partner[bottom_left] == Some(bottom_right)
+        {
+            // two horizontal dominoes become two vertical dominoes
+            ((top_left, bottom_left), (top_right, bottom_right))
+        } else if self.partner[top_left] == Some(bottom_left)
+            && self.partner[top_right] == Some(bottom_right)
+        {
+            // two vertical dominoes become two horizontal dominoes
+            ((top_left, top_right), (bottom_left, bottom_right))
+        } else {
+            return false;
+        };
+
+        for (a, b) in [first, second] {
+            self.partner[a] = Some(b);
+            self.partner[b] = Some(a);
+        }
+
+        true
+    }
+
+    /// Makes the given number of steps of the chain, returning how many of them changed
+    /// the tiling
+    pub fn run<R: Rng>(&mut self, steps: usize, rng: &mut R) -> usize {
+        (0..steps).filter(|_| self.step(rng)).count()
+    }
+
+    /// Returns the current tiling, with the dominoes ordered by their first cell
+    pub fn tiling(&self) -> Tiling {
+        let width = self.initial.width;
+        let cell = |index: usize| (index / width, index % width);
+
+        let placements = self
+            .partner
+            .iter()
+            .enumerate()
+            .filter_map(|(index, partner)| match partner {
+                Some(partner) if index < *partner => {
+                    Some(TilePlacement::new(vec![cell(index), cell(*partner)]))
+                }
+                _ => None,
+            })
+            .collect();
+
+        Tiling::from_placements(self.initial.clone(), placements)
+    }
+}
@@ -0,0 +1,522 @@
+use crate::board::{Board, RectangularBoard};
+use crate::tile::TileCollection;
+use crate::tiling::{TilePlacement, Tiling};
+use num::{BigUint, Zero};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// The algorithm used to count and search for tilings
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// A search over board states, placing each tile at the most constrained cell
+    #[default]
+    Search,
+
+    /// Knuth's Algorithm X, using dancing links to solve the exact cover problem
+    /// with a row for each tile placement and a column for each unmarked cell
+    Dlx,
+
+    /// The same exact cover problem as an integer linear program, with a 0-1 variable for
+    /// each tile placement, solved by branch and bound.  This can only find a single tiling,
+    /// so counting and rendering every tiling use `Search` instead.
+    #[cfg(feature = "ilp")]
+    Ilp,
+}
+
+/// A flag which can be shared between threads, used to ask a long running count or search
+/// to stop early, or which stops them once a deadline passes
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::solver::CancelToken;
+///
+/// let token = CancelToken::new();
+/// let shared = token.clone();
+///
+/// shared.cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    /// Asks every computation holding a clone of this token to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns a clone of this token which is also cancelled once the given deadline
+    /// passes.  Cancelling this token (or any of its clones) still cancels the new token,
+    /// but the deadline only applies to the new token and its own clones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::solver::CancelToken;
+    /// use std::time::Instant;
+    ///
+    /// let token = CancelToken::new();
+    /// let limited = token.with_deadline(Instant::now());
+    ///
+    /// assert!(limited.is_cancelled());
+    /// assert!(limited.deadline_passed());
+    /// assert!(!token.is_cancelled());
+    /// ```
+    pub fn with_deadline(&self, deadline: Instant) -> CancelToken {
+        CancelToken {
+            cancelled: Arc::clone(&self.cancelled),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// Determines whether `cancel` has been called on this token (or any of its clones),
+    /// or its deadline has passed
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || self.deadline_passed()
+    }
+
+    /// Determines whether the deadline given to `with_deadline` has passed
+    pub fn deadline_passed(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+/// The tiling problem for a board, phrased as an exact cover problem
+pub struct ExactCover {
+    board: RectangularBoard,
+    placements: Vec<Vec<(usize, usize)>>,
+    dlx: Dlx,
+
+    // the matrix given to `dlx`, kept to build the integer linear program from
+    #[cfg(feature = "ilp")]
+    primary: usize,
+    #[cfg(feature = "ilp")]
+    capacities: Vec<usize>,
+    #[cfg(feature = "ilp")]
+    rows: Vec<Vec<usize>>,
+}
+
+impl ExactCover {
+    /// Builds the exact cover problem for tiling the unmarked cells of `board` with `tiles`
+    pub fn new<B: Board>(board: &B, tiles: &TileCollection) -> Self {
+        let board = board.grid();
+
+        // each unmarked cell is a column which must be covered exactly once
+        let mut column_index = vec![vec![None; board.width]; board.height];
+        let mut columns = 0;
+
+        for (board_row, index_row) in board.board.iter().zip(column_index.iter_mut()) {
+            for (marked, index) in board_row.iter().zip(index_row.iter_mut()) {
+                if !marked {
+                    *index = Some(columns);
+                    columns += 1;
+                }
+            }
+        }
+
+        // each counted tile is a secondary column, which may be covered at most as many
+        // times as the tile can still be used
+        let capacities: Vec<usize> = tiles
+            .counts()
+            .iter()
+            .enumerate()
+            .map(|(c, count)| {
+                count.saturating_sub(board.used_counts().get(c).copied().unwrap_or(0))
+            })
+            .collect();
+        let (indices, placements): (Vec<usize>, Vec<Vec<(usize, usize)>>) =
+            board.indexed_placements(tiles).into_iter().unzip();
+        let rows: Vec<Vec<usize>> = placements
+            .iter()
+            .zip(indices)
+            .map(|(cells, index)| {
+                let mut row: Vec<usize> = cells
+                    .iter()
+                    .map(|(i, j)| column_index[*i][*j].unwrap())
+                    .collect();
+
+                if let Some(c) = tiles.count_index(index) {
+                    row.push(columns + c);
+                }
+
+                row
+            })
+            .collect();
+
+        ExactCover {
+            board: board.clone(),
+            dlx: Dlx::new(columns, &capacities, &rows),
+            placements,
+            #[cfg(feature = "ilp")]
+            primary: columns,
+            #[cfg(feature = "ilp")]
+            capacities,
+            #[cfg(feature = "ilp")]
+            rows,
+        }
+    }
+
+    /// Stops the search once the given token is cancelled.  A cancelled `count` returns the
+    /// number of tilings found so far, which is only a lower bound.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::solver::{CancelToken, ExactCover};
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let token = CancelToken::new();
+    /// token.cancel();
+    ///
+    /// let board = RectangularBoard::new(8, 8);
+    /// let tiles = TileCollection::from(Tile::domino());
+    /// let mut problem = ExactCover::new(&board, &tiles).with_cancel_token(token);
+    /// assert_eq!(problem.count(), 0u32.into());
+    /// ```
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Self {
+        self.dlx.cancel = Some(token);
+        self
+    }
+
+    /// Calls `f` with each tiling of the board.  The search stops early if `f` returns false.
+    pub fn for_each_tiling<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Tiling) -> bool,
+    {
+        let board = &self.board;
+        let placements = &self.placements;
+
+        self.dlx.search(|rows| {
+            let tiling = Tiling::from_placements(
+                board.clone(),
+                rows.iter()
+                    .map(|row| TilePlacement::new(placements[*row].clone()))
+                    .collect(),
+            );
+
+            f(tiling)
+        });
+    }
+
+    /// Returns the number of tilings of the board
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::solver::ExactCover;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let board = RectangularBoard::new(8, 8);
+    /// let tiles = TileCollection::from(Tile::t_tile(1));
+    /// assert_eq!(ExactCover::new(&board, &tiles).count(), 84u32.into());
+    /// ```
+    pub fn count(&mut self) -> BigUint {
+        let mut count = BigUint::zero();
+
+        self.dlx.search(|_| {
+            count += 1u32;
+            true
+        });
+
+        count
+    }
+
+    /// Finds a tiling of the board by solving the exact cover problem as an integer linear
+    /// program, or returns None if the board can't be tiled.
+    ///
+    /// Each placement is a variable which is 1 if the placement is used, every unmarked cell
+    /// gives a constraint that the placements covering it sum to 1, and every counted tile
+    /// gives a constraint that its placements sum to at most its count.  The linear
+    /// relaxation often shows that a board can't be tiled without trying any placements,
+    /// where a search would have to try them all.  The solver can't be interrupted, so
+    /// cancel tokens are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::solver::ExactCover;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(2));
+    ///
+    /// let tiling = ExactCover::new(&RectangularBoard::new(9, 6), &tiles).find_tiling();
+    /// assert_eq!(tiling.unwrap().placements().len(), 18);
+    ///
+    /// assert!(ExactCover::new(&RectangularBoard::new(5, 5), &tiles).find_tiling().is_none());
+    /// ```
+    #[cfg(feature = "ilp")]
+    pub fn find_tiling(&self) -> Option<Tiling> {
+        use microlp::{ComparisonOp, OptimizationDirection, Problem};
+
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let variables: Vec<_> = self
+            .rows
+            .iter()
+            .map(|_| problem.add_binary_var(0.0))
+            .collect();
+
+        let mut columns = vec![Vec::new(); self.primary + self.capacities.len()];
+        for (row, &variable) in self.rows.iter().zip(&variables) {
+            for &c in row {
+                columns[c].push((variable, 1.0));
+            }
+        }
+
+        for (c, column) in columns.iter().enumerate() {
+            if c < self.primary {
+                // a cell which no placement covers can't be tiled
+                if column.is_empty() {
+                    return None;
+                }
+
+                problem.add_constraint(column.as_slice(), ComparisonOp::Eq, 1.0);
+            } else if !column.is_empty() {
+                let capacity = self.capacities[c - self.primary];
+                problem.add_constraint(column.as_slice(), ComparisonOp::Le, capacity as f64);
+            }
+        }
+
+        let solution = problem.solve().ok()?;
+
+        Some(Tiling::from_placements(
+            self.board.clone(),
+            variables
+                .iter()
+                .zip(&self.placements)
+                .filter(|(&variable, _)| solution.var_value_rounded(variable) > 0.5)
+                .map(|(_, cells)| TilePlacement::new(cells.clone()))
+                .collect(),
+        ))
+    }
+}
+
+/// A sparse 0-1 matrix stored as Knuth's dancing links, supporting Algorithm X
+///
+/// Node 0 is the root, nodes 1..=columns are the column headers, and the remaining
+/// nodes are the 1s of the matrix.  The primary columns must be covered exactly once,
+/// and each secondary column (which follow the primary columns) may be covered at most
+/// as many times as its capacity.
+struct Dlx {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    row: Vec<usize>,
+    size: Vec<usize>,
+    capacity: Vec<usize>,
+    cancel: Option<CancelToken>,
+}
+
+impl Dlx {
+    fn new(primary: usize, capacities: &[usize], rows: &[Vec<usize>]) -> Self {
+        let columns = primary + capacities.len();
+        let mut dlx = Dlx {
+            left: Vec::new(),
+            right: Vec::new(),
+            up: Vec::new(),
+            down: Vec::new(),
+            column: Vec::new(),
+            row: Vec::new(),
+            size: vec![0; columns + 1],
+            capacity: vec![usize::MAX; primary + 1],
+            cancel: None,
+        };
+        dlx.capacity.extend(capacities);
+
+        // the root and primary column headers form a circular list, while each secondary
+        // column header is linked to itself so that it is never chosen by the search
+        for i in 0..=columns {
+            if i <= primary {
+                dlx.left.push(if i == 0 { primary } else { i - 1 });
+                dlx.right.push(if i == primary { 0 } else { i + 1 });
+            } else {
+                dlx.left.push(i);
+                dlx.right.push(i);
+            }
+            dlx.up.push(i);
+            dlx.down.push(i);
+            dlx.column.push(i);
+            dlx.row.push(usize::MAX);
+        }
+
+        for (row_index, row) in rows.iter().enumerate() {
+            let first = dlx.left.len();
+
+            for (k, c) in row.iter().enumerate() {
+                let header = c + 1;
+                let node = dlx.left.len();
+
+                // link into the row
+                dlx.left.push(if k == 0 { node } else { node - 1 });
+                dlx.right.push(first);
+                if k > 0 {
+                    dlx.right[node - 1] = node;
+                    dlx.left[first] = node;
+                }
+
+                // link into the bottom of the column
+                dlx.up.push(dlx.up[header]);
+                dlx.down.push(header);
+                let last = dlx.up[header];
+                dlx.down[last] = node;
+                dlx.up[header] = node;
+
+                dlx.column.push(header);
+                dlx.row.push(row_index);
+                dlx.size[header] += 1;
+            }
+        }
+
+        // rows using a secondary column with no capacity left can never be chosen
+        for c in (primary + 1)..=columns {
+            if dlx.capacity[c] == 0 {
+                dlx.cover(c);
+            }
+        }
+
+        dlx
+    }
+
+    fn cover(&mut self, c: usize) {
+        self.right[self.left[c]] = self.right[c];
+        self.left[self.right[c]] = self.left[c];
+
+        let mut i = self.down[c];
+        while i != c {
+            let mut j = self.right[i];
+            while j != i {
+                self.down[self.up[j]] = self.down[j];
+                self.up[self.down[j]] = self.up[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, c: usize) {
+        let mut i = self.up[c];
+        while i != c {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.down[self.up[j]] = j;
+                self.up[self.down[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+
+        self.right[self.left[c]] = c;
+        self.left[self.right[c]] = c;
+    }
+
+    /// Records that a row covering column `c` has been chosen.  Primary columns are
+    /// covered straight away, while secondary columns are only covered once they reach
+    /// their capacity.
+    fn select(&mut self, c: usize) {
+        if self.capacity[c] == usize::MAX {
+            self.cover(c);
+            return;
+        }
+
+        self.capacity[c] -= 1;
+        if self.capacity[c] == 0 {
+            self.cover(c);
+        }
+    }
+
+    /// Reverts `select`
+    fn unselect(&mut self, c: usize) {
+        if self.capacity[c] == usize::MAX {
+            self.uncover(c);
+            return;
+        }
+
+        if self.capacity[c] == 0 {
+            self.uncover(c);
+        }
+        self.capacity[c] += 1;
+    }
+
+    /// Calls `f` with the rows of each exact cover, stopping early if `f` returns false
+    fn search<F: FnMut(&[usize]) -> bool>(&mut self, mut f: F) {
+        let mut solution = Vec::new();
+        self.search_from(&mut solution, &mut f);
+    }
+
+    /// Returns false if the search should stop
+    fn search_from<F: FnMut(&[usize]) -> bool>(
+        &mut self,
+        solution: &mut Vec<usize>,
+        f: &mut F,
+    ) -> bool {
+        if self.right[0] == 0 {
+            return f(solution);
+        }
+        if self
+            .cancel
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+        {
+            return false;
+        }
+
+        // choose the column with the fewest remaining rows
+        let mut c = self.right[0];
+        let mut j = self.right[c];
+        while j != 0 {
+            if self.size[j] < self.size[c] {
+                c = j;
+            }
+            j = self.right[j];
+        }
+
+        if self.size[c] == 0 {
+            return true;
+        }
+
+        self.cover(c);
+
+        let mut keep_going = true;
+        let mut r = self.down[c];
+        while r != c && keep_going {
+            solution.push(self.row[r]);
+
+            let mut j = self.right[r];
+            while j != r {
+                self.select(self.column[j]);
+                j = self.right[j];
+            }
+
+            keep_going = self.search_from(solution, f);
+
+            let mut j = self.left[r];
+            while j != r {
+                self.unselect(self.column[j]);
+                j = self.left[j];
+            }
+
+            solution.pop();
+            r = self.down[r];
+        }
+
+        self.uncover(c);
+
+        keep_going
+    }
+}
@@ -1,4 +1,13 @@
-use std::collections::HashSet;
+use crate::board::Lattice;
+use crate::error::TilerError;
+use crate::weight::Weight;
+use num::One;
+use std::collections::{BTreeSet, HashSet};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::iter::FromIterator;
+use std::str::FromStr;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Direction {
@@ -52,6 +61,29 @@ impl Direction {
         }
     }
 
+    /// Returns the (row, column) offset obtained by moving one step in this direction
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::Direction;
+    ///
+    /// assert_eq!(Direction::Up.offset(), (-1, 0));
+    /// assert_eq!(Direction::DownRight.offset(), (1, 1));
+    /// ```
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (-1, 1),
+            Direction::DownRight => (1, 1),
+            Direction::DownLeft => (1, -1),
+        }
+    }
+
     pub fn reflect(self, axis: Axis) -> Self {
         match axis {
             Axis::Horizontal => match self {
@@ -76,16 +108,182 @@ impl Direction {
     }
 }
 
+impl FromStr for Direction {
+    type Err = ParseTileError;
+
+    /// Parses a direction from its abbreviation (`U`, `D`, `L`, `R`, `UL`, `UR`, `DL`, `DR`)
+    /// or its full name (e.g. `UpLeft`), ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "u" | "up" => Ok(Direction::Up),
+            "d" | "down" => Ok(Direction::Down),
+            "l" | "left" => Ok(Direction::Left),
+            "r" | "right" => Ok(Direction::Right),
+            "ul" | "upleft" => Ok(Direction::UpLeft),
+            "ur" | "upright" => Ok(Direction::UpRight),
+            "dl" | "downleft" => Ok(Direction::DownLeft),
+            "dr" | "downright" => Ok(Direction::DownRight),
+            _ => Err(ParseTileError {
+                direction: s.trim().to_string(),
+            }),
+        }
+    }
+}
+
+/// A step between two triangles of the triangular lattice (see `Lattice::Triangular`)
+/// which share an edge
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TriangularDirection {
+    /// To the neighbouring triangle in the same row, on the left
+    Left,
+    /// To the neighbouring triangle in the same row, on the right
+    Right,
+    /// Across the horizontal edge: down from a triangle pointing up, and up from a
+    /// triangle pointing down
+    Across,
+}
+
+impl TriangularDirection {
+    /// Returns the (row, column) of the triangle reached by stepping in this direction from
+    /// the (row, column) triangle
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::TriangularDirection;
+    ///
+    /// // the (0, 0) triangle points up and the (0, 1) triangle points down
+    /// assert_eq!(TriangularDirection::Across.step((0, 0)), (1, 0));
+    /// assert_eq!(TriangularDirection::Across.step((0, 1)), (-1, 1));
+    /// assert_eq!(TriangularDirection::Left.step((0, 1)), (0, 0));
+    /// ```
+    pub fn step(self, (row, col): (i32, i32)) -> (i32, i32) {
+        match self {
+            TriangularDirection::Left => (row, col - 1),
+            TriangularDirection::Right => (row, col + 1),
+            TriangularDirection::Across if Lattice::points_up(row as isize, col as isize) => {
+                (row + 1, col)
+            }
+            TriangularDirection::Across => (row - 1, col),
+        }
+    }
+}
+
+impl FromStr for TriangularDirection {
+    type Err = ParseTileError;
+
+    /// Parses a direction from its abbreviation (`L`, `R`, `A`) or its full name, ignoring
+    /// case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "l" | "left" => Ok(TriangularDirection::Left),
+            "r" | "right" => Ok(TriangularDirection::Right),
+            "a" | "across" => Ok(TriangularDirection::Across),
+            _ => Err(ParseTileError {
+                direction: s.trim().to_string(),
+            }),
+        }
+    }
+}
+
+/// The error returned when parsing a tile from a string of directions fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTileError {
+    direction: String,
+}
+
+impl fmt::Display for ParseTileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid direction '{}'", self.direction)
+    }
+}
+
+impl Error for ParseTileError {}
+
+/// The seven one-sided tetrominoes
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TetrominoKind {
+    I,
+    O,
+    T,
+    S,
+    Z,
+    L,
+    J,
+}
+
+/// The twelve free pentominoes, using Conway's lettering
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PentominoKind {
+    F,
+    I,
+    L,
+    N,
+    P,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+}
+
+/// The three free tetriamonds: the I (a parallelogram), the C (a trapezoid with a triangle
+/// on one end) and the T (a triangle with sides of length two)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TetriamondKind {
+    I,
+    C,
+    T,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Tile {
     pub directions: Vec<Direction>,
 }
 
 impl Tile {
+    /// Creates a tile covering the blocks visited by a walk in the given directions.  The
+    /// walk may return to blocks it has already covered, which are only covered once.
     pub fn new(directions: Vec<Direction>) -> Self {
         Tile { directions }
     }
 
+    /// Like `new`, but returns an error if the walk returns to a block it has already
+    /// covered, so that the tile covers one more block than the walk has steps
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::error::TilerError;
+    /// use dcc_tiler::tile::{Direction, Tile};
+    ///
+    /// let tile = Tile::try_new(vec![Direction::Right, Direction::Up]).unwrap();
+    /// assert_eq!(tile.size(), 3);
+    ///
+    /// assert_eq!(
+    ///     Tile::try_new(vec![Direction::Right, Direction::Up, Direction::Down]),
+    ///     Err(TilerError::OverlappingWalk { step: 3 })
+    /// );
+    /// ```
+    pub fn try_new(directions: Vec<Direction>) -> Result<Self, TilerError> {
+        let mut current = (0, 0);
+        let mut visited = HashSet::new();
+        visited.insert(current);
+
+        for (step, direction) in directions.iter().enumerate() {
+            let (dr, dc) = direction.offset();
+            current = (current.0 + dr, current.1 + dc);
+
+            if !visited.insert(current) {
+                return Err(TilerError::OverlappingWalk { step: step + 1 });
+            }
+        }
+
+        Ok(Tile::new(directions))
+    }
+
     /// Returns an L-shaped tile consisting of n + 1 blocks
     ///
     /// # Panics
@@ -101,7 +299,24 @@ impl Tile {
     /// assert_eq!(tile.directions, vec![Direction::Left, Direction::Up]);
     /// ```
     pub fn l_tile(length: usize) -> Self {
-        assert!(length > 0);
+        Tile::try_l_tile(length).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `l_tile`, but returns an error if length = 0
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::error::TilerError;
+    /// use dcc_tiler::tile::Tile;
+    ///
+    /// assert_eq!(Tile::try_l_tile(2), Ok(Tile::l_tile(2)));
+    /// assert!(matches!(Tile::try_l_tile(0), Err(TilerError::ZeroSize(_))));
+    /// ```
+    pub fn try_l_tile(length: usize) -> Result<Self, TilerError> {
+        if length == 0 {
+            return Err(TilerError::ZeroSize("tile size"));
+        }
 
         let mut directions = vec![Direction::Left];
 
@@ -109,15 +324,70 @@ impl Tile {
             directions.push(Direction::Up);
         }
 
-        Tile::new(directions)
+        Ok(Tile::new(directions))
+    }
+
+    /// Parses a tile from a comma separated list of directions, see `Direction::from_str`
+    /// for the accepted direction names.  The empty string is parsed as a box tile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{Tile, Direction};
+    ///
+    /// let tile = Tile::parse("L,U,U,R").unwrap();
+    /// assert_eq!(
+    ///     tile.directions,
+    ///     vec![Direction::Left, Direction::Up, Direction::Up, Direction::Right]
+    /// );
+    ///
+    /// assert!(Tile::parse("L,X").is_err());
+    /// ```
+    pub fn parse(s: &str) -> Result<Self, ParseTileError> {
+        if s.trim().is_empty() {
+            return Ok(Tile::box_tile());
+        }
+
+        let directions = s
+            .split(',')
+            .map(Direction::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Tile::new(directions))
     }
 
     pub fn box_tile() -> Self {
         Tile::new(Vec::new())
     }
 
+    /// Returns a domino, covering two horizontally adjacent cells
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{CellTile, Tile};
+    ///
+    /// let domino = CellTile::from(Tile::domino());
+    /// assert_eq!(domino.cells(), &[(0, 0), (0, 1)]);
+    /// ```
+    pub fn domino() -> Self {
+        Tile::new(vec![Direction::Right])
+    }
+
+    /// Returns a T-shaped tile
+    ///
+    /// # Panics
+    ///
+    /// Will panic if length = 0
     pub fn t_tile(length: usize) -> Self {
-        assert!(length > 0);
+        Tile::try_t_tile(length).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `t_tile`, but returns an error if length = 0
+    pub fn try_t_tile(length: usize) -> Result<Self, TilerError> {
+        if length == 0 {
+            return Err(TilerError::ZeroSize("tile size"));
+        }
 
         let mut directions = Vec::new();
 
@@ -131,7 +401,60 @@ impl Tile {
             directions.push(Direction::Right);
         }
 
-        Tile::new(directions)
+        Ok(Tile::new(directions))
+    }
+
+    /// Returns the specified tetromino
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{CellTile, TetrominoKind, Tile};
+    ///
+    /// let tile = Tile::tetromino(TetrominoKind::T);
+    /// assert_eq!(CellTile::from(&tile), CellTile::new(vec![(0, 0), (0, 1), (0, 2), (1, 1)]));
+    /// ```
+    pub fn tetromino(kind: TetrominoKind) -> Self {
+        let cells = match kind {
+            TetrominoKind::I => vec![(0, 0), (0, 1), (0, 2), (0, 3)],
+            TetrominoKind::O => vec![(0, 0), (0, 1), (1, 0), (1, 1)],
+            TetrominoKind::T => vec![(0, 0), (0, 1), (0, 2), (1, 1)],
+            TetrominoKind::S => vec![(0, 1), (0, 2), (1, 0), (1, 1)],
+            TetrominoKind::Z => vec![(0, 0), (0, 1), (1, 1), (1, 2)],
+            TetrominoKind::L => vec![(0, 0), (1, 0), (2, 0), (2, 1)],
+            TetrominoKind::J => vec![(0, 1), (1, 1), (2, 1), (2, 0)],
+        };
+
+        Tile::try_from(&CellTile::new(cells)).unwrap()
+    }
+
+    /// Returns the specified pentomino
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{CellTile, PentominoKind, Tile};
+    ///
+    /// let tile = Tile::pentomino(PentominoKind::X);
+    /// assert_eq!(CellTile::from(&tile).len(), 5);
+    /// ```
+    pub fn pentomino(kind: PentominoKind) -> Self {
+        let cells = match kind {
+            PentominoKind::F => vec![(0, 1), (0, 2), (1, 0), (1, 1), (2, 1)],
+            PentominoKind::I => vec![(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)],
+            PentominoKind::L => vec![(0, 0), (1, 0), (2, 0), (3, 0), (3, 1)],
+            PentominoKind::N => vec![(0, 1), (1, 1), (2, 1), (2, 0), (3, 0)],
+            PentominoKind::P => vec![(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)],
+            PentominoKind::T => vec![(0, 0), (0, 1), (0, 2), (1, 1), (2, 1)],
+            PentominoKind::U => vec![(0, 0), (0, 2), (1, 0), (1, 1), (1, 2)],
+            PentominoKind::V => vec![(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)],
+            PentominoKind::W => vec![(0, 0), (1, 0), (1, 1), (2, 1), (2, 2)],
+            PentominoKind::X => vec![(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)],
+            PentominoKind::Y => vec![(0, 1), (1, 0), (1, 1), (2, 1), (3, 1)],
+            PentominoKind::Z => vec![(0, 0), (0, 1), (1, 1), (2, 1), (2, 2)],
+        };
+
+        Tile::try_from(&CellTile::new(cells)).unwrap()
     }
 
     /// Returns a rotated (by 90 degrees clockwise) copy of this tile.
@@ -165,64 +488,1124 @@ impl Tile {
     pub fn reflect(&self, axis: Axis) -> Tile {
         Tile::new(self.directions.iter().map(|d| d.reflect(axis)).collect())
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct TileCollection {
-    tiles: Vec<Tile>,
-    contains_single_tile: bool,
-}
+    /// Returns the walk visiting the same blocks as this tile which is chosen for every tile
+    /// covering those blocks, so that two tiles covering the same blocks (after moving one
+    /// of them) are equal exactly when they are normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{Direction, Tile};
+    ///
+    /// // the same L-tromino, walked from different ends
+    /// let a = Tile::new(vec![Direction::Down, Direction::Right]);
+    /// let b = Tile::new(vec![Direction::Left, Direction::Up]);
+    /// assert_ne!(a, b);
+    /// assert_eq!(a.normalize(), b.normalize());
+    /// ```
+    pub fn normalize(&self) -> Tile {
+        let cells: Vec<(isize, isize)> = CellTile::from(self)
+            .cells
+            .iter()
+            .map(|(row, col)| (*row as isize, *col as isize))
+            .collect();
 
-impl TileCollection {
-    pub fn new(tiles: Vec<Tile>) -> Self {
-        TileCollection {
-            contains_single_tile: tiles.iter().any(|b| b.directions.is_empty()),
-            tiles,
-        }
+        Tile::from_cells(&cells)
     }
 
-    pub fn contains_single_tile(&self) -> bool {
-        self.contains_single_tile
+    /// Returns true if some rotation or reflection of this tile covers the same blocks as
+    /// `other` (after moving one of them), so that the two are the same free polyomino
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{Axis, Tile};
+    ///
+    /// let tile = Tile::l_tile(3);
+    /// assert!(tile.equivalent_up_to_symmetry(&tile.rotate().reflect(Axis::Vertical)));
+    /// assert!(!tile.equivalent_up_to_symmetry(&Tile::t_tile(3)));
+    /// ```
+    pub fn equivalent_up_to_symmetry(&self, other: &Tile) -> bool {
+        self.equivalent_under(other, TileSymmetry::All)
     }
 
-    pub fn iter<'b>(&'b self) -> Box<dyn Iterator<Item = &Tile> + 'b> {
-        Box::new(self.tiles.iter())
+    /// Like `equivalent_up_to_symmetry`, but only allowing the given rotations and reflections
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{Axis, Tile, TileSymmetry};
+    ///
+    /// // an L-tetromino and its mirror image are different one-sided polyominoes
+    /// let tile = Tile::l_tile(3);
+    /// let mirrored = tile.reflect(Axis::Vertical);
+    /// assert!(!tile.equivalent_under(&mirrored, TileSymmetry::RotationsOnly));
+    /// assert!(tile.equivalent_under(&mirrored.reflect(Axis::Horizontal), TileSymmetry::RotationsOnly));
+    /// assert!(!tile.equivalent_under(&tile.rotate(), TileSymmetry::None));
+    /// ```
+    pub fn equivalent_under(&self, other: &Tile, symmetry: TileSymmetry) -> bool {
+        CellTile::from(self).canonical_form(symmetry)
+            == CellTile::from(other).canonical_form(symmetry)
     }
-}
 
-impl From<Tile> for TileCollection {
-    fn from(tile: Tile) -> Self {
-        /// Generates the orbit of this tile under the symmetry + rotate actions
-        fn symmetry_orbit(tile: Tile) -> TileCollection {
-            let mut orbit = HashSet::new();
-
-            // our starting set of directions
-            orbit.insert(tile);
-
-            loop {
-                // in each iteration, we check whether our directions set
-                // increased.  If it didn't, then we've got the entire orbit
-                let current_size = orbit.len();
-
-                let mut to_insert = Vec::new();
-
-                for directions in &orbit {
-                    // apply the rotate function
-                    to_insert.push(directions.rotate());
-                    // apply the two axis reflections
-                    to_insert.push(directions.reflect(Axis::Horizontal));
-                    to_insert.push(directions.reflect(Axis::Vertical));
+    /// Returns a copy of this tile where each block has been replaced by a
+    /// `scale * scale` square of blocks.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if scale = 0
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::Tile;
+    ///
+    /// // a box tile at scale 2 is a 2x2 square
+    /// let tile = Tile::box_tile().scaled(2);
+    /// assert_eq!(tile.directions.len(), 3);
+    /// ```
+    pub fn scaled(&self, scale: usize) -> Tile {
+        self.try_scaled(scale).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `scaled`, but returns an error if scale = 0
+    pub fn try_scaled(&self, scale: usize) -> Result<Tile, TilerError> {
+        if scale == 0 {
+            return Err(TilerError::ZeroSize("tile scale"));
+        }
+        if scale == 1 {
+            return Ok(self.clone());
+        }
+
+        let scale = scale as isize;
+        let mut cells = Vec::new();
+
+        for (row, col) in self.cells() {
+            for i in 0..scale {
+                for j in 0..scale {
+                    let cell = (row * scale + i, col * scale + j);
+
+                    if !cells.contains(&cell) {
+                        cells.push(cell);
+                    }
                 }
+            }
+        }
+
+        Ok(Tile::from_cells(&cells))
+    }
+
+    /// Returns the (row, column) offsets of the blocks visited by this tile's walk,
+    /// relative to the first block, in the order they are first visited.  A block visited
+    /// more than once is only returned once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::Tile;
+    ///
+    /// // the stem of the T sticks up from the middle of its bar
+    /// assert_eq!(Tile::t_tile(1).cells(), vec![(0, 0), (0, 1), (-1, 1), (0, 2)]);
+    /// ```
+    pub fn cells(&self) -> Vec<(isize, isize)> {
+        let mut current = (0, 0);
+        let mut cells = vec![current];
+
+        for direction in &self.directions {
+            let (dr, dc) = direction.offset();
+            current = (current.0 + dr, current.1 + dc);
+
+            if !cells.contains(&current) {
+                cells.push(current);
+            }
+        }
+
+        cells
+    }
+
+    /// Returns the number of blocks covered by this tile
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::Tile;
+    ///
+    /// assert_eq!(Tile::t_tile(4).size(), 10);
+    /// assert_eq!(Tile::box_tile().size(), 1);
+    /// ```
+    pub fn size(&self) -> usize {
+        self.cells().len()
+    }
 
-                orbit.extend(to_insert);
+    /// Builds a tile covering exactly the specified (connected) cells.
+    ///
+    /// The resulting walk is a depth first traversal of the cells, so it may
+    /// revisit blocks when backtracking.  Cells which are only diagonally adjacent
+    /// to the rest of the tile are reached using diagonal steps.
+    fn from_cells(cells: &[(isize, isize)]) -> Tile {
+        fn visit(
+            cell: (isize, isize),
+            cells: &[(isize, isize)],
+            visited: &mut HashSet<(isize, isize)>,
+            directions: &mut Vec<Direction>,
+            walk_length: &mut usize,
+        ) {
+            visited.insert(cell);
+
+            for direction in &[
+                Direction::Up,
+                Direction::Right,
+                Direction::Down,
+                Direction::Left,
+                Direction::UpLeft,
+                Direction::UpRight,
+                Direction::DownRight,
+                Direction::DownLeft,
+            ] {
+                let (dr, dc) = direction.offset();
+                let next = (cell.0 + dr, cell.1 + dc);
 
-                if orbit.len() == current_size {
-                    break;
+                if cells.contains(&next) && !visited.contains(&next) {
+                    directions.push(*direction);
+                    *walk_length = directions.len();
+
+                    visit(next, cells, visited, directions, walk_length);
+                    directions.push(direction.opposite());
                 }
             }
+        }
+
+        let mut directions = Vec::new();
+        let mut walk_length = 0;
+
+        if let Some(start) = cells.first() {
+            visit(
+                *start,
+                cells,
+                &mut HashSet::new(),
+                &mut directions,
+                &mut walk_length,
+            );
+        }
+
+        // backtracking at the very end of the walk doesn't cover any new blocks
+        directions.truncate(walk_length);
+
+        Tile::new(directions)
+    }
+}
+
+/// A tile represented by the set of cells it covers, as (row, column) offsets.
+///
+/// Unlike `Tile`, this can represent any set of cells - including tiles which
+/// aren't connected.  The cells are normalized so that the smallest row and column
+/// offsets are both zero, so two `CellTile`s are equal exactly when one is a
+/// translation of the other.
+///
+/// Tiles on the triangular lattice (see `Lattice::Triangular`) may only be translated so that
+/// triangles pointing up stay pointing up, so their smallest column offset may be one.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CellTile {
+    cells: Vec<(i32, i32)>,
+    lattice: Lattice,
+}
+
+impl CellTile {
+    /// Creates a tile covering the specified cells
+    ///
+    /// # Panics
+    ///
+    /// Will panic if there are no cells
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::CellTile;
+    ///
+    /// // the plus shaped (X) pentomino
+    /// let tile = CellTile::new(vec![(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)]);
+    /// assert_eq!(tile.len(), 5);
+    /// assert_eq!(tile, CellTile::new(vec![(5, 6), (6, 5), (6, 6), (6, 7), (7, 6)]));
+    /// ```
+    pub fn new<I: IntoIterator<Item = (i32, i32)>>(cells: I) -> Self {
+        CellTile::try_new(cells).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `new`, but returns an error if there are no cells
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::error::TilerError;
+    /// use dcc_tiler::tile::CellTile;
+    ///
+    /// assert_eq!(CellTile::try_new(vec![]), Err(TilerError::EmptyTile));
+    /// ```
+    pub fn try_new<I: IntoIterator<Item = (i32, i32)>>(cells: I) -> Result<Self, TilerError> {
+        CellTile::try_with_lattice(cells, Lattice::Square)
+    }
+
+    /// Creates a tile covering the specified cells of the given lattice
+    ///
+    /// # Panics
+    ///
+    /// Will panic if there are no cells
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::Lattice;
+    /// use dcc_tiler::tile::CellTile;
+    ///
+    /// // the (0, 0) triangle points up, but the (0, 1) triangle points down
+    /// let up = CellTile::with_lattice(vec![(0, 0)], Lattice::Triangular);
+    /// let down = CellTile::with_lattice(vec![(0, 1)], Lattice::Triangular);
+    /// assert_ne!(up, down);
+    /// assert_eq!(up, CellTile::with_lattice(vec![(3, 5)], Lattice::Triangular));
+    /// ```
+    pub fn with_lattice<I: IntoIterator<Item = (i32, i32)>>(cells: I, lattice: Lattice) -> Self {
+        CellTile::try_with_lattice(cells, lattice).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `with_lattice`, but returns an error if there are no cells
+    pub fn try_with_lattice<I: IntoIterator<Item = (i32, i32)>>(
+        cells: I,
+        lattice: Lattice,
+    ) -> Result<Self, TilerError> {
+        let mut cells: Vec<(i32, i32)> = cells.into_iter().collect();
+        if cells.is_empty() {
+            return Err(TilerError::EmptyTile);
+        }
 
-            TileCollection::new(orbit.into_iter().collect())
+        let min_row = cells.iter().map(|(row, _)| *row).min().unwrap();
+        let mut min_col = cells.iter().map(|(_, col)| *col).min().unwrap();
+
+        // keep the triangles pointing the same way
+        if lattice == Lattice::Triangular && (min_row + min_col) % 2 != 0 {
+            min_col -= 1;
+        }
+
+        for cell in cells.iter_mut() {
+            *cell = (cell.0 - min_row, cell.1 - min_col);
         }
-        symmetry_orbit(tile)
+        cells.sort_unstable();
+        cells.dedup();
+
+        Ok(CellTile { cells, lattice })
+    }
+
+    /// Returns the lozenge, the tile made of two triangles of the triangular lattice which
+    /// share an edge.  Its three orientations are the shapes in
+    /// `TileCollection::from(CellTile::lozenge())`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{CellTile, TileCollection};
+    ///
+    /// assert_eq!(TileCollection::from(CellTile::lozenge()).cell_tiles().count(), 3);
+    /// ```
+    pub fn lozenge() -> Self {
+        CellTile::with_lattice(vec![(0, 0), (0, 1)], Lattice::Triangular)
+    }
+
+    /// Creates a tile on the triangular lattice covering the triangles visited by a walk
+    /// starting from a triangle pointing up.  A walk may revisit triangles, so branching
+    /// shapes can be described by stepping back over the branch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{CellTile, TriangularDirection};
+    ///
+    /// let tile = CellTile::from_triangular_walk(&[TriangularDirection::Right]);
+    /// assert_eq!(tile, CellTile::lozenge());
+    /// ```
+    pub fn from_triangular_walk(directions: &[TriangularDirection]) -> Self {
+        let mut cell = (0, 0);
+        let mut cells = vec![cell];
+
+        for direction in directions {
+            cell = direction.step(cell);
+            cells.push(cell);
+        }
+
+        CellTile::with_lattice(cells, Lattice::Triangular)
+    }
+
+    /// Parses a walk of comma separated triangular directions (see
+    /// `TriangularDirection::from_str`) into the tile covering it, as in
+    /// `from_triangular_walk`.  The empty string is parsed as a single triangle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::CellTile;
+    ///
+    /// assert_eq!(CellTile::parse_triangular("R,R").unwrap(), CellTile::triamond());
+    /// assert!(CellTile::parse_triangular("R,U").is_err());
+    /// ```
+    pub fn parse_triangular(s: &str) -> Result<Self, ParseTileError> {
+        if s.trim().is_empty() {
+            return Ok(CellTile::from_triangular_walk(&[]));
+        }
+
+        let directions = s
+            .split(',')
+            .map(TriangularDirection::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(CellTile::from_triangular_walk(&directions))
+    }
+
+    /// Returns the triamond, a row of three triangles.  It is the only polyiamond made of
+    /// three triangles, and has six orientations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{CellTile, TileCollection};
+    ///
+    /// let triamond = CellTile::triamond();
+    /// assert_eq!(triamond.len(), 3);
+    /// assert_eq!(TileCollection::from(triamond).cell_tiles().count(), 6);
+    /// ```
+    pub fn triamond() -> Self {
+        use self::TriangularDirection::*;
+
+        CellTile::from_triangular_walk(&[Right, Right])
+    }
+
+    /// Returns the specified tetriamond
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{CellTile, TetriamondKind, TileCollection};
+    ///
+    /// // the fourteen fixed tetriamonds
+    /// let orientations: usize = [TetriamondKind::I, TetriamondKind::C, TetriamondKind::T]
+    ///     .iter()
+    ///     .map(|&kind| TileCollection::from(CellTile::tetriamond(kind)).cell_tiles().count())
+    ///     .sum();
+    /// assert_eq!(orientations, 14);
+    /// ```
+    pub fn tetriamond(kind: TetriamondKind) -> Self {
+        use self::TriangularDirection::*;
+
+        let walk = match kind {
+            TetriamondKind::I => vec![Right, Right, Right],
+            TetriamondKind::C => vec![Right, Right, Across],
+            // step back over the middle triangle to reach the third corner
+            TetriamondKind::T => vec![Across, Left, Right, Right],
+        };
+
+        CellTile::from_triangular_walk(&walk)
+    }
+
+    /// Returns the (row, column) offsets of the cells covered by this tile
+    pub fn cells(&self) -> &[(i32, i32)] {
+        &self.cells
+    }
+
+    /// Returns the number of cells covered by this tile
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Always false, since every tile covers at least one cell
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Returns the lattice that the cells of this tile lie on
+    pub fn lattice(&self) -> Lattice {
+        self.lattice
+    }
+
+    /// Returns a copy of this tile rotated clockwise, by 90 degrees on the square lattice
+    /// and by 60 degrees on the triangular lattice
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::CellTile;
+    ///
+    /// let lozenge = CellTile::lozenge();
+    /// let mut rotated = lozenge.rotate();
+    /// assert_ne!(rotated, lozenge);
+    ///
+    /// for _ in 1..6 {
+    ///     rotated = rotated.rotate();
+    /// }
+    /// assert_eq!(rotated, lozenge);
+    /// ```
+    pub fn rotate(&self) -> CellTile {
+        match self.lattice {
+            Lattice::Square => CellTile::new(self.cells.iter().map(|(row, col)| (*col, -*row))),
+            Lattice::Triangular => CellTile::with_lattice(
+                self.cells.iter().map(|&(row, col)| {
+                    // rotate the centre of each triangle about a corner of the (0, 0)
+                    // triangle, measuring across in sixths of a side and down in thirds
+                    // of a row
+                    let x = 3 * col;
+                    let y = 3 * row
+                        + if Lattice::points_up(row as isize, col as isize) {
+                            2
+                        } else {
+                            1
+                        };
+                    let (x, y) = ((x - 3 * y) / 2, (x + y) / 2);
+
+                    (y.div_euclid(3), x / 3)
+                }),
+                self.lattice,
+            ),
+        }
+    }
+
+    /// Returns a reflected (about the specified axis) copy of this tile
+    pub fn reflect(&self, axis: Axis) -> CellTile {
+        CellTile::with_lattice(
+            self.cells
+                .iter()
+                .map(|(row, col)| match (axis, self.lattice) {
+                    (Axis::Horizontal, Lattice::Square) => (-*row, *col),
+                    // reflecting a row of triangles upside down swaps the way they point
+                    (Axis::Horizontal, Lattice::Triangular) => (-1 - *row, *col),
+                    (Axis::Vertical, _) => (*row, -*col),
+                }),
+            self.lattice,
+        )
+    }
+
+    /// Returns the image of this tile under the given rotations and reflections with the
+    /// smallest cells, so that two tiles have the same canonical form exactly when one is an
+    /// image of the other
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{CellTile, TileSymmetry};
+    ///
+    /// let lozenge = CellTile::lozenge();
+    /// let turned = lozenge.rotate();
+    /// assert_ne!(turned, lozenge);
+    /// assert_eq!(
+    ///     turned.canonical_form(TileSymmetry::All),
+    ///     lozenge.canonical_form(TileSymmetry::All)
+    /// );
+    /// ```
+    pub fn canonical_form(&self, symmetry: TileSymmetry) -> CellTile {
+        self.orbit(symmetry)
+            .into_iter()
+            .min_by(|a, b| a.cells.cmp(&b.cells))
+            .unwrap()
+    }
+
+    /// Returns the images of this tile under the given rotations and reflections, starting
+    /// with the tile itself
+    fn orbit(&self, symmetry: TileSymmetry) -> Vec<CellTile> {
+        let mut orbit = vec![self.clone()];
+        let mut index = 0;
+
+        while index < orbit.len() {
+            let mut images = Vec::new();
+
+            if symmetry.allows_rotations() {
+                images.push(orbit[index].rotate());
+            }
+            if symmetry.allows_reflections() {
+                images.push(orbit[index].reflect(Axis::Horizontal));
+                images.push(orbit[index].reflect(Axis::Vertical));
+            }
+
+            for image in images {
+                if !orbit.contains(&image) {
+                    orbit.push(image);
+                }
+            }
+            index += 1;
+        }
+
+        orbit
+    }
+}
+
+impl From<&Tile> for CellTile {
+    fn from(tile: &Tile) -> Self {
+        CellTile::new(
+            tile.cells()
+                .into_iter()
+                .map(|(row, col)| (row as i32, col as i32)),
+        )
+    }
+}
+
+impl From<Tile> for CellTile {
+    fn from(tile: Tile) -> Self {
+        CellTile::from(&tile)
+    }
+}
+
+/// The error returned when converting a `CellTile` whose cells aren't connected
+/// (even diagonally) into a `Tile`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisconnectedTileError;
+
+impl fmt::Display for DisconnectedTileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the cells of the tile are not connected")
+    }
+}
+
+impl Error for DisconnectedTileError {}
+
+impl TryFrom<&CellTile> for Tile {
+    type Error = DisconnectedTileError;
+
+    /// Converts a `CellTile` into a walk visiting each of its cells.  This is possible
+    /// exactly when the cells are connected, allowing diagonal steps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{CellTile, Tile};
+    /// use std::convert::TryFrom;
+    ///
+    /// let plus = CellTile::new(vec![(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)]);
+    /// let tile = Tile::try_from(&plus).unwrap();
+    /// assert_eq!(CellTile::from(&tile), plus);
+    ///
+    /// let disconnected = CellTile::new(vec![(0, 0), (0, 2)]);
+    /// assert!(Tile::try_from(&disconnected).is_err());
+    /// ```
+    fn try_from(tile: &CellTile) -> Result<Self, Self::Error> {
+        let cells: Vec<(isize, isize)> = tile
+            .cells
+            .iter()
+            .map(|(row, col)| (*row as isize, *col as isize))
+            .collect();
+
+        let walk_tile = Tile::from_cells(&cells);
+
+        if walk_tile.size() == cells.len() {
+            Ok(walk_tile)
+        } else {
+            Err(DisconnectedTileError)
+        }
+    }
+}
+
+/// Which rotations and reflections of a tile may be placed on a board
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum TileSymmetry {
+    /// Every rotation and reflection of the tile (free polyominoes)
+    #[default]
+    All,
+
+    /// Every rotation of the tile, but no reflections (one-sided polyominoes)
+    RotationsOnly,
+
+    /// The reflections of the tile in the horizontal and vertical axes, and the half turn
+    /// obtained by combining them
+    ReflectionsOnly,
+
+    /// Only the tile exactly as given (fixed polyominoes)
+    None,
+}
+
+impl TileSymmetry {
+    fn allows_rotations(self) -> bool {
+        matches!(self, TileSymmetry::All | TileSymmetry::RotationsOnly)
+    }
+
+    fn allows_reflections(self) -> bool {
+        matches!(self, TileSymmetry::All | TileSymmetry::ReflectionsOnly)
+    }
+}
+
+/// Returns every polyomino of `n` cells, counting polyominoes which are images of one another
+/// under the given rotations and reflections only once.  Each polyomino is given in its
+/// `canonical_form`, and they are sorted by their cells.  No polyominoes have zero cells.
+///
+/// The polyominoes are found with Redelmeier's algorithm, which grows each fixed polyomino
+/// exactly once from its first cell.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::tile::{generate_polyominoes, TileSymmetry};
+///
+/// assert_eq!(generate_polyominoes(5, TileSymmetry::All).len(), 12);
+/// assert_eq!(generate_polyominoes(5, TileSymmetry::RotationsOnly).len(), 18);
+/// assert_eq!(generate_polyominoes(5, TileSymmetry::None).len(), 63);
+/// assert!(generate_polyominoes(0, TileSymmetry::All).is_empty());
+/// ```
+pub fn generate_polyominoes(n: usize, symmetry: TileSymmetry) -> Vec<CellTile> {
+    /// Adds each cell in `untried` to `polyomino` in turn, and then grows it further using
+    /// the cells next to the new cell which haven't been `seen` before
+    fn grow(
+        polyomino: &mut Vec<(i32, i32)>,
+        mut untried: Vec<(i32, i32)>,
+        seen: &mut HashSet<(i32, i32)>,
+        n: usize,
+        shapes: &mut BTreeSet<Vec<(i32, i32)>>,
+        symmetry: TileSymmetry,
+    ) {
+        while let Some(cell) = untried.pop() {
+            polyomino.push(cell);
+
+            if polyomino.len() == n {
+                let tile = CellTile::new(polyomino.iter().copied());
+                shapes.insert(tile.canonical_form(symmetry).cells);
+            } else {
+                // only cells after the first cell (in reading order) are used, so that each
+                // fixed polyomino is grown from its first cell
+                let (row, col) = cell;
+                let mut added = Vec::new();
+                for next in [
+                    (row - 1, col),
+                    (row, col + 1),
+                    (row + 1, col),
+                    (row, col - 1),
+                ] {
+                    if (next.0 > 0 || (next.0 == 0 && next.1 >= 0)) && seen.insert(next) {
+                        added.push(next);
+                    }
+                }
+
+                let mut next_untried = untried.clone();
+                next_untried.extend(added.iter().copied());
+                grow(polyomino, next_untried, seen, n, shapes, symmetry);
+
+                for cell in added {
+                    seen.remove(&cell);
+                }
+            }
+
+            polyomino.pop();
+        }
+    }
+
+    let mut shapes = BTreeSet::new();
+    if n > 0 {
+        let mut seen = HashSet::new();
+        seen.insert((0, 0));
+
+        grow(
+            &mut Vec::new(),
+            vec![(0, 0)],
+            &mut seen,
+            n,
+            &mut shapes,
+            symmetry,
+        );
+    }
+
+    shapes.into_iter().map(CellTile::new).collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct TileCollection {
+    tiles: Vec<Tile>,
+    cell_tiles: Vec<CellTile>,
+    contains_single_tile: bool,
+
+    // for each shape in `cell_tiles`, the index into `counts` of the counted tile it is an
+    // orientation of, or None if the shape can be used any number of times
+    count_indices: Vec<Option<usize>>,
+    counts: Vec<usize>,
+
+    // the weight of each shape in `cell_tiles`, used by `Tiler::partition_function`
+    weights: Vec<Weight>,
+}
+
+impl TileCollection {
+    pub fn new(tiles: Vec<Tile>) -> Self {
+        let mut cell_tiles = Vec::new();
+
+        // distinct walks can cover the same cells, but we only need to place each shape once
+        for tile in &tiles {
+            let cell_tile = CellTile::from(tile);
+
+            if !cell_tiles.contains(&cell_tile) {
+                cell_tiles.push(cell_tile);
+            }
+        }
+
+        TileCollection {
+            contains_single_tile: cell_tiles.iter().any(|t| t.len() == 1),
+            count_indices: vec![None; cell_tiles.len()],
+            counts: Vec::new(),
+            weights: vec![Weight::one(); cell_tiles.len()],
+            tiles,
+            cell_tiles,
+        }
+    }
+
+    /// Creates a collection from tiles specified by the cells they cover.
+    ///
+    /// Only the tiles on the square lattice with connected cells are included in `iter`,
+    /// but every tile is used when placing tiles on a board.
+    pub fn from_cell_tiles(cell_tiles: Vec<CellTile>) -> Self {
+        let mut distinct_tiles: Vec<CellTile> = Vec::new();
+
+        for cell_tile in cell_tiles {
+            if !distinct_tiles.contains(&cell_tile) {
+                distinct_tiles.push(cell_tile);
+            }
+        }
+
+        TileCollection {
+            contains_single_tile: distinct_tiles.iter().any(|t| t.len() == 1),
+            tiles: distinct_tiles
+                .iter()
+                .filter(|t| t.lattice == Lattice::Square)
+                .filter_map(|t| Tile::try_from(t).ok())
+                .collect(),
+            count_indices: vec![None; distinct_tiles.len()],
+            counts: Vec::new(),
+            weights: vec![Weight::one(); distinct_tiles.len()],
+            cell_tiles: distinct_tiles,
+        }
+    }
+
+    /// Creates a collection containing the symmetry orbit of each of the given tiles, where
+    /// each tile (in any of its orientations) may be placed at most the given number of times.
+    ///
+    /// Tiles which are rotations or reflections of one another share a single count, which is
+    /// the sum of their given counts.  When the total area of the tiles equals the area to be
+    /// tiled, every tiling uses each tile exactly the given number of times.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{PentominoKind, Tile, TileCollection};
+    ///
+    /// // each of the 12 pentominoes exactly once
+    /// let kinds = [
+    ///     PentominoKind::F, PentominoKind::I, PentominoKind::L, PentominoKind::N,
+    ///     PentominoKind::P, PentominoKind::T, PentominoKind::U, PentominoKind::V,
+    ///     PentominoKind::W, PentominoKind::X, PentominoKind::Y, PentominoKind::Z,
+    /// ];
+    /// let tiles = TileCollection::with_counts(
+    ///     kinds.iter().map(|kind| (Tile::pentomino(*kind), 1)).collect(),
+    /// );
+    ///
+    /// assert_eq!(tiles.counts(), &[1; 12]);
+    /// assert_eq!(tiles.cell_tiles().count(), 63);
+    /// ```
+    pub fn with_counts(tiles: Vec<(Tile, usize)>) -> Self {
+        TileCollection::with_counts_and_symmetry(tiles, TileSymmetry::All)
+    }
+
+    /// Like `with_counts`, except that only the given rotations and reflections of each
+    /// tile may be placed
+    pub fn with_counts_and_symmetry(tiles: Vec<(Tile, usize)>, symmetry: TileSymmetry) -> Self {
+        let mut collection = TileCollection::new(Vec::new());
+
+        for (tile, count) in tiles {
+            let orbit = TileCollection::from_tile_with(tile, symmetry);
+            let first = orbit.cell_tiles[0].clone();
+
+            // orbits are either identical or disjoint, so checking one shape is enough
+            if let Some(index) = collection.cell_tiles.iter().position(|t| *t == first) {
+                let count_index = collection.count_indices[index].unwrap();
+                collection.counts[count_index] += count;
+                continue;
+            }
+
+            let count_index = collection.counts.len();
+            collection.counts.push(count);
+
+            for cell_tile in orbit.cell_tiles {
+                collection.contains_single_tile |= cell_tile.len() == 1;
+                collection.cell_tiles.push(cell_tile);
+                collection.count_indices.push(Some(count_index));
+                collection.weights.push(Weight::one());
+            }
+            for orbit_tile in orbit.tiles {
+                if !collection.tiles.contains(&orbit_tile) {
+                    collection.tiles.push(orbit_tile);
+                }
+            }
+        }
+
+        collection
+    }
+
+    /// Creates a collection containing the symmetry orbit of each of the given tiles, where
+    /// each tile (in any of its orientations) has the given weight.  The weight of a tiling
+    /// is the product of the weights of its tiles, see `Tiler::partition_function`.
+    ///
+    /// Tiles which are rotations or reflections of one another can be told apart only by
+    /// their weights, so such tiles are placed once with the sum of their weights.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::weight::Weight;
+    ///
+    /// // monomers with weight x, and dimers
+    /// let tiles = TileCollection::with_weights(vec![
+    ///     (Tile::box_tile(), Weight::x()),
+    ///     (Tile::domino(), Weight::from(1u32)),
+    /// ]);
+    ///
+    /// assert_eq!(tiles.cell_tiles().count(), 3);
+    /// assert_eq!(tiles.weight(0), &Weight::x());
+    /// ```
+    pub fn with_weights(tiles: Vec<(Tile, Weight)>) -> Self {
+        let mut collection = TileCollection::new(Vec::new());
+
+        for (tile, weight) in tiles {
+            let orbit = TileCollection::from(tile);
+
+            for cell_tile in orbit.cell_tiles {
+                match collection.cell_tiles.iter().position(|t| *t == cell_tile) {
+                    Some(index) => collection.weights[index] += weight.clone(),
+                    None => {
+                        collection.contains_single_tile |= cell_tile.len() == 1;
+                        collection.cell_tiles.push(cell_tile);
+                        collection.count_indices.push(None);
+                        collection.weights.push(weight.clone());
+                    }
+                }
+            }
+            for orbit_tile in orbit.tiles {
+                if !collection.tiles.contains(&orbit_tile) {
+                    collection.tiles.push(orbit_tile);
+                }
+            }
+        }
+
+        collection
+    }
+
+    /// Returns the weight of the `i`-th shape of `cell_tiles`, which is 1 unless the
+    /// collection was made with `with_weights`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `i` is not less than the number of shapes in this collection
+    pub fn weight(&self, i: usize) -> &Weight {
+        &self.weights[i]
+    }
+
+    /// Returns the number of times each counted tile may be placed, in the order the tiles
+    /// were given to `with_counts`.  This is empty if every tile can be used any number of times.
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+
+    /// Determines whether some tile in this collection may only be used a limited number of times
+    pub fn has_counts(&self) -> bool {
+        !self.counts.is_empty()
+    }
+
+    /// Returns the index into `counts` of the counted tile which the `i`-th shape of
+    /// `cell_tiles` is an orientation of, or None if that shape can be used any number of times.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `i` is not less than the number of shapes in this collection
+    pub fn count_index(&self, i: usize) -> Option<usize> {
+        self.count_indices[i]
+    }
+
+    /// Returns the total area of the tiles which may still be placed, given the number of
+    /// times each counted tile has already been used, or None if some tile may be used any
+    /// number of times.
+    pub fn remaining_area(&self, used: &[usize]) -> Option<usize> {
+        let mut area = 0;
+        let mut seen = vec![false; self.counts.len()];
+
+        for (cell_tile, count_index) in self.cell_tiles.iter().zip(self.count_indices.iter()) {
+            let count_index = (*count_index)?;
+
+            if !seen[count_index] {
+                seen[count_index] = true;
+                let remaining = self.counts[count_index]
+                    .saturating_sub(used.get(count_index).copied().unwrap_or(0));
+                area += remaining * cell_tile.len();
+            }
+        }
+
+        Some(area)
+    }
+
+    /// Returns a collection containing the images of the given tile under the allowed
+    /// rotations and reflections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{Tile, TileCollection, TileSymmetry};
+    ///
+    /// // the L-tetromino has 8 orientations, but only 4 without reflections
+    /// let tile = Tile::l_tile(3);
+    /// assert_eq!(TileCollection::from_tile_with(tile.clone(), TileSymmetry::All).cell_tiles().count(), 8);
+    /// assert_eq!(TileCollection::from_tile_with(tile.clone(), TileSymmetry::RotationsOnly).cell_tiles().count(), 4);
+    /// assert_eq!(TileCollection::from_tile_with(tile, TileSymmetry::None).cell_tiles().count(), 1);
+    /// ```
+    pub fn from_tile_with(tile: Tile, symmetry: TileSymmetry) -> Self {
+        // the orbit is kept in the order it was discovered, so that tiles are always
+        // placed in the same order
+        let mut orbit = vec![tile];
+        let mut index = 0;
+
+        // each tile in the orbit has its images added, until no new tiles are found
+        while index < orbit.len() {
+            let mut images = Vec::new();
+
+            if symmetry.allows_rotations() {
+                images.push(orbit[index].rotate());
+            }
+            if symmetry.allows_reflections() {
+                images.push(orbit[index].reflect(Axis::Horizontal));
+                images.push(orbit[index].reflect(Axis::Vertical));
+            }
+
+            for image in images {
+                if !orbit.contains(&image) {
+                    orbit.push(image);
+                }
+            }
+            index += 1;
+        }
+
+        TileCollection::new(orbit)
+    }
+
+    /// Returns a collection containing the symmetry orbit of the given tile
+    /// scaled by each of the specified factors.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if any of the scales are 0
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// // an L-tromino together with its copy at twice the size
+    /// let tiles = TileCollection::with_scales(&Tile::l_tile(2), &[1, 2]);
+    /// assert_eq!(tiles.iter().count(), 16);
+    /// ```
+    pub fn with_scales(tile: &Tile, scales: &[usize]) -> Self {
+        let mut tiles: Vec<Tile> = Vec::new();
+
+        for scale in scales {
+            for scaled_tile in TileCollection::from(tile.scaled(*scale)).tiles {
+                if !tiles.contains(&scaled_tile) {
+                    tiles.push(scaled_tile);
+                }
+            }
+        }
+
+        TileCollection::new(tiles)
+    }
+
+    /// Returns the greatest common divisor of the areas of the tiles in this collection.
+    ///
+    /// Any region tiled by this collection must have an area divisible by this number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let tiles = TileCollection::with_scales(&Tile::l_tile(2), &[1, 2]);
+    /// assert_eq!(tiles.area_gcd(), 3);
+    /// ```
+    pub fn area_gcd(&self) -> usize {
+        self.cell_tiles
+            .iter()
+            .map(|tile| tile.len())
+            .fold(0, num::integer::gcd)
+    }
+
+    pub fn contains_single_tile(&self) -> bool {
+        self.contains_single_tile
+    }
+
+    pub fn iter<'b>(&'b self) -> Box<dyn Iterator<Item = &'b Tile> + 'b> {
+        Box::new(self.tiles.iter())
+    }
+
+    /// Iterates over the distinct shapes in this collection, as sets of cells
+    pub fn cell_tiles<'b>(&'b self) -> Box<dyn Iterator<Item = &'b CellTile> + 'b> {
+        Box::new(self.cell_tiles.iter())
+    }
+}
+
+impl FromStr for Tile {
+    type Err = ParseTileError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Tile::parse(s)
+    }
+}
+
+impl Extend<Tile> for TileCollection {
+    /// Adds the given tiles (but not their rotations or reflections) to this collection, to
+    /// be used any number of times
+    fn extend<I: IntoIterator<Item = Tile>>(&mut self, iter: I) {
+        for tile in iter {
+            let cell_tile = CellTile::from(&tile);
+
+            if !self.cell_tiles.contains(&cell_tile) {
+                self.contains_single_tile |= cell_tile.len() == 1;
+                self.cell_tiles.push(cell_tile);
+                self.count_indices.push(None);
+                self.weights.push(Weight::one());
+            }
+            if !self.tiles.contains(&tile) {
+                self.tiles.push(tile);
+            }
+        }
+    }
+}
+
+impl FromIterator<Tile> for TileCollection {
+    /// Collects tiles (but not their rotations or reflections) into a collection
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// // L-trominoes in every orientation, together with box tiles
+    /// let tiles: TileCollection = TileCollection::from(Tile::l_tile(2))
+    ///     .iter()
+    ///     .cloned()
+    ///     .chain(std::iter::once(Tile::box_tile()))
+    ///     .collect();
+    ///
+    /// assert!(tiles.contains_single_tile());
+    /// assert_eq!(tiles.cell_tiles().count(), 5);
+    /// ```
+    fn from_iter<I: IntoIterator<Item = Tile>>(iter: I) -> Self {
+        let mut collection = TileCollection::new(Vec::new());
+        collection.extend(iter);
+        collection
+    }
+}
+
+impl From<CellTile> for TileCollection {
+    /// Creates a collection consisting of every rotation and reflection of the given tile
+    fn from(tile: CellTile) -> Self {
+        TileCollection::from_cell_tiles(tile.orbit(TileSymmetry::All))
+    }
+}
+
+impl From<Tile> for TileCollection {
+    /// Creates a collection consisting of every rotation and reflection of the given tile
+    fn from(tile: Tile) -> Self {
+        TileCollection::from_tile_with(tile, TileSymmetry::All)
     }
 }
@@ -0,0 +1,3934 @@
+use crate::analysis::{self, Coloring};
+use crate::arith::{self, Arith, Count};
+use crate::board::{
+    Board, BoardKey, Boundary, CellSelection, Lattice, Position, RectangularBoard, Symmetry,
+    TilePosition,
+};
+use crate::error::TilerError;
+use crate::graph::BoardGraph;
+use crate::kasteleyn;
+use crate::solver::{Backend, CancelToken, ExactCover};
+use crate::tile::{CellTile, TileCollection};
+use crate::tiling::Tiling;
+use crate::weight::Weight;
+use num::bigint::RandBigInt;
+use num::{BigUint, One, Zero};
+
+use crate::parallel::*;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+
+#[cfg(feature = "fs")]
+use crate::render::ImageFormat;
+use crate::render::RenderOptions;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "fs")]
+use serde_derive::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::collections::hash_map::DefaultHasher;
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::hash::{Hash, Hasher};
+#[cfg(feature = "fs")]
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Result, Write};
+#[cfg(feature = "fs")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "fs")]
+use tempfile::NamedTempFile;
+
+/// The number of tilings found before they are rendered in parallel, when rendering tilings
+/// found one at a time
+#[cfg(feature = "fs")]
+const RENDER_BATCH_SIZE: usize = 1 << 8;
+
+/// The number of rendered images which can wait to be written to a ZIP file
+#[cfg(feature = "fs")]
+const ZIP_QUEUE_SIZE: usize = 1 << 6;
+
+/// The number of boards a randomized search worker explores before its first restart
+const RESTART_BUDGET: usize = 1000;
+
+/// Receives updates on the progress of a long running count or graph generation
+pub trait Progress: Send + Sync {
+    /// Called before counting or generating the graph starts, with the largest number of
+    /// layers of placements that could be needed to tile the board.
+    fn on_start(&self, _max_depth: usize) {}
+
+    /// Called after each layer of placements has been processed, with the number of
+    /// layers processed so far, the number of boards waiting to be processed, and the
+    /// number of complete tilings counted so far.
+    fn on_level(&self, depth: usize, frontier_size: usize, counted_so_far: &BigUint);
+
+    /// Called after each layer of the tiling graph has been generated, with the number of
+    /// layers generated so far, the number of boards waiting to be processed, and the number
+    /// of nodes in the graph so far.
+    fn on_graph_level(&self, _depth: usize, _frontier_size: usize, _nodes: usize) {}
+
+    /// Called once counting or generating the graph has finished
+    fn on_finish(&self) {}
+}
+
+/// The state of a breadth first count, saved so that the count can be resumed
+#[cfg(feature = "fs")]
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    /// The initial board and tiles, to check that we're resuming the same count
+    board: Vec<Vec<bool>>,
+    tiles: Vec<Vec<(i32, i32)>>,
+    counts: Vec<usize>,
+
+    /// The number of layers of placements processed so far
+    depth: usize,
+
+    /// The number of complete tilings counted so far
+    total: BigUint,
+
+    /// The boards in the next layer, with the number of ways of reaching each of them
+    frontier: Vec<(BoardKey, BigUint)>,
+}
+
+/// The counts of the regions counted by `count_tilings_by_regions`, saved so that later
+/// counts with the same tiles can reuse them
+#[cfg(feature = "fs")]
+#[derive(Serialize, Deserialize)]
+struct RegionCache {
+    /// The tiles the regions were tiled by, to check that we're reusing the right counts
+    tiles: Vec<Vec<(i32, i32)>>,
+
+    regions: Vec<(RegionShape, BigUint)>,
+}
+
+/// The outcome of a single budgeted randomized search
+enum SearchResult<B> {
+    Found(Vec<B>),
+    Exhausted,
+    OutOfBudget,
+    Cancelled,
+}
+
+/// Decides whether a tile may be placed over the given cells of the board it's placed on
+pub type PlacementFilter = Arc<dyn Fn(&RectangularBoard, &TilePosition) -> bool + Send + Sync>;
+
+/// Counts, finds and renders the tilings of a board by a collection of tiles.  The board may
+/// be any `Board` - such as a `RectangularBoard` or a `HexBoard` - whose lattice the tiles
+/// lie on, and the tiling graph is made of boards of the same kind.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::board::RectangularBoard;
+/// use dcc_tiler::tile::{Tile, TileCollection};
+/// use dcc_tiler::tiler::Tiler;
+///
+/// let board = RectangularBoard::new(8, 8);
+/// let tiles = TileCollection::from(Tile::t_tile(1));
+///
+/// let mut tiler = Tiler::new(tiles, board);
+/// assert_eq!(tiler.count_tilings(), 84u32.into());
+///
+/// // the graph of partial tilings is kept, and reused by later calls
+/// let graph = tiler.graph();
+/// let graph = graph.read().unwrap();
+/// assert_eq!(graph.count_complete_paths(&graph.count_paths()), 84u32.into());
+/// ```
+pub struct Tiler<B: Board = RectangularBoard> {
+    tiles: TileCollection,
+    initial_board: B,
+    graph: Option<Arc<RwLock<BoardGraph<B>>>>,
+    tie_break_seed: Option<u64>,
+    search_strategy: SearchStrategy,
+    cell_selection: CellSelection,
+    progress: Option<Box<dyn Progress>>,
+    stats: Option<Mutex<StatsRecorder>>,
+    max_memory: Option<usize>,
+    #[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+    threads: Option<usize>,
+    #[cfg(feature = "fs")]
+    memory_limit: Option<usize>,
+    backend: Backend,
+    rng: Mutex<StdRng>,
+    render_options: RenderOptions,
+    #[cfg(feature = "fs")]
+    image_format: ImageFormat,
+    symmetry_reduction: bool,
+    fault_free: bool,
+    placement_filter: Option<PlacementFilter>,
+    target: Target,
+    arith: Arith,
+    #[cfg(feature = "fs")]
+    checkpoint_path: Option<PathBuf>,
+    #[cfg(feature = "fs")]
+    resume_from: Option<Checkpoint>,
+    #[cfg(feature = "fs")]
+    cache_dir: Option<PathBuf>,
+    cancel: Option<CancelToken>,
+    time_limit: Option<Duration>,
+    timed_out: bool,
+    // the number of boards whose children have been found, shared with component tilers
+    explored: Arc<AtomicUsize>,
+}
+
+impl<B: Board> Tiler<B> {
+    pub fn new(tiles: TileCollection, initial_board: B) -> Self {
+        Tiler {
+            tiles,
+            initial_board,
+            graph: None,
+            tie_break_seed: None,
+            search_strategy: SearchStrategy::default(),
+            cell_selection: CellSelection::default(),
+            progress: None,
+            stats: None,
+            max_memory: None,
+            threads: None,
+            #[cfg(feature = "fs")]
+            memory_limit: None,
+            backend: Backend::default(),
+            rng: Mutex::new(StdRng::from_entropy()),
+            render_options: RenderOptions::default(),
+            #[cfg(feature = "fs")]
+            image_format: ImageFormat::default(),
+            symmetry_reduction: false,
+            fault_free: false,
+            placement_filter: None,
+            target: Target::default(),
+            arith: Arith::default(),
+            #[cfg(feature = "fs")]
+            checkpoint_path: None,
+            #[cfg(feature = "fs")]
+            resume_from: None,
+            #[cfg(feature = "fs")]
+            cache_dir: None,
+            cancel: None,
+            time_limit: None,
+            timed_out: false,
+            explored: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Uses the given RNG (rather than one seeded from system entropy) to choose between
+    /// the tilings found by the single tiling searches, so that their results can be
+    /// reproduced.  The parallel search is only reproducible when using a single worker.
+    pub fn with_rng<R: Rng>(mut self, rng: R) -> Self {
+        self.rng = Mutex::new(StdRng::from_rng(rng).expect("Unable to seed RNG"));
+        self
+    }
+
+    /// Sets the options used when rendering tilings
+    pub fn with_render_options(mut self, options: RenderOptions) -> Self {
+        self.render_options = options;
+        self
+    }
+
+    /// Sets the format of the images written by `render_all_tilings` and `render_first_tilings`
+    #[cfg(feature = "fs")]
+    pub fn with_image_format(mut self, format: ImageFormat) -> Self {
+        self.image_format = format;
+        self
+    }
+
+    /// Makes `tilings`, `render_all_tilings` and `render_first_tilings` only produce one
+    /// tiling out of each family of tilings which are rotations or reflections of one another
+    pub fn with_symmetry_reduction(mut self) -> Self {
+        self.symmetry_reduction = true;
+        self
+    }
+
+    /// Only accepts tilings with no fault lines (see `Tiling::fault_lines`), when counting,
+    /// enumerating, rendering or searching for tilings.
+    ///
+    /// Whether a tiling has a fault line depends on all of its tiles at once, so fault-free
+    /// tilings are counted by checking every tiling as it is completed, rather than with the
+    /// faster counting methods.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let tiles = TileCollection::from(Tile::domino());
+    ///
+    /// // the smallest rectangles with fault-free domino tilings are 5x6
+    /// let mut tiler = Tiler::new(tiles.clone(), RectangularBoard::new(4, 4)).with_fault_free();
+    /// assert_eq!(tiler.count_tilings(), 0u32.into());
+    ///
+    /// let mut tiler = Tiler::new(tiles, RectangularBoard::new(6, 5)).with_fault_free();
+    /// assert_eq!(tiler.count_tilings(), 6u32.into());
+    /// ```
+    pub fn with_fault_free(mut self) -> Self {
+        self.fault_free = true;
+        self
+    }
+
+    /// Only places tiles where `filter` allows, when counting, enumerating or searching for
+    /// tilings.  The filter is given the board a tile is about to be placed on and the cells
+    /// the tile would cover, and returns whether the placement is allowed.
+    ///
+    /// The filter is checked as each tile is placed during the search, so the shortcuts which
+    /// don't place tiles one at a time (dancing links, Kasteleyn's method, broken-profile
+    /// dynamic programming and Burnside's lemma) and splitting the board into separate regions
+    /// are all turned off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{Position, RectangularBoard};
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// // keep the top left cell of the board from being covered by a vertical domino
+    /// let mut tiler = Tiler::new(TileCollection::from(Tile::domino()), RectangularBoard::new(4, 4))
+    ///     .with_placement_filter(|_, tp| {
+    ///         let covered = tp.covered();
+    ///         !(covered.contains(&Position::new(0, 0)) && covered.contains(&Position::new(1, 0)))
+    ///     });
+    ///
+    /// // half of the 36 domino tilings of a 4x4 board cover it with a horizontal domino
+    /// assert_eq!(tiler.count_tilings(), 18u32.into());
+    /// ```
+    pub fn with_placement_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&RectangularBoard, &TilePosition) -> bool + Send + Sync + 'static,
+    {
+        self.placement_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Only counts, enumerates or searches for the tilings meeting the given target, rather
+    /// than those covering every cell of the board, see `Target`.
+    ///
+    /// Tilings which leave cells uncovered are found by also trying to leave each cell
+    /// uncovered as tiles are placed, so the shortcuts which don't place tiles one at a time
+    /// and splitting the board into separate regions are turned off for them, as with
+    /// `with_placement_filter`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::{Target, Tiler};
+    ///
+    /// let tiles = TileCollection::from(Tile::domino());
+    ///
+    /// // a 3x3 board can't be tiled by dominoes, but 4 dominoes can cover all but a corner
+    /// // or the centre
+    /// let mut tiler = Tiler::new(tiles.clone(), RectangularBoard::new(3, 3));
+    /// assert_eq!(tiler.count_tilings(), 0u32.into());
+    ///
+    /// let mut tiler = Tiler::new(tiles.clone(), RectangularBoard::new(3, 3))
+    ///     .with_target(Target::CoverAtLeast(8));
+    /// assert_eq!(tiler.count_tilings(), 18u32.into());
+    ///
+    /// // only tile the top two rows
+    /// let region = vec![vec![true; 3], vec![true; 3], vec![false; 3]];
+    /// let mut tiler = Tiler::new(tiles, RectangularBoard::new(3, 3))
+    ///     .with_target(Target::CoverRegion(region));
+    /// assert_eq!(tiler.count_tilings(), 3u32.into());
+    /// ```
+    pub fn with_target(mut self, target: Target) -> Self {
+        if let Target::CoverRegion(region) = &target {
+            let grid = self.initial_board.grid();
+            let outside: Vec<(usize, usize)> = (0..grid.height)
+                .flat_map(|i| (0..grid.width).map(move |j| (i, j)))
+                .filter(|&(i, j)| !region.get(i).and_then(|row| row.get(j)).is_some_and(|c| *c))
+                .collect();
+
+            self.initial_board = self.initial_board.with_grid(grid.mark_cells(&outside));
+        }
+
+        self.target = target;
+        self
+    }
+
+    /// Determines whether tilings can only be found by placing tiles one at a time, as the
+    /// placements are filtered or cells may be left uncovered
+    fn needs_search(&self) -> bool {
+        self.placement_filter.is_some() || matches!(self.target, Target::CoverAtLeast(_))
+    }
+
+    /// Returns the placement filter, or a filter accepting every placement if there isn't one
+    fn filter(&self) -> &(dyn Fn(&RectangularBoard, &TilePosition) -> bool + '_) {
+        match &self.placement_filter {
+            Some(filter) => filter.as_ref(),
+            None => &|_, _| true,
+        }
+    }
+
+    /// Returns every board obtained by placing a tile on the given board, respecting the
+    /// placement filter, or by leaving a cell uncovered if the target allows it
+    fn children(&self, board: &B) -> Vec<B> {
+        self.explored.fetch_add(1, Ordering::Relaxed);
+
+        self.placements(board)
+    }
+
+    /// Like `children`, without counting the board as explored
+    fn placements(&self, board: &B) -> Vec<B> {
+        if self.spare_cells(board.grid()) > 0 {
+            return self
+                .indexed_children(board.grid(), self.filter())
+                .into_iter()
+                .map(|(_, grid)| board.with_grid(grid))
+                .collect();
+        }
+
+        board.place_tile_with_selection(&self.tiles, self.cell_selection, self.filter())
+    }
+
+    /// Returns every board obtained by placing a tile on the given board, keeping only the
+    /// placements accepted by `filter`, along with the index (in `cell_tiles`) of the tile
+    /// placed to make each board.  If the target allows it, the cell being covered may also
+    /// be left uncovered, which places no tile.
+    fn indexed_children(
+        &self,
+        board: &RectangularBoard,
+        filter: &dyn Fn(&RectangularBoard, &TilePosition) -> bool,
+    ) -> Vec<(Option<usize>, RectangularBoard)> {
+        // with cells to spare, the board needn't be completely covered, so none of the checks
+        // giving up on boards which can't be are made
+        if self.spare_cells(board) > 0 {
+            let cell = match board.select_cell_filtered(self.cell_selection, &self.tiles, filter) {
+                Some(cell) => cell,
+                None => return Vec::new(),
+            };
+
+            let mut children: Vec<(Option<usize>, RectangularBoard)> = board
+                .place_tile_at(cell, &self.tiles, filter)
+                .into_iter()
+                .map(|(index, child)| (Some(index), child))
+                .collect();
+            children.push((None, board.leave_uncovered(cell)));
+
+            return children;
+        }
+
+        board
+            .place_tile_indexed(&self.tiles, self.cell_selection, filter)
+            .into_iter()
+            .map(|(index, child)| (Some(index), child))
+            .collect()
+    }
+
+    /// Returns the number of cells of the given board which may still be left uncovered
+    fn spare_cells(&self, board: &RectangularBoard) -> usize {
+        match self.target {
+            Target::CoverAtLeast(cells) => {
+                let initial = self.initial_board.grid();
+                let left = board.uncovered_count() - initial.uncovered_count();
+
+                initial
+                    .unmarked_count()
+                    .saturating_sub(cells)
+                    .saturating_sub(left)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Determines whether the given board is finished, so that the placements leading to it
+    /// make up one of the tilings being counted or searched for
+    fn is_finished(&self, board: &RectangularBoard) -> bool {
+        if !board.is_all_marked() {
+            return false;
+        }
+
+        match self.target {
+            Target::CoverAtLeast(cells) => {
+                let initial = self.initial_board.grid();
+                let left = board.uncovered_count() - initial.uncovered_count();
+
+                initial.unmarked_count() >= cells + left
+            }
+            _ => true,
+        }
+    }
+
+    /// Like `children`, but ordering the boards at random, and breaking ties between equally
+    /// constrained cells at random when choosing the most constrained cell
+    fn children_with_rng<R: Rng>(&self, board: &B, rng: &mut R) -> Vec<B> {
+        if self.cell_selection != CellSelection::MostConstrained
+            || self.spare_cells(board.grid()) > 0
+        {
+            let mut boards = self.placements(board);
+            boards.shuffle(rng);
+            return boards;
+        }
+
+        match &self.placement_filter {
+            Some(filter) => board.place_tile_with_rng_filtered(&self.tiles, rng, filter.as_ref()),
+            None => board.place_tile_with_rng(&self.tiles, rng),
+        }
+    }
+
+    /// Sets the arithmetic used to accumulate counts in `count_tilings` and
+    /// `count_tilings_profile`.  With `Arith::Auto` counts are kept in `u128`s until they
+    /// overflow, which is much faster for counts of small boards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::arith::Arith;
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let board = RectangularBoard::new(8, 8);
+    /// let mut tiler = Tiler::new(TileCollection::from(Tile::t_tile(1)), board).with_arith(Arith::Auto);
+    /// assert_eq!(tiler.count_tilings(), 84u32.into());
+    /// ```
+    pub fn with_arith(mut self, arith: Arith) -> Self {
+        self.arith = arith;
+        self
+    }
+
+    /// Determines whether the tilings should be found with dancing links
+    fn uses_dlx(&self) -> bool {
+        self.backend == Backend::Dlx && !self.needs_search()
+    }
+
+    /// Determines whether tilings should be found by solving an integer linear program
+    #[cfg(feature = "ilp")]
+    fn uses_ilp(&self) -> bool {
+        self.backend == Backend::Ilp && !self.needs_search()
+    }
+
+    /// Makes `count_tilings` save its progress to the given file after each layer of
+    /// placements, so that an interrupted count can be continued with `load_checkpoint`.
+    ///
+    /// Checkpointed counts always use the breadth first counter, even when the board
+    /// has several components or could be counted faster another way.
+    #[cfg(feature = "fs")]
+    pub fn with_checkpoint<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.checkpoint_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Loads a checkpoint saved by a count with `with_checkpoint`, so that the next call
+    /// to `count_tilings` continues from where that count left off.
+    ///
+    /// Fails if the checkpoint can't be read, or was saved while counting the tilings of
+    /// a different board or with different tiles.
+    #[cfg(feature = "fs")]
+    pub fn load_checkpoint<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let file = BufReader::new(File::open(path)?);
+        let checkpoint: Checkpoint =
+            bincode::deserialize_from(file).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+        if checkpoint.board != self.initial_board.grid().board
+            || checkpoint.tiles != self.tile_cells()
+            || checkpoint.counts != self.tiles.counts()
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "the checkpoint was saved while tiling a different board or with different tiles",
+            ));
+        }
+
+        self.resume_from = Some(checkpoint);
+        Ok(())
+    }
+
+    /// Makes `count_tilings_by_regions` save the count of every region it counts to a file
+    /// in the given directory, and reuse the counts saved by earlier counts with the same
+    /// tiles.  The regions are remembered by their shapes, so counts of different boards
+    /// (such as the boards of a scaling run) share the regions they have in common.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let cache = tempfile::tempdir().unwrap();
+    /// let tiles = TileCollection::from(Tile::l_tile(2));
+    ///
+    /// let mut tiler = Tiler::new(tiles.clone(), RectangularBoard::new(9, 6)).with_cache_dir(cache.path());
+    /// assert_eq!(tiler.count_tilings_by_regions(), 4312u32.into());
+    ///
+    /// // the second count picks up the regions saved by the first
+    /// let mut tiler = Tiler::new(tiles, RectangularBoard::new(9, 6)).with_cache_dir(cache.path());
+    /// assert_eq!(tiler.count_tilings_by_regions(), 4312u32.into());
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn with_cache_dir<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.cache_dir = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Returns the path of the file in the cache directory holding the counts of regions
+    /// tiled by our tiles, if there is a cache directory
+    #[cfg(feature = "fs")]
+    fn region_cache_path(&self) -> Option<PathBuf> {
+        let mut hasher = DefaultHasher::new();
+        self.initial_board.grid().lattice().hash(&mut hasher);
+        self.tile_cells().hash(&mut hasher);
+
+        self.cache_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("regions-{:016x}.bin", hasher.finish())))
+    }
+
+    /// Loads the counts of regions saved in the cache directory, if there are any
+    #[cfg(feature = "fs")]
+    fn load_region_cache(&self) -> Result<HashMap<RegionShape, BigUint>> {
+        let path = match self.region_cache_path() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(HashMap::new()),
+        };
+
+        let file = BufReader::new(File::open(path)?);
+        let cache: RegionCache =
+            bincode::deserialize_from(file).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+        // a different set of tiles whose file name happens to match
+        if cache.tiles != self.tile_cells() {
+            return Ok(HashMap::new());
+        }
+
+        Ok(cache.regions.into_iter().collect())
+    }
+
+    /// Saves the counts of regions to the cache directory, if there is one
+    #[cfg(feature = "fs")]
+    fn save_region_cache(&self, memo: &HashMap<RegionShape, BigUint>) -> Result<()> {
+        let path = match self.region_cache_path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let cache = RegionCache {
+            tiles: self.tile_cells(),
+            regions: memo
+                .iter()
+                .map(|(shape, count)| (shape.clone(), count.clone()))
+                .collect(),
+        };
+
+        // write to a temporary file first, so that we never leave a partial cache behind
+        std::fs::create_dir_all(self.cache_dir.as_ref().unwrap())?;
+        let temporary = path.with_extension("tmp");
+        {
+            let mut file = BufWriter::new(File::create(&temporary)?);
+            bincode::serialize_into(&mut file, &cache).map_err(Error::other)?;
+            file.flush()?;
+        }
+
+        std::fs::rename(temporary, path)
+    }
+
+    /// Saves the state of a breadth first count to the checkpoint file, if there is one
+    #[cfg(feature = "fs")]
+    fn save_checkpoint(
+        &self,
+        depth: usize,
+        total: &BigUint,
+        stack: &HashSet<BoardKey>,
+        counter: &HashMap<BoardKey, Count>,
+    ) -> Result<()> {
+        let path = match &self.checkpoint_path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let checkpoint = Checkpoint {
+            board: self.initial_board.grid().board.clone(),
+            tiles: self.tile_cells(),
+            counts: self.tiles.counts().to_vec(),
+            depth,
+            total: total.clone(),
+            frontier: stack
+                .iter()
+                .map(|key| (key.clone(), counter[key].to_biguint()))
+                .collect(),
+        };
+
+        // write to a temporary file first, so that we never leave a partial checkpoint behind
+        let temporary = path.with_extension("tmp");
+        {
+            let mut file = BufWriter::new(File::create(&temporary)?);
+            bincode::serialize_into(&mut file, &checkpoint).map_err(Error::other)?;
+            file.flush()?;
+        }
+
+        std::fs::rename(temporary, path)
+    }
+
+    /// Tells the progress sink that we're starting to count or generate the graph
+    fn report_start(&self, phase: &'static str) {
+        self.start_phase(phase);
+
+        if let Some(progress) = &self.progress {
+            let unmarked = self
+                .initial_board
+                .grid()
+                .board
+                .iter()
+                .flatten()
+                .filter(|b| !**b);
+            let smallest_tile = self.tiles.cell_tiles().map(|tile| tile.len()).min();
+
+            progress.on_start(unmarked.count() / smallest_tile.unwrap_or(1).max(1));
+        }
+    }
+
+    fn report_finish(&self) {
+        if let Some(stats) = &self.stats {
+            stats.lock().expect("Unable to lock stats").finish_phase();
+        }
+
+        if let Some(progress) = &self.progress {
+            progress.on_finish();
+        }
+    }
+
+    /// The cells of each tile, used to check that a checkpoint matches this tiler
+    #[cfg(feature = "fs")]
+    fn tile_cells(&self) -> Vec<Vec<(i32, i32)>> {
+        self.tiles
+            .cell_tiles()
+            .map(|tile| tile.cells().to_vec())
+            .collect()
+    }
+
+    /// Reconstructs a board reached from the initial board from its key
+    fn restore(&self, key: &BoardKey) -> B {
+        self.initial_board
+            .with_grid(self.initial_board.grid().restore(key))
+    }
+
+    /// Determines whether the current count or graph build has been cancelled
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+    }
+
+    /// Starts the clock on the time limit given to `with_time_limit`, if there is one, so
+    /// that the computation is cancelled once it passes.  Returns the cancel token to
+    /// restore with `stop_clock`.
+    fn start_clock(&mut self) -> Option<CancelToken> {
+        let previous = self.cancel.clone();
+
+        if let Some(limit) = self.time_limit {
+            self.timed_out = false;
+            self.explored.store(0, Ordering::Relaxed);
+            self.cancel = Some(
+                previous
+                    .clone()
+                    .unwrap_or_default()
+                    .with_deadline(Instant::now() + limit),
+            );
+        }
+
+        previous
+    }
+
+    /// Stops the clock started by `start_clock`, noting whether the time limit passed
+    fn stop_clock(&mut self, previous: Option<CancelToken>) {
+        if self.time_limit.is_some() {
+            self.timed_out = self
+                .cancel
+                .as_ref()
+                .is_some_and(CancelToken::deadline_passed);
+        }
+        self.cancel = previous;
+    }
+
+    /// The error reporting that the time limit passed, if it did, during the last count
+    fn time_limit_error(&self, lower_bound: &BigUint) -> Option<TilerError> {
+        self.timed_out.then(|| TilerError::TimedOut {
+            lower_bound: lower_bound.clone(),
+            explored: self.explored.load(Ordering::Relaxed),
+        })
+    }
+
+    fn rng(&self) -> MutexGuard<'_, StdRng> {
+        self.rng.lock().expect("Unable to lock RNG")
+    }
+
+    /// Sets the algorithm used to count tilings, search for tilings, and render all tilings
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Stops `count_tilings`, `count_tilings_up_to_symmetry`, `try_exists_tiling` and the
+    /// generation of the tiling graph once they have run for the given time.  A count stopped
+    /// early returns the number of tilings found so far, which is only a lower bound -
+    /// `try_count_tilings` reports it as `TilerError::TimedOut`, along with the number of
+    /// boards explored.
+    ///
+    /// The time limit needs a clock, which `wasm32-unknown-unknown` doesn't have.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::error::TilerError;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    /// use std::time::Duration;
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(2));
+    ///
+    /// let mut tiler = Tiler::new(tiles.clone(), RectangularBoard::new(9, 6))
+    ///     .with_time_limit(Duration::from_secs(600));
+    /// assert_eq!(tiler.try_count_tilings(), Ok(4312u32.into()));
+    ///
+    /// let mut tiler = Tiler::new(tiles, RectangularBoard::new(30, 30))
+    ///     .with_time_limit(Duration::ZERO);
+    /// assert!(matches!(
+    ///     tiler.try_count_tilings(),
+    ///     Err(TilerError::TimedOut { .. })
+    /// ));
+    /// ```
+    pub fn with_time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Limits the memory used when counting tilings to approximately the given number
+    /// of bytes.
+    ///
+    /// Counting starts out with the usual breadth first search, and switches to a depth
+    /// first enumeration of the remaining placements once the frontier grows too large.
+    pub fn with_max_memory(mut self, bytes: usize) -> Self {
+        self.max_memory = Some(bytes);
+        self
+    }
+
+    /// Limits the memory used by the frontier of breadth first counts to approximately the
+    /// given number of bytes.
+    ///
+    /// Once the frontier grows past half of this limit, it is moved into temporary files,
+    /// split into buckets by the hash of each board.  Each layer is then counted one bucket at
+    /// a time, so that only a single bucket needs to be held in memory.  Unlike
+    /// `with_max_memory`, boards reached by different placement orders are still merged.
+    #[cfg(feature = "fs")]
+    pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    /// Generates the tiling graph and renders tilings using the given number of threads,
+    /// rather than one thread per CPU
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Reports the progress of tiling counts to the given sink
+    pub fn with_progress<P: Progress + 'static>(mut self, progress: P) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Makes `count_tilings` and the generation of the tiling graph keep statistics on the
+    /// boards they explore, which can be read back with `stats`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(2));
+    /// let mut tiler = Tiler::new(tiles, RectangularBoard::new(9, 6)).with_stats();
+    /// assert_eq!(tiler.count_tilings(), 4312u32.into());
+    ///
+    /// // 18 trominoes fill the board, so the count took 18 layers of placements
+    /// let stats = tiler.stats().unwrap();
+    /// assert_eq!(stats.frontier_sizes().len(), 18);
+    /// assert_eq!(stats.frontier_sizes().last(), Some(&0));
+    /// assert!(stats.states() > 18);
+    /// ```
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(Mutex::new(StatsRecorder::default()));
+        self
+    }
+
+    /// Returns the statistics kept by the last count or generation of the tiling graph, if
+    /// `with_stats` was called.  Counts which don't search through boards (such as domino
+    /// counts given by `kasteleyn`, or counts using dancing links) leave no statistics.
+    pub fn stats(&self) -> Option<SolveStats> {
+        let recorder = self.stats.as_ref()?.lock().expect("Unable to lock stats");
+
+        recorder.started.then(|| recorder.stats.clone())
+    }
+
+    /// Forgets the statistics of any earlier count
+    fn reset_stats(&self) {
+        if let Some(stats) = &self.stats {
+            *stats.lock().expect("Unable to lock stats") = StatsRecorder::default();
+        }
+    }
+
+    /// Starts timing the next phase of a count, finishing the current one
+    fn start_phase(&self, name: &'static str) {
+        if let Some(stats) = &self.stats {
+            let mut recorder = stats.lock().expect("Unable to lock stats");
+            recorder.finish_phase();
+            recorder.started = true;
+            recorder.phase = Some((name, Instant::now()));
+        }
+    }
+
+    /// Records a layer of a count, with the number of boards left in the frontier, the
+    /// number of distinct boards reached by the layer and an estimate of the memory in use
+    fn record_level(&self, frontier_size: usize, states: usize, memory: usize) {
+        if let Some(stats) = &self.stats {
+            let mut recorder = stats.lock().expect("Unable to lock stats");
+            recorder.stats.frontier_sizes.push(frontier_size);
+            recorder.stats.states += states;
+            recorder.record_memory(memory);
+        }
+    }
+
+    /// Records an estimate of the memory in use outside of a layer of a count
+    fn record_memory(&self, memory: usize) {
+        if let Some(stats) = &self.stats {
+            stats
+                .lock()
+                .expect("Unable to lock stats")
+                .record_memory(memory);
+        }
+    }
+
+    /// Sets how the cell covered by the next tile is chosen as tilings are built up one tile
+    /// at a time, see `CellSelection`.  This doesn't change the tilings found, only how many
+    /// boards are explored to find them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{CellSelection, RectangularBoard};
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let board = RectangularBoard::new(8, 8);
+    /// let mut tiler = Tiler::new(TileCollection::from(Tile::t_tile(1)), board)
+    ///     .with_cell_selection(CellSelection::FewestFits);
+    /// assert_eq!(tiler.count_tilings(), 84u32.into());
+    /// ```
+    pub fn with_cell_selection(mut self, selection: CellSelection) -> Self {
+        self.cell_selection = selection;
+        self
+    }
+
+    /// Makes `get_single_tiling` break ties between equally constrained cells (and order
+    /// the placements it tries) at random, using an RNG seeded with the given seed.
+    pub fn with_tie_breaking(mut self, seed: u64) -> Self {
+        self.tie_break_seed = Some(seed);
+        self
+    }
+
+    /// Sets the order in which `get_single_tiling` tries the ways of placing tiles
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::{SearchBudget, SearchStrategy, Tiler};
+    ///
+    /// let mut tiler = Tiler::new(TileCollection::from(Tile::l_tile(2)), RectangularBoard::new(9, 5))
+    ///     .with_search_strategy(SearchStrategy::LeastFreedom);
+    ///
+    /// let tiling = tiler.get_single_tiling(SearchBudget::first_solution()).unwrap();
+    /// assert_eq!(tiling.placements().len(), 15);
+    /// ```
+    pub fn with_search_strategy(mut self, strategy: SearchStrategy) -> Self {
+        self.search_strategy = strategy;
+        self
+    }
+
+    /// Uses the given tiling graph of the initial board (such as one read back in with
+    /// `BoardGraph::from_reader`) instead of generating it, so that its tilings can be
+    /// counted and rendered again.  The graph's boards are trusted, so the tiles are only
+    /// used by searches which don't go through the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(2));
+    /// let mut tiler = Tiler::new(tiles, RectangularBoard::l_board(2, 4));
+    /// let json = serde_json::to_string(&*tiler.graph().read().unwrap()).unwrap();
+    ///
+    /// let graph: BoardGraph = BoardGraph::from_reader(json.as_bytes()).unwrap();
+    /// let initial = graph.get_node(0).unwrap().clone();
+    ///
+    /// let mut tiler = Tiler::new(TileCollection::new(Vec::new()), initial).with_graph(graph);
+    /// assert_eq!(tiler.count_tilings(), 409u32.into());
+    /// ```
+    pub fn with_graph(mut self, graph: BoardGraph<B>) -> Self {
+        self.graph = Some(Arc::new(RwLock::new(graph)));
+        self
+    }
+
+    pub fn count_tilings(&mut self) -> BigUint {
+        let previous = self.start_clock();
+        let count = self.count_tilings_unlimited();
+        self.stop_clock(previous);
+
+        count
+    }
+
+    /// Like `count_tilings`, but returns `TilerError::TimedOut` if the count was stopped by
+    /// the time limit given to `with_time_limit`
+    pub fn try_count_tilings(&mut self) -> std::result::Result<BigUint, TilerError> {
+        let count = self.count_tilings();
+
+        match self.time_limit_error(&count) {
+            Some(e) => Err(e),
+            None => Ok(count),
+        }
+    }
+
+    /// Returns the entropy per cell of the tilings of the initial board, the natural
+    /// logarithm of the number of tilings divided by the number of cells to cover.  As
+    /// boards grow this tends to the entropy of the tiles, such as Catalan's constant over
+    /// pi (about 0.2916) for dominoes on the square lattice.  A board with no tilings has an
+    /// entropy of negative infinity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let tiles = TileCollection::from(Tile::domino());
+    /// let mut tiler = Tiler::new(tiles, RectangularBoard::new(8, 8));
+    ///
+    /// // there are 12988816 domino tilings of a chessboard
+    /// assert!((tiler.entropy_per_cell() - 12988816f64.ln() / 64.0).abs() < 1e-12);
+    /// ```
+    pub fn entropy_per_cell(&mut self) -> f64 {
+        let count = self.count_tilings();
+
+        arith::ln(&count) / self.initial_board.grid().unmarked_count() as f64
+    }
+
+    /// Counts the tilings of the initial board, ignoring the time limit
+    fn count_tilings_unlimited(&mut self) -> BigUint {
+        self.reset_stats();
+
+        if self.fault_free {
+            return self.count_matching_tilings(Tiling::is_fault_free);
+        }
+
+        #[cfg(feature = "fs")]
+        if self.checkpoint_path.is_some() || self.resume_from.is_some() {
+            return self.count_tilings_quick();
+        }
+
+        // Use a boardgraph, if available.
+        if self.graph.is_some() {
+            self.count_tilings_from_graph()
+        } else if !self.needs_search()
+            && kasteleyn::applies_to(self.initial_board.grid(), &self.tiles)
+        {
+            let grid = self.initial_board.grid();
+            // a determinant given up on part way through says nothing about the count
+            kasteleyn::count_domino_tilings_cancellable(
+                grid.width,
+                grid.height,
+                self.cancel.as_ref(),
+            )
+            .unwrap_or_default()
+        } else if let Some(tilers) = self.component_tilers() {
+            // the tilings of disjoint regions are independent of one another
+            tilers
+                .into_iter()
+                .map(|mut tiler| tiler.count_tilings())
+                .product()
+        } else if self.uses_dlx() {
+            let mut problem = ExactCover::new(&self.initial_board, &self.tiles);
+            if let Some(token) = &self.cancel {
+                problem = problem.with_cancel_token(token.clone());
+            }
+            problem.count()
+        } else {
+            self.count_tilings_quick()
+        }
+    }
+
+    /// Like `count_tilings`, but stops early (returning `TilerError::Cancelled`) once the
+    /// given token is cancelled, so that the count can be abandoned from another thread.
+    /// A count stopped by the time limit returns `TilerError::TimedOut` instead.
+    ///
+    /// Checkpoints are only saved for complete layers of placements, so a cancelled count
+    /// can still be resumed from its last checkpoint.
+    pub fn count_tilings_cancellable(
+        &mut self,
+        token: CancelToken,
+    ) -> std::result::Result<BigUint, TilerError> {
+        self.cancel = Some(token);
+        let count = self.count_tilings();
+        let cancelled = self.is_cancelled();
+        self.cancel = None;
+
+        if let Some(e) = self.time_limit_error(&count) {
+            Err(e)
+        } else if cancelled {
+            Err(TilerError::Cancelled)
+        } else {
+            Ok(count)
+        }
+    }
+
+    /// If the initial board consists of several disjoint regions, returns
+    /// a tiler for each of them.
+    fn component_tilers(&self) -> Option<Vec<Tiler<B>>> {
+        let components = self.initial_board.grid().components();
+
+        // components share the limited tiles and the cells which may be left uncovered, so
+        // they can't be tiled independently - and fault lines can run through several
+        // components at once, and placement filters see the whole board
+        if components.len() < 2 || self.tiles.has_counts() || self.fault_free || self.needs_search()
+        {
+            return None;
+        }
+
+        Some(
+            components
+                .into_iter()
+                .map(|component| {
+                    let component = self.initial_board.with_grid(component);
+                    let mut tiler = Tiler::new(self.tiles.clone(), component);
+                    tiler.tie_break_seed = self.tie_break_seed;
+                    tiler.search_strategy = self.search_strategy;
+                    tiler.cell_selection = self.cell_selection;
+                    tiler.backend = self.backend;
+                    tiler.arith = self.arith;
+                    tiler.render_options = self.render_options.clone();
+                    #[cfg(feature = "fs")]
+                    {
+                        tiler.image_format = self.image_format;
+                    }
+                    tiler.symmetry_reduction = self.symmetry_reduction;
+                    tiler.cancel = self.cancel.clone();
+                    tiler.explored = Arc::clone(&self.explored);
+                    tiler.with_rng(&mut *self.rng())
+                })
+                .collect(),
+        )
+    }
+
+    /// Searches for a tiling of each component separately, and combines them
+    /// into a single tiling of the initial board.
+    fn combine_component_tilings<F>(&self, tilers: Vec<Tiler<B>>, mut search: F) -> Option<Tiling>
+    where
+        F: FnMut(&mut Tiler<B>) -> Option<Tiling>,
+    {
+        let mut placements = Vec::new();
+
+        // each component is the same size as the initial board, so its placements
+        // can be used on the initial board as they are
+        for mut tiler in tilers {
+            let tiling = search(&mut tiler)?;
+            placements.extend(tiling.placements().iter().cloned());
+        }
+
+        Some(Tiling::from_placements(
+            self.initial_board.grid().clone(),
+            placements,
+        ))
+    }
+
+    /// Returns the symmetries used to merge the boards of the frontier in `count_tilings`.
+    ///
+    /// A board and its image under a symmetry of the initial board have the same number of
+    /// completions, so the frontier only needs to keep one board from each orbit, counting
+    /// the ways of reaching any board in the orbit.  Limited tiles are counted separately
+    /// from their reflections, and placement filters needn't be symmetric, so in those cases
+    /// no boards are merged.
+    fn frontier_symmetries(&self) -> Vec<Symmetry> {
+        if self.tiles.has_counts() || self.placement_filter.is_some() {
+            return vec![Symmetry::Identity];
+        }
+
+        self.symmetry_group()
+    }
+
+    /// Returns the key of the board representing the given board in the frontier of
+    /// `count_tilings`, see `frontier_symmetries`
+    fn frontier_key(&self, board: &B, symmetries: &[Symmetry]) -> BoardKey {
+        if symmetries.len() > 1 {
+            board.grid().canonical_key(symmetries)
+        } else {
+            board.grid().key()
+        }
+    }
+
+    /// Counts the tilings by a breadth first search, placing one more tile on every board of
+    /// each layer.  The counts of all of the boards of a layer which are finished (see
+    /// `is_finished`) are added to the total, and the rest make up the next layer.
+    fn count_tilings_quick(&self) -> BigUint {
+        // Boards are stored by their compact keys, and only restored when we place
+        // tiles on them - this is far smaller than storing every board in full
+        let symmetries = self.frontier_symmetries();
+        let initial_key = self.initial_board.grid().key();
+
+        // we keep the counter behind an Arc<RwLock<>>
+        let mut counter = HashMap::new();
+
+        // our working stack
+        let mut stack = HashSet::new();
+
+        // tiles of different sizes can complete the board after different numbers
+        // of placements, so we accumulate the count of completed boards from each layer
+        let mut total = num::BigUint::zero();
+        let mut depth = 0;
+
+        counter.insert(initial_key.clone(), Count::one(self.arith));
+        stack.insert(initial_key);
+
+        // carry on from where the checkpointed count left off
+        #[cfg(feature = "fs")]
+        if let Some(checkpoint) = &self.resume_from {
+            stack = checkpoint
+                .frontier
+                .iter()
+                .map(|(key, _)| key.clone())
+                .collect();
+            counter = checkpoint
+                .frontier
+                .iter()
+                .map(|(key, count)| (key.clone(), Count::from_biguint(count.clone(), self.arith)))
+                .collect();
+            total = checkpoint.total.clone();
+            depth = checkpoint.depth;
+        }
+
+        let mut counter = Arc::new(RwLock::new(counter));
+        self.report_start("breadth first search");
+
+        while !stack.is_empty() {
+            let handles = stack
+                .par_iter()
+                .map(|key| {
+                    if self.is_cancelled() {
+                        return (HashSet::new(), HashSet::new(), HashMap::new());
+                    }
+                    let current_count = &counter.read().unwrap()[key];
+
+                    let boards = self.children(&self.restore(key));
+
+                    let mut next_boards = HashSet::new();
+                    let mut completed_boards = HashSet::new();
+                    let mut count_updates = HashMap::new();
+
+                    for board in boards {
+                        let board_key = self.frontier_key(&board, &symmetries);
+
+                        merge_into_orbit(
+                            &mut count_updates,
+                            board_key.clone(),
+                            board.grid().key(),
+                            current_count,
+                            self.arith,
+                        );
+
+                        if self.is_finished(board.grid()) {
+                            completed_boards.insert(board_key);
+                        } else {
+                            next_boards.insert(board_key);
+                        }
+                    }
+
+                    (next_boards, completed_boards, count_updates)
+                })
+                .collect::<Vec<_>>();
+
+            // a cancelled layer is incomplete, so we stop before it replaces the frontier
+            if self.is_cancelled() {
+                self.report_finish();
+                return total;
+            }
+
+            let step_stack = Arc::new(RwLock::new(HashSet::new()));
+            let step_completed = Arc::new(RwLock::new(HashSet::new()));
+            let orbits = Arc::new(RwLock::new(HashMap::new()));
+
+            handles
+                .into_par_iter()
+                .for_each(|(next_boards, completed_boards, count_updates)| {
+                    // extend the new stack
+                    {
+                        let mut stack_write = step_stack.write().unwrap();
+                        stack_write.extend(next_boards);
+                    }
+
+                    // update all of the tiling counts
+                    {
+                        let mut orbits_write = orbits.write().unwrap();
+
+                        // update the counts
+                        for (orbit, (board, count)) in count_updates {
+                            merge_into_orbit(&mut orbits_write, orbit, board, &count, self.arith);
+                        }
+                    }
+
+                    // completed boards occur so infrequently that we only take the lock
+                    // when there are some
+                    if !completed_boards.is_empty() {
+                        step_completed.write().unwrap().extend(completed_boards);
+                    }
+                });
+
+            // every finished board of the layer adds its count to the total, whichever
+            // thread found it and in whatever order
+            let mut orbits = Arc::try_unwrap(orbits).unwrap().into_inner().unwrap();
+            let states = orbits.len();
+            let completed = Arc::try_unwrap(step_completed)
+                .unwrap()
+                .into_inner()
+                .unwrap();
+            for orbit in &completed {
+                let (_, count) = orbits.remove(orbit).unwrap();
+                total += BigUint::from(count);
+            }
+
+            // each orbit of boards in the frontier is represented by one of its boards
+            stack = Arc::try_unwrap(step_stack)
+                .unwrap()
+                .into_inner()
+                .unwrap()
+                .iter()
+                .map(|orbit| orbits[orbit].0.clone())
+                .collect();
+            counter = Arc::new(RwLock::new(orbits.into_values().collect()));
+
+            // The next layer is usually larger than the current one, so we switch
+            // strategies once the frontier (stored in both the stack and the counter)
+            // uses half of our memory budget
+            let frontier_memory: usize = stack
+                .iter()
+                .map(|key| 2 * key.estimated_memory() + std::mem::size_of::<Count>())
+                .sum();
+
+            depth += 1;
+            self.record_level(stack.len(), states, frontier_memory);
+            if let Some(progress) = &self.progress {
+                progress.on_level(depth, stack.len(), &total);
+            }
+
+            #[cfg(feature = "fs")]
+            self.save_checkpoint(depth, &total, &stack, &counter.read().unwrap())
+                .expect("Unable to save checkpoint");
+
+            #[cfg(feature = "fs")]
+            if let Some(memory_limit) = self.memory_limit {
+                if 2 * frontier_memory > memory_limit {
+                    let mut counts = std::mem::take(&mut *counter.write().unwrap());
+                    let frontier = stack
+                        .into_iter()
+                        .map(|key| {
+                            let count = counts.remove(&key).unwrap();
+                            (key, BigUint::from(count))
+                        })
+                        .collect();
+
+                    self.start_phase("spilling to disk");
+                    let remaining = self
+                        .count_completions_spilled(frontier, depth, memory_limit)
+                        .expect("Unable to spill the frontier to disk");
+
+                    self.report_finish();
+                    return total + remaining;
+                }
+            }
+
+            if let Some(max_memory) = self.max_memory {
+                if 2 * frontier_memory > max_memory {
+                    // the depth first searches fill whatever memory is left over
+                    self.start_phase("depth first search");
+                    self.record_memory(max_memory);
+
+                    let worker_memory =
+                        (max_memory - frontier_memory.min(max_memory)) / current_num_threads();
+                    let counter_read = counter.read().unwrap();
+
+                    let remaining: BigUint = stack
+                        .par_iter()
+                        .map(|key| {
+                            let board = self.restore(key);
+                            counter_read[key].to_biguint()
+                                * self.count_completions_dfs(&board, Some(worker_memory))
+                        })
+                        .sum();
+
+                    self.report_finish();
+                    return total + remaining;
+                }
+            }
+        }
+
+        self.report_finish();
+        total
+    }
+
+    /// Counts the completions of the given frontier (boards paired with the number of
+    /// ways to reach them) by a breadth first search whose layers are stored on disk.
+    ///
+    /// Each layer is split into bucket files by the hash of each board, so that all the
+    /// ways of reaching a board end up in the same bucket.  Buckets are loaded (and their
+    /// duplicate boards merged) one at a time, and buckets which have grown too large to
+    /// load are first split into smaller buckets.
+    #[cfg(feature = "fs")]
+    fn count_completions_spilled(
+        &self,
+        frontier: Vec<(BoardKey, BigUint)>,
+        mut depth: usize,
+        memory_limit: usize,
+    ) -> Result<BigUint> {
+        let directory = tempfile::tempdir()?;
+        let bucket_memory = (memory_limit / 2).max(1);
+        let mut total = BigUint::zero();
+
+        let frontier_memory: usize = frontier
+            .iter()
+            .map(|(key, _)| key.estimated_memory() + std::mem::size_of::<BigUint>())
+            .sum();
+        let bucket_count = (2 * frontier_memory / bucket_memory).max(2);
+
+        let mut buckets = SpillBuckets::create(directory.path(), depth as u64, bucket_count)?;
+        for (key, count) in frontier {
+            buckets.push(&key, &count)?;
+        }
+        let mut layer = buckets.finish()?;
+
+        while !layer.is_empty() {
+            // we expect each bucket of the next layer to be about as large as a bucket
+            // of this layer, which keeps the number of buckets which need splitting small
+            let layer_bytes: u64 = layer.iter().map(|bucket| bucket.bytes).sum();
+            let bucket_count = ((layer_bytes / bucket_memory as u64) as usize + 1).max(layer.len());
+
+            let mut next = SpillBuckets::create(directory.path(), depth as u64 + 1, bucket_count)?;
+            let mut frontier_size = 0;
+            let mut bucket_memory_used = 0;
+
+            while let Some(mut bucket) = layer.pop() {
+                if self.is_cancelled() {
+                    return Ok(total);
+                }
+                if bucket.bytes > bucket_memory as u64 {
+                    let mut parts = bucket.split(directory.path(), bucket_memory)?;
+
+                    // if every record went to the same part, they are (almost certainly)
+                    // copies of a few boards, which will merge into a small map
+                    if parts.len() > 1 {
+                        layer.extend(parts);
+                        continue;
+                    }
+                    bucket = parts.pop().unwrap();
+                }
+
+                let boards: Vec<(BoardKey, BigUint)> = bucket.load()?.into_iter().collect();
+                frontier_size += boards.len();
+                bucket_memory_used = bucket_memory_used.max(
+                    boards
+                        .iter()
+                        .map(|(key, _)| key.estimated_memory() + std::mem::size_of::<BigUint>())
+                        .sum(),
+                );
+
+                for chunk in boards.chunks(SPILL_CHUNK_SIZE) {
+                    let children: Vec<(BoardKey, BigUint, bool)> = chunk
+                        .par_iter()
+                        .flat_map_iter(|(key, count)| {
+                            self.children(&self.restore(key))
+                                .into_iter()
+                                .map(move |board| {
+                                    (
+                                        board.grid().key(),
+                                        count.clone(),
+                                        self.is_finished(board.grid()),
+                                    )
+                                })
+                        })
+                        .collect();
+
+                    for (key, count, complete) in children {
+                        if complete {
+                            total += count;
+                        } else {
+                            next.push(&key, &count)?;
+                        }
+                    }
+                }
+            }
+
+            depth += 1;
+            self.record_level(frontier_size, frontier_size, bucket_memory_used);
+            if let Some(progress) = &self.progress {
+                progress.on_level(depth, frontier_size, &total);
+            }
+
+            layer = next.finish()?;
+        }
+
+        Ok(total)
+    }
+
+    /// Counts tilings by a depth first enumeration of every sequence of placements.
+    ///
+    /// Unlike `count_tilings`, boards reached by different placement orders aren't
+    /// deduplicated, so this is usually much slower - but memory use is bounded by the
+    /// depth of the search rather than the size of the frontier.  Each level of the search
+    /// caches the boards reachable from it while the estimated size of the stack stays below
+    /// `memory_limit` bytes; past that, each child board is recomputed when it is needed.
+    pub fn count_tilings_dfs(&self, memory_limit: Option<usize>) -> BigUint {
+        if self.fault_free {
+            return self.count_matching_tilings(Tiling::is_fault_free);
+        }
+
+        self.count_completions_dfs(&self.initial_board, memory_limit)
+    }
+
+    /// Estimates the number of tilings of the initial board by sequential importance
+    /// sampling, for boards with far too many tilings to count exactly.
+    ///
+    /// Each sample places tiles one at a time, at the same most constrained cell used when
+    /// counting, choosing uniformly at random between the ways of covering it.  A sample
+    /// which completes the board is weighted by the product of the number of choices it had
+    /// at each step (and one which gets stuck by zero), so that the mean weight of the
+    /// samples is an unbiased estimate of the number of tilings (see Knuth, "Estimating the
+    /// efficiency of backtrack programs").  Samples are taken in parallel, using seeds drawn
+    /// from the tiler's RNG, so the estimate can be reproduced with `with_rng`.
+    ///
+    /// Every tiling is counted, ignoring `with_symmetry_reduction` and `with_fault_free`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `samples` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let tiles = TileCollection::from(Tile::domino());
+    /// let tiler = Tiler::new(tiles, RectangularBoard::new(8, 8))
+    ///     .with_rng(StdRng::seed_from_u64(1));
+    ///
+    /// // there are 12988816 domino tilings of a chessboard
+    /// let estimate = tiler.estimate_tilings(10000);
+    /// assert!((estimate.estimate() - 12988816.0).abs() < 3.0 * estimate.standard_error());
+    /// assert!(estimate.lower_bound() < 12988816.0 && 12988816.0 < estimate.upper_bound());
+    /// ```
+    pub fn estimate_tilings(&self, samples: usize) -> TilingEstimate {
+        assert!(
+            samples > 0,
+            "at least one sample is needed to estimate a count"
+        );
+
+        let seeds: Vec<u64> = {
+            let mut rng = self.rng();
+            (0..samples).map(|_| rng.gen()).collect()
+        };
+
+        // the weights can be far too large for an f64, so we keep their logarithms
+        let ln_weights: Vec<f64> = self.install(|| {
+            seeds
+                .into_par_iter()
+                .map(|seed| self.sample_ln_weight(&mut StdRng::seed_from_u64(seed)))
+                .collect()
+        });
+
+        TilingEstimate::from_ln_weights(&ln_weights)
+    }
+
+    /// Places tiles at random until the board is complete (returning the logarithm of the
+    /// product of the number of choices at each step) or stuck (returning negative infinity)
+    fn sample_ln_weight<R: Rng>(&self, rng: &mut R) -> f64 {
+        let mut board = self.initial_board.clone();
+        let mut ln_weight = 0.0;
+
+        while !self.is_finished(board.grid()) {
+            let mut children = self.children(&board);
+            if children.is_empty() {
+                return f64::NEG_INFINITY;
+            }
+
+            ln_weight += (children.len() as f64).ln();
+            board = children.swap_remove(rng.gen_range(0..children.len()));
+        }
+
+        ln_weight
+    }
+
+    /// Counts the tilings of the initial board by splitting it into independent regions.
+    ///
+    /// Once the placed tiles cut the empty cells into several separate regions, each region
+    /// can be tiled independently of the others, so the number of ways of finishing the board
+    /// is the product of the numbers of tilings of its regions.  The count of each region is
+    /// remembered by its shape, so a region which turns up again anywhere on the board is
+    /// only counted once.  This pays off on boards where tiles often wall off regions, as
+    /// the ways of tiling each region are counted once rather than in every combination
+    /// with the ways of tiling the others.
+    ///
+    /// If the number of times a tile may be used is limited, the board wraps around, only
+    /// fault-free tilings are wanted or there is a placement filter, the regions aren't
+    /// independent, so this falls back to `count_tilings`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(2));
+    /// let mut tiler = Tiler::new(tiles, RectangularBoard::new(30, 3));
+    ///
+    /// assert_eq!(tiler.count_tilings_by_regions(), 32768u32.into());
+    /// assert_eq!(tiler.count_tilings(), 32768u32.into());
+    /// ```
+    pub fn count_tilings_by_regions(&mut self) -> BigUint {
+        if self.tiles.has_counts()
+            || self.initial_board.grid().boundary() != Boundary::Bounded
+            || self.fault_free
+            || self.needs_search()
+        {
+            return self.count_tilings();
+        }
+
+        #[cfg(feature = "fs")]
+        let mut memo = self
+            .load_region_cache()
+            .expect("Unable to load the region cache");
+        #[cfg(not(feature = "fs"))]
+        let mut memo = HashMap::new();
+
+        #[cfg(feature = "fs")]
+        let cached = memo.len();
+
+        let board = self.initial_board.clone();
+        let count = self.count_region_completions(&board, &mut memo);
+
+        // a cancelled count leaves partial counts behind, which mustn't be reused
+        #[cfg(feature = "fs")]
+        if memo.len() > cached && !self.is_cancelled() {
+            self.save_region_cache(&memo)
+                .expect("Unable to save the region cache");
+        }
+
+        count
+    }
+
+    /// Counts the ways of completing the given board to a tiling, multiplying together the
+    /// counts of its separate regions, see `count_tilings_by_regions`
+    fn count_region_completions(
+        &self,
+        board: &B,
+        memo: &mut HashMap<RegionShape, BigUint>,
+    ) -> BigUint {
+        if self.is_finished(board.grid()) {
+            return BigUint::one();
+        }
+        if self.is_cancelled() {
+            return BigUint::zero();
+        }
+
+        if !board.grid().is_connected() {
+            let mut count = BigUint::one();
+
+            // there's no need to count the other regions once one can't be tiled
+            for component in board.grid().components() {
+                count *= self.count_region_completions(&board.with_grid(component), memo);
+                if count.is_zero() {
+                    break;
+                }
+            }
+
+            return count;
+        }
+
+        let shape = RegionShape::new(board.grid());
+        if let Some(count) = memo.get(&shape) {
+            return count.clone();
+        }
+
+        let count = self
+            .children(board)
+            .iter()
+            .map(|child| self.count_region_completions(child, memo))
+            .sum::<BigUint>();
+
+        memo.insert(shape, count.clone());
+        count
+    }
+
+    /// Counts the tilings of the initial board using broken-profile dynamic programming.
+    ///
+    /// Cells are visited one at a time along the shorter side of the board, and each
+    /// state records which of the upcoming cells are already covered by placed tiles.
+    /// The number of states is at most exponential in the shorter side of the board,
+    /// regardless of its length, so this can count tilings of long boards which are far
+    /// out of reach of `count_tilings`.
+    ///
+    /// If a tile is too large for its profile to fit in 128 cells, the number of times a
+    /// tile may be used is limited, the board wraps around or isn't a square grid, only
+    /// fault-free tilings are wanted or there is a placement filter, this falls back to
+    /// `count_tilings`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::hex::HexBoard;
+    /// use dcc_tiler::tile::{CellTile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// // a hexagon with sides of length 2 has 20 lozenge tilings
+    /// let tiles = TileCollection::from(CellTile::lozenge());
+    /// let mut tiler = Tiler::new(tiles, HexBoard::hexagon(2, 2, 2));
+    /// assert_eq!(tiler.count_tilings_profile(), 20u32.into());
+    /// assert_eq!(tiler.count_tilings_profile(), tiler.count_tilings());
+    /// ```
+    pub fn count_tilings_profile(&mut self) -> BigUint {
+        let board = self.initial_board.grid();
+
+        // Scan along the shorter side, so that the profile is as small as possible.
+        // `along` is the length of each line of cells, `across` is the number of lines.
+        let transposed = board.height > board.width;
+        let (along, across) = if transposed {
+            (board.width, board.height)
+        } else {
+            (board.height, board.width)
+        };
+        let is_marked = |line: usize, k: usize| {
+            if transposed {
+                board.board[line][k]
+            } else {
+                board.board[k][line]
+            }
+        };
+
+        // Each tile is anchored at its first cell in scan order, with the remaining cells
+        // given as (line, position along line) offsets from it.
+        let shapes: Vec<Vec<(i64, i64)>> = self
+            .tiles
+            .cell_tiles()
+            .map(|tile| {
+                let mut cells: Vec<(i64, i64)> = tile
+                    .cells()
+                    .iter()
+                    .map(|&(r, c)| {
+                        if transposed {
+                            (r as i64, c as i64)
+                        } else {
+                            (c as i64, r as i64)
+                        }
+                    })
+                    .collect();
+                cells.sort_unstable();
+                let (l0, k0) = cells[0];
+
+                cells.iter().map(|(l, k)| (l - l0, k - k0)).collect()
+            })
+            .collect();
+
+        let fits_in_profile = shapes
+            .iter()
+            .flatten()
+            .all(|(dl, dk)| dl * along as i64 + dk < 128);
+
+        if !fits_in_profile
+            || self.tiles.has_counts()
+            || board.boundary() != Boundary::Bounded
+            || board.lattice() != Lattice::Square
+            || self.fault_free
+            || self.needs_search()
+        {
+            return self.count_tilings();
+        }
+
+        // bit `n` of a profile is set if the `n`th cell after the current one is covered
+        let arith = self.arith;
+        let mut states: HashMap<u128, Count> = HashMap::new();
+        states.insert(0, Count::one(arith));
+
+        for line in 0..across {
+            for k in 0..along {
+                let mut next_states: HashMap<u128, Count> = HashMap::new();
+
+                for (profile, count) in states {
+                    if profile & 1 == 1 || is_marked(line, k) {
+                        *next_states
+                            .entry(profile >> 1)
+                            .or_insert_with(|| Count::zero(arith)) += &count;
+                        continue;
+                    }
+
+                    'shapes: for shape in &shapes {
+                        let mut covered = profile;
+
+                        for &(dl, dk) in shape {
+                            let (l, p) = (line as i64 + dl, k as i64 + dk);
+
+                            if l >= across as i64
+                                || p < 0
+                                || p >= along as i64
+                                || is_marked(l as usize, p as usize)
+                            {
+                                continue 'shapes;
+                            }
+
+                            let bit = 1u128 << (dl * along as i64 + dk);
+                            if covered & bit != 0 {
+                                continue 'shapes;
+                            }
+                            covered |= bit;
+                        }
+
+                        *next_states
+                            .entry(covered >> 1)
+                            .or_insert_with(|| Count::zero(arith)) += &count;
+                    }
+                }
+
+                states = next_states;
+            }
+        }
+
+        states.remove(&0).map_or_else(BigUint::zero, BigUint::from)
+    }
+
+    /// Counts the tilings of the initial board, treating tilings which are rotations or
+    /// reflections of one another as the same.
+    ///
+    /// This uses Burnside's lemma: the number of distinct tilings is the average, over the
+    /// symmetries of the board (which also map the tiles onto themselves), of the number of
+    /// tilings left unchanged by each symmetry.  A count stopped by the time limit (see
+    /// `with_time_limit`) is meaningless, so use `try_count_tilings_up_to_symmetry` with one.
+    pub fn count_tilings_up_to_symmetry(&mut self) -> BigUint {
+        let previous = self.start_clock();
+        let count = self.count_tilings_up_to_symmetry_unlimited();
+        self.stop_clock(previous);
+
+        count
+    }
+
+    /// Like `count_tilings_up_to_symmetry`, but returns `TilerError::TimedOut` if the count
+    /// was stopped by the time limit given to `with_time_limit`
+    pub fn try_count_tilings_up_to_symmetry(&mut self) -> std::result::Result<BigUint, TilerError> {
+        let count = self.count_tilings_up_to_symmetry();
+
+        match self.time_limit_error(&BigUint::zero()) {
+            Some(e) => Err(e),
+            None => Ok(count),
+        }
+    }
+
+    fn count_tilings_up_to_symmetry_unlimited(&mut self) -> BigUint {
+        let symmetries = self.symmetry_group();
+
+        // the fixed tilings below are built from whole orbits of placements, which doesn't
+        // keep track of how many times each tile is used, of fault lines, of placement
+        // filters or of uncovered cells - so we check every tiling instead
+        if self.tiles.has_counts() || self.fault_free || self.needs_search() {
+            let fault_free = self.fault_free;
+
+            return self.count_matching_tilings(|tiling| {
+                tiling.is_canonical(&symmetries) && (!fault_free || tiling.is_fault_free())
+            });
+        }
+
+        let mut total = BigUint::zero();
+
+        for symmetry in &symmetries {
+            total += if *symmetry == Symmetry::Identity {
+                self.count_tilings_unlimited()
+            } else {
+                let board = self.initial_board.grid();
+                self.count_fixed_completions(board, *symmetry, &mut HashMap::new())
+            };
+        }
+
+        total / symmetries.len()
+    }
+
+    /// Counts the tilings of the initial board by the number of tiles they use in each
+    /// orientation.
+    ///
+    /// The orientations are the shapes of `TileCollection::cell_tiles`, so each key of the
+    /// returned map gives the number of tiles placed in each of those shapes, and its value is
+    /// the number of tilings using exactly those tiles - the coefficients of the generating
+    /// polynomial with a variable for each orientation.  The counts are found a layer of
+    /// placements at a time, like `count_tilings`, but keyed on both the board and the
+    /// orientations of the tiles placed so far.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let tiles = TileCollection::from(Tile::domino());
+    /// let tiler = Tiler::new(tiles, RectangularBoard::new(4, 2));
+    ///
+    /// // 3 of the 5 domino tilings of a 4x2 board use 2 horizontal and 2 vertical dominoes
+    /// let counts = tiler.count_tilings_by_orientation();
+    /// assert_eq!(counts.len(), 3);
+    /// assert_eq!(counts[&vec![2, 2]], 3u32.into());
+    /// assert_eq!(counts[&vec![4, 0]], 1u32.into());
+    /// assert_eq!(counts[&vec![0, 4]], 1u32.into());
+    /// ```
+    pub fn count_tilings_by_orientation(&self) -> BTreeMap<Vec<usize>, BigUint> {
+        let orientations = self.tiles.cell_tiles().count();
+
+        self.sum_over_tilings(
+            BTreeMap::from([(vec![0; orientations], BigUint::one())]),
+            |profiles, index| match index {
+                Some(index) => profiles
+                    .iter()
+                    .map(|(profile, count)| {
+                        let mut profile = profile.clone();
+                        profile[index] += 1;
+                        (profile, count.clone())
+                    })
+                    .collect(),
+                None => profiles.clone(),
+            },
+            |totals, profiles| {
+                for (profile, count) in profiles {
+                    *totals.entry(profile).or_insert_with(BigUint::zero) += count;
+                }
+            },
+        )
+        .unwrap_or_default()
+    }
+
+    /// Returns the partition function of the initial board - the sum, over every tiling, of
+    /// the product of the weights of its tiles (see `TileCollection::with_weights`).  With
+    /// every weight equal to 1 this is the number of tilings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    /// use dcc_tiler::weight::Weight;
+    ///
+    /// // the monomer-dimer polynomial of a 2x2 board, with monomers of weight x
+    /// let tiles = TileCollection::with_weights(vec![
+    ///     (Tile::box_tile(), Weight::x()),
+    ///     (Tile::domino(), Weight::from(1u32)),
+    /// ]);
+    /// let tiler = Tiler::new(tiles, RectangularBoard::new(2, 2));
+    ///
+    /// assert_eq!(tiler.partition_function().to_string(), "x^4 + 4x^2 + 2");
+    /// ```
+    pub fn partition_function(&self) -> Weight {
+        self.sum_over_tilings(
+            Weight::one(),
+            |weight, index| match index {
+                Some(index) => weight * self.tiles.weight(index),
+                None => weight.clone(),
+            },
+            |total, weight| *total += weight,
+        )
+        .unwrap_or_else(Weight::zero)
+    }
+
+    /// Counts the tilings allowed by the target (see `with_target`) separately by the number
+    /// of cells they leave uncovered, so that the `k`th count is the number of tilings
+    /// leaving exactly `k` cells uncovered.  With `Target::CoverAtLeast(0)` and dominoes these
+    /// are the monomer-dimer counts, with the uncovered cells as monomers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::{Target, Tiler};
+    /// use num::BigUint;
+    ///
+    /// // the monomer-dimer counts of a 2x2 board, as given by `partition_function`
+    /// let tiles = TileCollection::from(Tile::domino());
+    /// let tiler = Tiler::new(tiles, RectangularBoard::new(2, 2)).with_target(Target::CoverAtLeast(0));
+    /// assert_eq!(tiler.count_tilings_by_uncovered(), [2u32, 0, 4, 0, 1].map(BigUint::from));
+    ///
+    /// // without a target, every cell is covered
+    /// let tiler = Tiler::new(TileCollection::from(Tile::domino()), RectangularBoard::new(2, 2));
+    /// assert_eq!(tiler.count_tilings_by_uncovered(), vec![2u32.into()]);
+    /// ```
+    pub fn count_tilings_by_uncovered(&self) -> Vec<BigUint> {
+        let mut counts = self
+            .sum_over_tilings(
+                vec![BigUint::one()],
+                |counts, index| match index {
+                    Some(_) => counts.clone(),
+                    None => std::iter::once(BigUint::zero())
+                        .chain(counts.iter().cloned())
+                        .collect(),
+                },
+                |totals: &mut Vec<BigUint>, counts| {
+                    if totals.len() < counts.len() {
+                        totals.resize(counts.len(), BigUint::zero());
+                    }
+                    for (total, count) in totals.iter_mut().zip(counts) {
+                        *total += count;
+                    }
+                },
+            )
+            .unwrap_or_default();
+
+        // a count for every number of cells the target allows to be left uncovered
+        let allowed = self.spare_cells(self.initial_board.grid()) + 1;
+        if counts.len() < allowed {
+            counts.resize(allowed, BigUint::zero());
+        }
+        counts
+    }
+
+    /// Sums a value over every tiling of the initial board, a layer of placements at a time.
+    ///
+    /// The initial board starts with the value `initial`, and `extend` gives the value after
+    /// placing the tile with the given index (in `cell_tiles`), or after leaving a cell
+    /// uncovered when given None.  Boards reached in more than
+    /// one way carry the sum of their values, combined with `add`, so that each board is only
+    /// expanded once.  Returns None if there are no tilings.
+    fn sum_over_tilings<T, E, A>(&self, initial: T, extend: E, add: A) -> Option<T>
+    where
+        T: Clone,
+        E: Fn(&T, Option<usize>) -> T,
+        A: Fn(&mut T, T),
+    {
+        let filter = self.filter();
+        let mut total: Option<T> = None;
+        let accumulate = |total: &mut Option<T>, value: T| match total {
+            Some(total) => add(total, value),
+            None => *total = Some(value),
+        };
+
+        if self.fault_free {
+            // fault lines depend on the whole tiling, so each tiling is checked separately
+            let mut stack = vec![(vec![self.initial_board.grid().clone()], initial)];
+
+            while let Some((boards, value)) = stack.pop() {
+                let board = boards.last().unwrap();
+
+                if self.is_finished(board) {
+                    if Tiling::new(boards).is_fault_free() {
+                        accumulate(&mut total, value);
+                    }
+                    continue;
+                }
+
+                for (index, child) in self.indexed_children(board, filter) {
+                    let mut child_boards = boards.clone();
+                    child_boards.push(child);
+
+                    let child_value = extend(&value, index);
+                    stack.push((child_boards, child_value));
+                }
+            }
+
+            return total;
+        }
+
+        let mut layer = HashMap::from([(self.initial_board.grid().clone(), initial)]);
+
+        while !layer.is_empty() {
+            let mut next: HashMap<RectangularBoard, T> = HashMap::new();
+
+            for (board, value) in layer {
+                if self.is_finished(&board) {
+                    accumulate(&mut total, value);
+                    continue;
+                }
+
+                for (index, child) in self.indexed_children(&board, filter) {
+                    let child_value = extend(&value, index);
+
+                    match next.entry(child) {
+                        Entry::Occupied(mut entry) => add(entry.get_mut(), child_value),
+                        Entry::Vacant(entry) => {
+                            entry.insert(child_value);
+                        }
+                    }
+                }
+            }
+
+            layer = next;
+        }
+
+        total
+    }
+
+    /// Returns the symmetries of the initial board which also map the tiles onto themselves,
+    /// and hence map tilings to tilings.
+    fn symmetry_group(&self) -> Vec<Symmetry> {
+        let tile_shapes: HashSet<CellTile> = self.tiles.cell_tiles().cloned().collect();
+
+        let board = self.initial_board.grid();
+
+        board
+            .symmetries()
+            .into_iter()
+            .filter(|symmetry| {
+                tile_shapes
+                    .iter()
+                    .all(|tile| tile_shapes.contains(&transform_tile(tile, *symmetry, board)))
+            })
+            .collect()
+    }
+
+    /// Counts the tilings accepted by `keep`, by enumerating every tiling of the initial board
+    fn count_matching_tilings<F: Fn(&Tiling) -> bool>(&self, keep: F) -> BigUint {
+        if self.uses_dlx() {
+            let mut problem = ExactCover::new(&self.initial_board, &self.tiles);
+            if let Some(token) = &self.cancel {
+                problem = problem.with_cancel_token(token.clone());
+            }
+
+            let mut count = BigUint::zero();
+            problem.for_each_tiling(|tiling| {
+                if keep(&tiling) {
+                    count += 1u32;
+                }
+                true
+            });
+
+            return count;
+        }
+
+        Tilings::new(self).filter(keep).count().into()
+    }
+
+    /// Returns a filter which keeps a single tiling out of each family of tilings which
+    /// are rotations or reflections of one another, if `with_symmetry_reduction` is set,
+    /// and only fault-free tilings, if `with_fault_free` is set.
+    fn tiling_filter(&self) -> impl Fn(&Tiling) -> bool {
+        let symmetries = if self.symmetry_reduction {
+            Some(self.symmetry_group())
+        } else {
+            None
+        };
+        let fault_free = self.fault_free;
+
+        move |tiling| {
+            symmetries
+                .as_ref()
+                .is_none_or(|symmetries| tiling.is_canonical(symmetries))
+                && (!fault_free || tiling.is_fault_free())
+        }
+    }
+
+    /// Determines whether the tiling made by the given sequence of boards is acceptable as
+    /// the result of a search for a single tiling
+    fn is_wanted(&self, boards: &[B]) -> bool {
+        !self.fault_free || Tiling::new(boards.to_vec()).is_fault_free()
+    }
+
+    /// Counts the ways of completing the given board to a tiling which is unchanged by the
+    /// given symmetry.  The board itself must be unchanged by the symmetry.
+    ///
+    /// Such a tiling is made up of orbits of tiles under the symmetry, so rather than placing
+    /// one tile at a time we place a tile together with all of its images.
+    fn count_fixed_completions(
+        &self,
+        board: &RectangularBoard,
+        symmetry: Symmetry,
+        memo: &mut HashMap<BoardKey, BigUint>,
+    ) -> BigUint {
+        if self.is_cancelled() {
+            return BigUint::zero();
+        }
+
+        let (i, j) = match board.most_constrained_cell() {
+            Some((cell, _)) => cell,
+            None => return BigUint::one(),
+        };
+
+        let key = board.key();
+        if let Some(count) = memo.get(&key) {
+            return count.clone();
+        }
+
+        // every placement of a tile covering the most constrained cell
+        let mut placements: Vec<BTreeSet<(usize, usize)>> = Vec::new();
+
+        for tile in self.tiles.cell_tiles() {
+            for anchor in 0..tile.len() {
+                if let Some(tp) = board.tile_fits_at_position(tile, Position::from((i, j)), anchor)
+                {
+                    let cells = tp
+                        .covered()
+                        .iter()
+                        .map(|p| (p.x() as usize, p.y() as usize))
+                        .collect();
+
+                    if !placements.contains(&cells) {
+                        placements.push(cells);
+                    }
+                }
+            }
+        }
+
+        let mut count = BigUint::zero();
+
+        for placement in placements {
+            // the images of this placement must either coincide or not overlap at all
+            let mut orbit = vec![placement];
+
+            loop {
+                let image: BTreeSet<(usize, usize)> = orbit
+                    .last()
+                    .unwrap()
+                    .iter()
+                    .map(|cell| symmetry.apply(*cell, board.width, board.height))
+                    .collect();
+
+                if image == orbit[0] {
+                    break;
+                }
+                orbit.push(image);
+            }
+
+            let cells: Vec<(usize, usize)> = orbit.iter().flatten().copied().collect();
+            let distinct: HashSet<&(usize, usize)> = cells.iter().collect();
+
+            if distinct.len() == cells.len() {
+                let child = board.mark_cells(&cells);
+                count += self.count_fixed_completions(&child, symmetry, memo);
+            }
+        }
+
+        memo.insert(key, count.clone());
+        count
+    }
+
+    /// Counts the ways of completing the given board to a tiling, by a depth first enumeration
+    fn count_completions_dfs(&self, board: &B, memory_limit: Option<usize>) -> BigUint {
+        struct Frame<B> {
+            board: B,
+            children: Option<Vec<B>>,
+            next: usize,
+        }
+
+        if self.is_finished(board.grid()) {
+            return BigUint::one();
+        }
+
+        let board_memory = board.grid().estimated_memory();
+        let mut stack_memory = board_memory;
+        let mut count = BigUint::zero();
+
+        let mut stack = vec![Frame {
+            board: board.clone(),
+            children: None,
+            next: 0,
+        }];
+
+        while let Some(frame) = stack.last_mut() {
+            if self.is_cancelled() {
+                break;
+            }
+
+            let child = match &frame.children {
+                Some(children) => children.get(frame.next).cloned(),
+                None => {
+                    let children = self.children(&frame.board);
+                    let children_memory = children.len() * board_memory;
+
+                    if frame.next == 0
+                        && memory_limit.is_none_or(|limit| stack_memory + children_memory <= limit)
+                    {
+                        // we have room to keep this level's children around
+                        stack_memory += children_memory;
+                        let child = children.first().cloned();
+                        frame.children = Some(children);
+                        child
+                    } else {
+                        children.into_iter().nth(frame.next)
+                    }
+                }
+            };
+            frame.next += 1;
+
+            match child {
+                Some(child) if self.is_finished(child.grid()) => count += 1u32,
+                Some(child) => {
+                    stack_memory += board_memory;
+                    stack.push(Frame {
+                        board: child,
+                        children: None,
+                        next: 0,
+                    });
+                }
+                None => {
+                    // every child of this board has been explored
+                    let frame = stack.pop().unwrap();
+                    stack_memory -= board_memory;
+                    if let Some(children) = frame.children {
+                        stack_memory -= children.len() * board_memory;
+                    }
+                }
+            }
+        }
+
+        count
+    }
+
+    fn count_tilings_from_graph(&self) -> BigUint {
+        let graph = Arc::clone(self.graph.as_ref().unwrap());
+        let g = graph.read().unwrap();
+
+        // a board can be reached after different numbers of placements, so the counts are
+        // found in topological order rather than layer by layer
+        g.count_complete_paths(&g.count_paths())
+    }
+
+    #[allow(dead_code, clippy::map_entry)]
+    fn generate_graph(&mut self) {
+        let mut graph = BoardGraph::new();
+        graph.add_node(self.initial_board.clone());
+
+        let mut stack = vec![0];
+        let mut depth = 0;
+        let board_memory = self.initial_board.grid().estimated_memory();
+        self.reset_stats();
+        self.report_start("graph generation");
+
+        while !stack.is_empty() {
+            let boards: Vec<(usize, B)> = stack
+                .into_iter()
+                .map(|board_index| (board_index, graph.get_node(board_index).unwrap().clone()))
+                .collect();
+
+            // Each thread collects the children it finds into its own shard, recording the
+            // parents of each child along with the (parent, child) position at which it was
+            // first found.  Merging the shards and sorting by first position then numbers the
+            // new nodes in the same order as a sequential search would.
+            let children = self.install(|| {
+                boards
+                    .par_iter()
+                    .enumerate()
+                    .fold(
+                        HashMap::new,
+                        |mut shard: GraphShard<B>, (position, (index, board))| {
+                            if self.is_cancelled() {
+                                return shard;
+                            }
+
+                            for (ordinal, child) in self.children(board).into_iter().enumerate() {
+                                let (first, parents) = shard
+                                    .entry(child)
+                                    .or_insert_with(|| ((position, ordinal), Vec::new()));
+                                *first = (*first).min((position, ordinal));
+                                parents.push(*index);
+                            }
+                            shard
+                        },
+                    )
+                    .reduce(HashMap::new, |left, right| {
+                        if left.len() < right.len() {
+                            return merge_graph_shards(right, left);
+                        }
+                        merge_graph_shards(left, right)
+                    })
+            });
+
+            // the graph is only kept once every layer has been generated
+            if self.is_cancelled() {
+                self.report_finish();
+                return;
+            }
+
+            let mut children: Vec<_> = children.into_iter().collect();
+            children.sort_unstable_by_key(|(_, (first, _))| *first);
+
+            let states = children.len();
+            let mut next_iteration = Vec::new();
+            for (board, (_, parents)) in children {
+                // boards can be reached after different numbers of placements (when the
+                // tiles are different sizes), so they may already be in the graph
+                if let Some(child_index) = graph.find_node(&board) {
+                    for parent in parents {
+                        graph.add_edge(parent, child_index);
+                    }
+                    continue;
+                }
+
+                let complete = self.is_finished(board.grid());
+                let child_index = graph.add_node(board);
+
+                for parent in parents {
+                    graph.add_edge(parent, child_index);
+                }
+
+                if complete {
+                    // mark this as a finished node in our graph
+                    graph.mark_node_as_complete(child_index);
+                } else {
+                    next_iteration.push(child_index);
+                }
+            }
+
+            stack = next_iteration;
+
+            depth += 1;
+            self.record_level(stack.len(), states, graph.len() * board_memory);
+            if let Some(progress) = &self.progress {
+                progress.on_graph_level(depth, stack.len(), graph.len());
+            }
+        }
+        self.report_finish();
+        self.graph = Some(Arc::new(RwLock::new(graph)));
+    }
+
+    /// Runs `op` on the thread pool set by `with_threads`, or on the global thread pool
+    #[cfg(feature = "parallel")]
+    fn install<OP, R>(&self, op: OP) -> R
+    where
+        OP: FnOnce() -> R + Send,
+        R: Send,
+    {
+        match self.threads {
+            Some(threads) => rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .expect("Unable to build thread pool")
+                .install(op),
+            None => op(),
+        }
+    }
+
+    /// Runs `op` on the current thread, as there is no thread pool without `parallel`
+    #[cfg(not(feature = "parallel"))]
+    fn install<OP: FnOnce() -> R, R>(&self, op: OP) -> R {
+        op()
+    }
+
+    /// Like `graph`, but stops generating the graph (returning `TilerError::Cancelled`) once
+    /// the given token is cancelled, or (returning `TilerError::TimedOut`) once the time
+    /// limit given to `with_time_limit` passes
+    pub fn graph_cancellable(
+        &mut self,
+        token: CancelToken,
+    ) -> std::result::Result<Arc<RwLock<BoardGraph<B>>>, TilerError> {
+        if self.graph.is_none() {
+            self.cancel = Some(token);
+            self.generate_graph_limited();
+            self.cancel = None;
+        }
+
+        match (&self.graph, self.time_limit_error(&BigUint::zero())) {
+            (Some(graph), _) => Ok(Arc::clone(graph)),
+            (None, Some(e)) => Err(e),
+            (None, None) => Err(TilerError::Cancelled),
+        }
+    }
+
+    /// Returns the tiling graph, generating it if need be.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the time limit given to `with_time_limit` passes before the graph has been
+    /// generated - use `graph_cancellable` to handle this.
+    pub fn graph(&mut self) -> Arc<RwLock<BoardGraph<B>>> {
+        // If the graph doesn't exist already, generate it
+        if self.graph.is_none() {
+            self.generate_graph_limited();
+        }
+
+        // Now return a reference to the graph
+        Arc::clone(
+            self.graph
+                .as_ref()
+                .expect("The time limit passed before the tiling graph was generated"),
+        )
+    }
+
+    /// Generates the tiling graph, stopping once the time limit passes
+    fn generate_graph_limited(&mut self) {
+        let previous = self.start_clock();
+        self.generate_graph();
+        self.stop_clock(previous);
+    }
+
+    /// Renders every tiling into a ZIP file, with one image per tiling.
+    ///
+    /// Tilings are the paths through the tiling graph to each complete board (see
+    /// `BoardGraph::enumerate_paths`), and are rendered concurrently in batches, while a
+    /// single thread writes the images to the ZIP file in the order they are rendered.
+    #[cfg(feature = "fs")]
+    pub fn render_all_tilings(&mut self, output_filename: &str) -> Result<()> {
+        if self.uses_dlx() {
+            return self.render_all_tilings_dlx(output_filename);
+        }
+
+        let graph = self.graph();
+        let graph = graph.read().expect("Unable to read graph");
+
+        let keep = self.tiling_filter();
+
+        self.write_zip(output_filename, |emit| {
+            let mut batch = Vec::new();
+
+            for complete in graph.get_complete_indices() {
+                for path in graph.enumerate_paths(0, complete) {
+                    let tiling = Tiling::new(
+                        path.into_iter()
+                            .map(|index| graph.get_node(index).unwrap().clone())
+                            .collect(),
+                    );
+
+                    if keep(&tiling) {
+                        batch.push(tiling);
+                    }
+                    if batch.len() == RENDER_BATCH_SIZE && !emit_batch(&mut batch, emit) {
+                        return;
+                    }
+                }
+            }
+
+            emit_batch(&mut batch, emit);
+        })
+    }
+
+    #[cfg(feature = "fs")]
+    fn render_all_tilings_dlx(&self, output_filename: &str) -> Result<()> {
+        let keep = self.tiling_filter();
+
+        self.write_zip(output_filename, |emit| {
+            let mut batch = Vec::new();
+            let mut writing = true;
+
+            ExactCover::new(&self.initial_board, &self.tiles).for_each_tiling(|tiling| {
+                if keep(&tiling) {
+                    batch.push(tiling);
+                }
+                if batch.len() == RENDER_BATCH_SIZE {
+                    writing = emit_batch(&mut batch, emit);
+                }
+
+                // stop enumerating if we couldn't write to the ZIP file
+                writing
+            });
+
+            if writing {
+                emit_batch(&mut batch, emit);
+            }
+        })
+    }
+
+    /// Renders the first `limit` tilings found by `tilings` into a ZIP file, without
+    /// generating the tiling graph.
+    #[cfg(feature = "fs")]
+    pub fn render_first_tilings(&self, output_filename: &str, limit: usize) -> Result<()> {
+        self.write_zip(output_filename, |emit| {
+            let mut batch = Vec::new();
+
+            for tiling in self.tilings().take(limit) {
+                batch.push(tiling);
+                if batch.len() == RENDER_BATCH_SIZE && !emit_batch(&mut batch, emit) {
+                    return;
+                }
+            }
+
+            emit_batch(&mut batch, emit);
+        })
+    }
+
+    /// Renders some of the tilings into a ZIP file, chosen by the given strategy, and
+    /// rendering at most `max_count` tilings.  Tilings excluded by `with_symmetry_reduction`
+    /// or `with_fault_free` are skipped after being chosen, so fewer tilings may be rendered.
+    ///
+    /// The number of tilings below each node of the tiling graph is used to find the tilings
+    /// directly from their numbers, so only the chosen tilings are ever constructed.
+    #[cfg(feature = "fs")]
+    pub fn render_all_tilings_sampled(
+        &mut self,
+        output_filename: &str,
+        max_count: Option<usize>,
+        strategy: SampleStrategy,
+    ) -> Result<()> {
+        if strategy == SampleStrategy::EveryKth(0) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "cannot render every 0th tiling",
+            ));
+        }
+
+        let keep = self.tiling_filter();
+        let graph = self.graph();
+        let graph = graph.read().expect("Unable to read graph");
+
+        self.write_zip(output_filename, |emit| {
+            let counts = graph.count_paths();
+            let mut ranks = self
+                .sample_ranks(&graph.count_complete_paths(&counts), strategy)
+                .take(max_count.unwrap_or(usize::MAX));
+
+            loop {
+                let batch: Vec<BigUint> = ranks.by_ref().take(RENDER_BATCH_SIZE).collect();
+                if batch.is_empty() {
+                    break;
+                }
+
+                let writing = batch.into_par_iter().all(|rank| {
+                    let path = graph.nth_complete_path(&counts, rank).unwrap();
+                    let tiling = Tiling::new(
+                        path.into_iter()
+                            .map(|index| graph.get_node(index).unwrap().clone())
+                            .collect(),
+                    );
+
+                    !keep(&tiling) || emit(tiling)
+                });
+
+                if !writing {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Returns the numbers of the tilings chosen by the given strategy, in increasing order,
+    /// where `total` is the number of tilings
+    #[cfg(feature = "fs")]
+    fn sample_ranks(
+        &self,
+        total: &BigUint,
+        strategy: SampleStrategy,
+    ) -> Box<dyn Iterator<Item = BigUint> + Send> {
+        let every = |step: usize| {
+            let total = total.clone();
+
+            std::iter::successors(Some(BigUint::zero()), move |rank| Some(rank + step))
+                .take_while(move |rank| *rank < total)
+        };
+
+        match strategy {
+            SampleStrategy::First(n) => Box::new(every(1).take(n)),
+            SampleStrategy::EveryKth(k) => Box::new(every(k)),
+            SampleStrategy::RandomUniform(n) if BigUint::from(n) >= *total => Box::new(every(1)),
+            SampleStrategy::RandomUniform(n) => {
+                let mut rng = self.rng();
+                let mut ranks = HashSet::new();
+
+                while ranks.len() < n {
+                    ranks.insert(rng.gen_biguint_below(total));
+                }
+
+                let mut ranks: Vec<BigUint> = ranks.into_iter().collect();
+                ranks.sort_unstable();
+
+                Box::new(ranks.into_iter())
+            }
+        }
+    }
+
+    /// Creates a ZIP file and calls `tilings` with a function which renders a tiling and adds
+    /// it to the file, returning false once the file can no longer be written to.  The
+    /// function may be called from several threads at once, while the ZIP file is written on
+    /// a thread of its own, numbering the images in the order they arrive.
+    #[cfg(feature = "fs")]
+    fn write_zip<F>(&self, output_filename: &str, tilings: F) -> Result<()>
+    where
+        F: FnOnce(&(dyn Fn(Tiling) -> bool + Sync)) + Send,
+    {
+        let file = File::create(output_filename)?;
+        let extension = self.image_format.extension();
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<u8>>(ZIP_QUEUE_SIZE);
+
+        std::thread::scope(|scope| {
+            let writer = scope.spawn(move || -> Result<()> {
+                let mut zip = zip::ZipWriter::new(file);
+
+                for (tiling_counter, image) in receiver.into_iter().enumerate() {
+                    zip.start_file(
+                        format!("{}.{}", tiling_counter, extension),
+                        Default::default(),
+                    )?;
+                    zip.write_all(&image)?;
+                }
+
+                let _ = zip.finish()?;
+
+                Ok(())
+            });
+
+            {
+                let emit = |tiling: Tiling| {
+                    let image = self
+                        .image_format
+                        .render(tiling.boards().iter().collect(), &self.render_options);
+
+                    // the writer only hangs up early if it failed to write to the ZIP file
+                    sender.send(image).is_ok()
+                };
+
+                self.install(|| tilings(&emit));
+            }
+
+            drop(sender);
+            writer.join().expect("ZIP writer thread panicked")
+        })
+    }
+
+    /// Returns an iterator over every tiling of the initial board.
+    ///
+    /// Tilings are found lazily by a depth first search, so only the boards along the
+    /// current branch of the search are held in memory, and iteration can be stopped at
+    /// any point.  Each tiling is yielded exactly once, unless it is excluded by
+    /// `with_symmetry_reduction` or `with_fault_free`.
+    pub fn tilings(&self) -> impl Iterator<Item = Tiling> + '_ {
+        Tilings::new(self).filter(self.tiling_filter())
+    }
+
+    /// Determines whether the initial board can be tiled, stopping as soon as a
+    /// single tiling is found.
+    ///
+    /// This does none of the bookkeeping required for counting; the only state kept
+    /// is the set of boards already visited, so that dead ends reached by different
+    /// placement orders are only explored once.  With the `Ilp` backend the question is
+    /// instead answered by solving an integer linear program, which can often show that a
+    /// board can't be tiled far faster than searching through every placement.  With
+    /// `with_fault_free` the tilings are enumerated until a fault-free one is found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let tiles = TileCollection::from(Tile::domino());
+    ///
+    /// let tiler = Tiler::new(tiles.clone(), RectangularBoard::new(4, 4));
+    /// assert!(tiler.exists_tiling());
+    ///
+    /// // every domino tiling of a 4x4 board has a fault line
+    /// let tiler = Tiler::new(tiles.clone(), RectangularBoard::new(4, 4)).with_fault_free();
+    /// assert!(!tiler.exists_tiling());
+    ///
+    /// let tiler = Tiler::new(tiles, RectangularBoard::new(6, 5)).with_fault_free();
+    /// assert!(tiler.exists_tiling());
+    /// ```
+    pub fn exists_tiling(&self) -> bool {
+        // whether a tiling is fault-free can't be told until it's complete
+        if self.fault_free {
+            return Tilings::new(self).any(|tiling| tiling.is_fault_free());
+        }
+
+        if self.is_finished(self.initial_board.grid()) {
+            return true;
+        }
+
+        #[cfg(feature = "ilp")]
+        if self.uses_ilp() {
+            return ExactCover::new(&self.initial_board, &self.tiles)
+                .find_tiling()
+                .is_some();
+        }
+
+        let mut stack = vec![self.initial_board.clone()];
+        let mut visited = HashSet::new();
+
+        while let Some(board) = stack.pop() {
+            if self.is_cancelled() {
+                return false;
+            }
+
+            for child_board in self.children(&board) {
+                if self.is_finished(child_board.grid()) {
+                    return true;
+                }
+
+                if !visited.contains(&child_board) {
+                    visited.insert(child_board.clone());
+                    stack.push(child_board);
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Like `exists_tiling`, but gives up once the time limit given to `with_time_limit`
+    /// passes without finding a tiling, returning `TilerError::TimedOut`.  The `Ilp` backend
+    /// isn't stopped by the time limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::error::TilerError;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    /// use std::time::Duration;
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(2));
+    ///
+    /// let mut tiler = Tiler::new(tiles.clone(), RectangularBoard::new(3, 2))
+    ///     .with_time_limit(Duration::from_secs(600));
+    /// assert_eq!(tiler.try_exists_tiling(), Ok(true));
+    ///
+    /// let mut tiler = Tiler::new(tiles, RectangularBoard::new(30, 30))
+    ///     .with_time_limit(Duration::ZERO);
+    /// assert!(matches!(
+    ///     tiler.try_exists_tiling(),
+    ///     Err(TilerError::TimedOut { .. })
+    /// ));
+    /// ```
+    pub fn try_exists_tiling(&mut self) -> std::result::Result<bool, TilerError> {
+        let previous = self.start_clock();
+        let exists = self.exists_tiling();
+        self.stop_clock(previous);
+
+        match self.time_limit_error(&BigUint::zero()) {
+            Some(e) if !exists => Err(e),
+            _ => Ok(exists),
+        }
+    }
+
+    /// Searches for a colouring of the initial board proving that it can't be tiled, such
+    /// as the chessboard colouring showing that a chessboard with two opposite corners removed
+    /// can't be tiled by dominoes.  See `analysis::find_coloring` for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let board = RectangularBoard::new(5, 5);
+    /// let tiler = Tiler::new(TileCollection::from(Tile::l_tile(2)), board.clone());
+    ///
+    /// let coloring = tiler.prove_impossible().unwrap();
+    /// assert!(coloring.verify(&board, &TileCollection::from(Tile::l_tile(2))));
+    /// ```
+    pub fn prove_impossible(&self) -> Option<Coloring> {
+        analysis::find_coloring(self.initial_board.grid(), &self.tiles)
+    }
+
+    /// Returns a tiling chosen uniformly at random from all tilings of the initial board, or
+    /// None if the board can't be tiled.
+    ///
+    /// Working backwards from the complete board in the tiling graph, each tile placement is
+    /// chosen with probability proportional to the number of tilings it is part of, so (unlike
+    /// `get_single_tiling`, which favours the tilings it finds first) every tiling is equally
+    /// likely.  With `with_symmetry_reduction` tilings are drawn until a canonical one is
+    /// found, so that each family of equivalent tilings is equally likely.  With
+    /// `with_fault_free` there may be no acceptable tilings at all, so every tiling is
+    /// enumerated and one of the fault-free tilings is chosen as they go past.
+    pub fn sample_uniform_tiling(&mut self) -> Option<Tiling> {
+        if self.fault_free {
+            let mut rng = self.rng();
+            let mut chosen = None;
+
+            // reservoir sampling - the nth acceptable tiling replaces the chosen one with
+            // probability 1/n
+            for (n, tiling) in self.tilings().enumerate() {
+                if rng.gen_range(0..=n) == 0 {
+                    chosen = Some(tiling);
+                }
+            }
+
+            return chosen;
+        }
+
+        if !self.symmetry_reduction {
+            if let Some(tilers) = self.component_tilers() {
+                // the components are tiled independently, so a uniform tiling of each of them
+                // makes a uniform tiling of the whole board
+                return self
+                    .combine_component_tilings(tilers, |tiler| tiler.sample_uniform_tiling());
+            }
+        }
+
+        let keep = self.tiling_filter();
+        let graph = self.graph();
+        let graph = graph.read().expect("Unable to read graph");
+
+        let counts = graph.count_paths();
+        let total = graph.count_complete_paths(&counts);
+        if total.is_zero() {
+            return None;
+        }
+
+        loop {
+            // choosing a tiling by its number is the same as choosing each placement with
+            // probability proportional to the number of tilings through it
+            let rank = self.rng().gen_biguint_below(&total);
+            let path = graph.nth_complete_path(&counts, rank).unwrap();
+            let tiling = Tiling::new(
+                path.into_iter()
+                    .map(|index| graph.get_node(index).unwrap().clone())
+                    .collect(),
+            );
+
+            if keep(&tiling) {
+                return Some(tiling);
+            }
+        }
+    }
+
+    /// Searches for a single tiling using several randomized depth first searches
+    /// running in parallel, returning the first tiling found by any of them.
+    ///
+    /// Each worker explores placements in a random order, and restarts with a fresh
+    /// order (and twice the budget) whenever it explores too many boards without
+    /// finding a tiling, so that a poor early choice doesn't leave it stuck.  As the workers
+    /// race each other, the tiling found can differ between runs, even with a seeded rng
+    /// (see `with_rng`).
+    pub fn get_single_tiling_parallel(&self, workers: usize) -> Option<Tiling> {
+        if let Some(tilers) = self.component_tilers() {
+            return self.combine_component_tilings(tilers, |tiler| {
+                tiler.get_single_tiling_parallel(workers)
+            });
+        }
+
+        let finished = AtomicBool::new(false);
+
+        (0..workers.max(1)).into_par_iter().find_map_any(|_| {
+            let mut rng = StdRng::seed_from_u64(self.rng().gen());
+            let mut budget = RESTART_BUDGET;
+
+            loop {
+                match self.randomized_search(&mut rng, budget, &finished) {
+                    SearchResult::Found(tiling) => {
+                        finished.store(true, Ordering::Relaxed);
+                        return Some(Tiling::new(tiling));
+                    }
+                    SearchResult::Exhausted => {
+                        // the whole search space was explored, so no other worker
+                        // is going to find a tiling either
+                        finished.store(true, Ordering::Relaxed);
+                        return None;
+                    }
+                    SearchResult::OutOfBudget => budget *= 2,
+                    SearchResult::Cancelled => return None,
+                }
+            }
+        })
+    }
+
+    fn randomized_search<R: Rng>(
+        &self,
+        rng: &mut R,
+        budget: usize,
+        finished: &AtomicBool,
+    ) -> SearchResult<B> {
+        if self.is_finished(self.initial_board.grid()) {
+            return SearchResult::Found(vec![self.initial_board.clone()]);
+        }
+
+        let mut stack = vec![vec![self.initial_board.clone()]];
+        let mut explored = 0;
+
+        while let Some(tvec) = stack.pop() {
+            if finished.load(Ordering::Relaxed) {
+                return SearchResult::Cancelled;
+            }
+
+            explored += 1;
+            if explored > budget {
+                return SearchResult::OutOfBudget;
+            }
+
+            let fitting_tiles = self.children_with_rng(tvec.last().unwrap(), rng);
+
+            for board in fitting_tiles {
+                let complete = self.is_finished(board.grid());
+
+                let mut new_tvec = tvec.clone();
+                new_tvec.push(board);
+
+                if complete {
+                    if self.is_wanted(&new_tvec) {
+                        return SearchResult::Found(new_tvec);
+                    }
+                    continue;
+                }
+                stack.push(new_tvec);
+            }
+        }
+
+        SearchResult::Exhausted
+    }
+
+    /// Searches for a tiling using a best-first search, guided by how constrained each
+    /// board is and by a one-step lookahead.
+    ///
+    /// Boards are explored in order of (number of marked cells, constraint count of the
+    /// most constrained cell, number of viable placements), where a placement is viable if
+    /// it leaves every empty region with an area that some combination of tiles could fill
+    /// (see `Board::prune_check`).
+    /// Boards with no viable placements are discarded without being explored.
+    pub fn get_single_tiling_best_first(&self, budget: SearchBudget) -> Option<Tiling> {
+        if let Some(tilers) = self.component_tilers() {
+            return self.combine_component_tilings(tilers, |tiler| {
+                tiler.get_single_tiling_best_first(budget)
+            });
+        }
+
+        // the clock is only read with a time limit, as there isn't one in the browser
+        let deadline = budget.time.map(|time| Instant::now() + time);
+
+        // a board is worth keeping only if all of its empty regions could be filled
+        let viable =
+            |board: &B| self.spare_cells(board.grid()) > 0 || board.prune_check(&self.tiles);
+
+        let mut queue = BinaryHeap::new();
+        let mut completed_tilings = Vec::new();
+        let mut explored = 0;
+
+        if self.is_finished(self.initial_board.grid()) {
+            return Some(Tiling::new(vec![self.initial_board.clone()]));
+        }
+        queue.push(BestFirstEntry::new(vec![self.initial_board.clone()], 0));
+
+        while let Some(entry) = queue.pop() {
+            explored += 1;
+
+            if budget.nodes.is_some_and(|nodes| explored > nodes)
+                || deadline.is_some_and(|deadline| Instant::now() > deadline)
+            {
+                break;
+            }
+
+            let current_board = entry.boards.last().unwrap();
+
+            for board in self.children(current_board) {
+                let mut new_boards = entry.boards.clone();
+
+                if self.is_finished(board.grid()) {
+                    new_boards.push(board);
+                    if self.is_wanted(&new_boards) {
+                        completed_tilings.push(new_boards);
+                    }
+                    continue;
+                }
+
+                if !viable(&board) {
+                    continue;
+                }
+
+                // one step lookahead - count the placements from this board which stay viable
+                let lookahead = self
+                    .children(&board)
+                    .iter()
+                    .filter(|child| self.is_finished(child.grid()) || viable(child))
+                    .count();
+
+                if lookahead > 0 {
+                    new_boards.push(board);
+                    queue.push(BestFirstEntry::new(new_boards, lookahead));
+                }
+            }
+
+            if completed_tilings.len() >= budget.solutions {
+                break;
+            }
+        }
+
+        completed_tilings
+            .choose(&mut *self.rng())
+            .cloned()
+            .map(Tiling::new)
+    }
+
+    /// Searches for a tiling of the initial board, doing at most the amount of work
+    /// permitted by the given budget.
+    ///
+    /// If more than one tiling is found before the search stops, one of them is chosen
+    /// at random.  The order in which placements are tried is set by
+    /// `with_search_strategy`, except with the dancing links backend.  The `Ilp` backend
+    /// ignores the budget and always returns the one tiling found by its solver.
+    pub fn get_single_tiling(&mut self, budget: SearchBudget) -> Option<Tiling> {
+        if let Some(tilers) = self.component_tilers() {
+            return self.combine_component_tilings(tilers, |tiler| tiler.get_single_tiling(budget));
+        }
+
+        // the clock is only read with a time limit, as there isn't one in the browser
+        let deadline = budget.time.map(|time| Instant::now() + time);
+
+        // the integer linear program can't rule out tilings with fault lines
+        #[cfg(feature = "ilp")]
+        if self.uses_ilp() && !self.fault_free {
+            return ExactCover::new(&self.initial_board, &self.tiles).find_tiling();
+        }
+
+        if self.uses_dlx() {
+            let mut problem = ExactCover::new(&self.initial_board, &self.tiles);
+            if let Some(deadline) = deadline {
+                problem = problem.with_cancel_token(CancelToken::new().with_deadline(deadline));
+            }
+
+            let mut completed_tilings = Vec::new();
+            problem.for_each_tiling(|tiling| {
+                if !self.fault_free || tiling.is_fault_free() {
+                    completed_tilings.push(tiling);
+                }
+
+                completed_tilings.len() < budget.solutions
+                    && deadline.is_none_or(|deadline| Instant::now() <= deadline)
+            });
+
+            return completed_tilings.choose(&mut *self.rng()).cloned();
+        }
+
+        let mut tie_break_rng = self.tie_break_seed.map(StdRng::seed_from_u64);
+        let mut explored = 0;
+        let mut completed_tilings = Vec::new();
+
+        // iterative deepening repeats the search, trying more placements each time
+        let mut breadth = match self.search_strategy {
+            SearchStrategy::Iddfs => 1,
+            _ => usize::MAX,
+        };
+
+        loop {
+            // each search covers everything the previous one did
+            completed_tilings.clear();
+
+            let mut stack = vec![vec![self.initial_board.clone()]];
+            let mut truncated = false;
+            let mut out_of_budget = false;
+
+            while let Some(tvec) = stack.pop() {
+                explored += 1;
+
+                if budget.nodes.is_some_and(|nodes| explored > nodes)
+                    || deadline.is_some_and(|deadline| Instant::now() > deadline)
+                {
+                    out_of_budget = true;
+                    break;
+                }
+
+                let current_board = tvec.last().unwrap();
+                let mut fitting_tiles = match &mut tie_break_rng {
+                    Some(rng) => self.children_with_rng(current_board, rng),
+                    None => self.children(current_board),
+                };
+
+                // the stack explores the last placement first, so we order the placements
+                // from last to first
+                if self.search_strategy == SearchStrategy::LeastFreedom {
+                    fitting_tiles.sort_by_cached_key(|board| {
+                        let grid = board.grid();
+                        let constraint = grid.most_constrained_cell().map(|(_, count)| count);
+
+                        (Reverse(grid.freedom()), constraint)
+                    });
+                }
+                if fitting_tiles.len() > breadth {
+                    fitting_tiles.drain(..fitting_tiles.len() - breadth);
+                    truncated = true;
+                }
+
+                for board in fitting_tiles {
+                    let complete = self.is_finished(board.grid());
+
+                    let mut new_tvec = tvec.clone();
+                    new_tvec.push(board);
+
+                    if complete {
+                        if self.is_wanted(&new_tvec) {
+                            completed_tilings.push(new_tvec);
+                        }
+                    } else {
+                        stack.push(new_tvec);
+                    }
+                }
+
+                if completed_tilings.len() >= budget.solutions {
+                    break;
+                }
+            }
+
+            if out_of_budget || !truncated || completed_tilings.len() >= budget.solutions {
+                break;
+            }
+            breadth *= 2;
+        }
+
+        completed_tilings
+            .choose(&mut *self.rng())
+            .cloned()
+            .map(Tiling::new)
+    }
+
+    /// Finds a placement of tiles on the initial board leaving as few cells uncovered as
+    /// possible, returning it together with the number of cells it leaves uncovered.
+    ///
+    /// The search chooses a cell (see `with_cell_selection`) and either covers it with a tile
+    /// or leaves it uncovered, abandoning any board which can't improve on the best placement
+    /// found so far (see `RectangularBoard::uncovered_lower_bound`), and stopping early once
+    /// no better placement is possible.  The placement filter and counted tiles are
+    /// respected, but the target is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiler::Tiler;
+    ///
+    /// let tiler = Tiler::new(TileCollection::from(Tile::domino()), RectangularBoard::new(3, 3));
+    /// let (tiling, uncovered) = tiler.max_packing();
+    /// assert_eq!(uncovered, 1);
+    /// assert_eq!(tiling.placements().len(), 4);
+    ///
+    /// // a board which can be tiled is covered completely
+    /// let tiler = Tiler::new(TileCollection::from(Tile::l_tile(2)), RectangularBoard::new(3, 2));
+    /// assert_eq!(tiler.max_packing().1, 0);
+    /// ```
+    pub fn max_packing(&self) -> (Tiling, usize) {
+        let initial = self.initial_board.grid();
+        if initial.is_all_marked() {
+            return (Tiling::new(vec![self.initial_board.clone()]), 0);
+        }
+        let base = initial.uncovered_count();
+
+        // leaving the cell uncovered comes first, so that it's tried after every placement
+        let children = |board: &B| -> Vec<B> {
+            self.explored.fetch_add(1, Ordering::Relaxed);
+
+            let grid = board.grid();
+            let cell =
+                match grid.select_cell_filtered(self.cell_selection, &self.tiles, self.filter()) {
+                    Some(cell) => cell,
+                    None => return Vec::new(),
+                };
+
+            let mut children = vec![board.with_grid(grid.leave_uncovered(cell))];
+            children.extend(
+                grid.place_tile_at(cell, &self.tiles, self.filter())
+                    .into_iter()
+                    .map(|(_, child)| board.with_grid(child)),
+            );
+            children
+        };
+
+        let floor = initial.uncovered_lower_bound(&self.tiles);
+        let mut best = initial.unmarked_count() + 1;
+        let mut best_path = Vec::new();
+
+        // the fewest cells left uncovered on the way to each board reached so far
+        let mut reached: HashMap<BoardKey, usize> = HashMap::new();
+
+        let mut path = vec![self.initial_board.clone()];
+        let mut stack = vec![children(&self.initial_board)];
+
+        while let Some(boards) = stack.last_mut() {
+            let board = match boards.pop() {
+                Some(board) => board,
+                None => {
+                    stack.pop();
+                    path.pop();
+                    continue;
+                }
+            };
+
+            let grid = board.grid();
+            let uncovered = grid.uncovered_count() - base;
+            if uncovered + grid.uncovered_lower_bound(&self.tiles) >= best {
+                continue;
+            }
+
+            match reached.entry(grid.marked_key()) {
+                Entry::Occupied(entry) if *entry.get() <= uncovered => continue,
+                Entry::Occupied(mut entry) => {
+                    entry.insert(uncovered);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(uncovered);
+                }
+            }
+
+            if grid.is_all_marked() {
+                best = uncovered;
+                best_path = path.clone();
+                best_path.push(board);
+
+                if best <= floor {
+                    break;
+                }
+                continue;
+            }
+
+            stack.push(children(&board));
+            path.push(board);
+        }
+
+        (Tiling::new(best_path), best)
+    }
+}
+
+/// The depth first search behind `Tiler::tilings`
+struct Tilings<'a, B: Board> {
+    tiler: &'a Tiler<B>,
+
+    /// The boards along the current branch of the search, starting from the initial board
+    path: Vec<B>,
+
+    /// The children of each board in `path` which are yet to be explored
+    stack: Vec<Vec<B>>,
+
+    /// Set if the initial board is already completely marked, and hasn't been yielded yet
+    trivial: bool,
+}
+
+impl<'a, B: Board> Tilings<'a, B> {
+    fn new(tiler: &'a Tiler<B>) -> Self {
+        let initial_board = &tiler.initial_board;
+        let trivial = tiler.is_finished(initial_board.grid());
+        let stack = if trivial {
+            Vec::new()
+        } else {
+            vec![tiler.children(initial_board)]
+        };
+
+        Tilings {
+            tiler,
+            path: vec![initial_board.clone()],
+            stack,
+            trivial,
+        }
+    }
+}
+
+impl<B: Board> Iterator for Tilings<'_, B> {
+    type Item = Tiling;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.trivial {
+            self.trivial = false;
+            return Some(Tiling::new(self.path.clone()));
+        }
+
+        while let Some(children) = self.stack.last_mut() {
+            if self.tiler.is_cancelled() {
+                self.stack.clear();
+                return None;
+            }
+
+            match children.pop() {
+                Some(child) => {
+                    self.path.truncate(self.stack.len());
+                    self.path.push(child);
+
+                    let child = self.path.last().unwrap();
+                    if self.tiler.is_finished(child.grid()) {
+                        return Some(Tiling::new(self.path.clone()));
+                    }
+
+                    let grandchildren = self.tiler.children(child);
+                    self.stack.push(grandchildren);
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// A partial tiling waiting to be explored by the best-first search
+struct BestFirstEntry<B> {
+    priority: (usize, usize, usize),
+    boards: Vec<B>,
+}
+
+impl<B: Board> BestFirstEntry<B> {
+    fn new(boards: Vec<B>, lookahead: usize) -> Self {
+        let board = boards.last().unwrap().grid();
+
+        let marked = board.board.iter().flatten().filter(|b| **b).count();
+        let constraint = board.most_constrained_cell().map_or(0, |(_, count)| count);
+
+        BestFirstEntry {
+            priority: (marked, constraint, lookahead),
+            boards,
+        }
+    }
+}
+
+impl<B> PartialEq for BestFirstEntry<B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<B> Eq for BestFirstEntry<B> {}
+
+impl<B> PartialOrd for BestFirstEntry<B> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<B> Ord for BestFirstEntry<B> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.priority.cmp(&other.priority)
+    }
+}
+
+/// Limits on the amount of work done by `Tiler::get_single_tiling`
+#[derive(Debug, Copy, Clone)]
+pub struct SearchBudget {
+    /// Stop searching once this many complete tilings have been found
+    pub solutions: usize,
+
+    /// Stop searching after exploring this many boards
+    pub nodes: Option<usize>,
+
+    /// Stop searching after this much time has elapsed
+    pub time: Option<Duration>,
+}
+
+impl SearchBudget {
+    /// A budget which stops as soon as the first tiling is found
+    pub fn first_solution() -> Self {
+        SearchBudget::solutions(1)
+    }
+
+    /// A budget which stops once the given number of tilings have been found
+    pub fn solutions(solutions: usize) -> Self {
+        SearchBudget {
+            solutions: solutions.max(1),
+            nodes: None,
+            time: None,
+        }
+    }
+
+    pub fn with_node_limit(mut self, nodes: usize) -> Self {
+        self.nodes = Some(nodes);
+        self
+    }
+
+    /// Stops the search once this much time has passed.  Searches without a time limit never
+    /// read the clock, so they also run in the browser, which doesn't have one.
+    pub fn with_time_limit(mut self, time: Duration) -> Self {
+        self.time = Some(time);
+        self
+    }
+}
+
+impl Default for SearchBudget {
+    fn default() -> Self {
+        SearchBudget::first_solution()
+    }
+}
+
+/// An estimate of the number of tilings of a board, made by `Tiler::estimate_tilings`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TilingEstimate {
+    // the natural logarithms of the estimate and its standard error, as either can be far
+    // too large for an f64
+    ln_estimate: f64,
+    ln_standard_error: f64,
+    samples: usize,
+    completed: usize,
+}
+
+impl TilingEstimate {
+    /// Combines the logarithms of the weights of each sample into an estimate
+    fn from_ln_weights(ln_weights: &[f64]) -> Self {
+        let n = ln_weights.len() as f64;
+        let ln_estimate = ln_mean_exp(ln_weights);
+        let squares: Vec<f64> = ln_weights.iter().map(|w| 2.0 * w).collect();
+        let ln_second_moment = ln_mean_exp(&squares);
+
+        // the sample variance is n / (n - 1) times E[w^2] - E[w]^2, and the standard error
+        // of the mean is the square root of the variance over n
+        let ln_standard_error = if ln_weights.len() < 2 {
+            f64::INFINITY
+        } else if ln_second_moment == f64::NEG_INFINITY {
+            f64::NEG_INFINITY
+        } else {
+            let spread = (-(2.0 * ln_estimate - ln_second_moment).exp()).ln_1p();
+            0.5 * (ln_second_moment + spread - (n - 1.0).ln())
+        };
+
+        TilingEstimate {
+            ln_estimate,
+            ln_standard_error,
+            samples: ln_weights.len(),
+            completed: ln_weights.iter().filter(|w| w.is_finite()).count(),
+        }
+    }
+
+    /// The estimated number of tilings (infinite if it's too large for an f64, see
+    /// `log10_estimate`)
+    pub fn estimate(&self) -> f64 {
+        self.ln_estimate.exp()
+    }
+
+    /// The standard error of the estimate
+    pub fn standard_error(&self) -> f64 {
+        self.ln_standard_error.exp()
+    }
+
+    /// The natural logarithm of the estimate, which is negative infinity if no sample
+    /// completed a tiling
+    pub fn ln_estimate(&self) -> f64 {
+        self.ln_estimate
+    }
+
+    /// The base 10 logarithm of the estimate, which is negative infinity if no sample
+    /// completed a tiling
+    pub fn log10_estimate(&self) -> f64 {
+        self.ln_estimate / std::f64::consts::LN_10
+    }
+
+    /// The lower end of an approximate 95% confidence interval for the number of tilings,
+    /// two standard errors below the estimate.  The weights of the samples vary wildly, so
+    /// unless many samples complete a tiling the interval is often too narrow.
+    pub fn lower_bound(&self) -> f64 {
+        (self.estimate() - 2.0 * self.standard_error()).max(0.0)
+    }
+
+    /// The upper end of an approximate 95% confidence interval for the number of tilings,
+    /// see `lower_bound`
+    pub fn upper_bound(&self) -> f64 {
+        self.estimate() + 2.0 * self.standard_error()
+    }
+
+    /// The number of samples the estimate was made from
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// The number of samples which completed a tiling, rather than getting stuck
+    pub fn completed(&self) -> usize {
+        self.completed
+    }
+}
+
+/// Returns the logarithm of the mean of the exponentials of the given values, without
+/// overflowing
+fn ln_mean_exp(values: &[f64]) -> f64 {
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+
+    max + values.iter().map(|v| (v - max).exp()).sum::<f64>().ln() - (values.len() as f64).ln()
+}
+
+impl fmt::Display for TilingEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.estimate().is_finite() && self.standard_error().is_finite() {
+            write!(f, "{:.4e} ± {:.2e}", self.estimate(), self.standard_error())
+        } else {
+            let ln_10 = std::f64::consts::LN_10;
+            write!(
+                f,
+                "10^{:.4} ± 10^{:.2}",
+                self.ln_estimate / ln_10,
+                self.ln_standard_error / ln_10
+            )
+        }
+    }
+}
+
+/// Statistics on the boards explored by a count or the generation of a tiling graph, see
+/// `Tiler::with_stats`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    frontier_sizes: Vec<usize>,
+    states: usize,
+    phases: Vec<(&'static str, Duration)>,
+    peak_memory: usize,
+}
+
+impl SolveStats {
+    /// The number of boards left to process after each layer of placements
+    pub fn frontier_sizes(&self) -> &[usize] {
+        &self.frontier_sizes
+    }
+
+    /// The total number of distinct boards reached, adding up the boards reached by each
+    /// layer of placements.  Boards which are merged with a symmetric board are only
+    /// counted once.
+    pub fn states(&self) -> usize {
+        self.states
+    }
+
+    /// The name and wall time of each phase, such as the breadth first search of a count
+    /// followed by spilling its frontier to disk
+    pub fn phases(&self) -> &[(&'static str, Duration)] {
+        &self.phases
+    }
+
+    /// The total wall time of all of the phases
+    pub fn elapsed(&self) -> Duration {
+        self.phases.iter().map(|(_, time)| *time).sum()
+    }
+
+    /// An estimate of the most memory, in bytes, used to hold boards at any one time
+    pub fn peak_memory(&self) -> usize {
+        self.peak_memory
+    }
+}
+
+impl fmt::Display for SolveStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "layers: {}", self.frontier_sizes.len())?;
+        writeln!(
+            f,
+            "largest frontier: {} boards",
+            self.frontier_sizes.iter().max().unwrap_or(&0)
+        )?;
+        writeln!(f, "distinct boards explored: {}", self.states)?;
+        writeln!(f, "estimated peak memory: {} bytes", self.peak_memory)?;
+        for (name, time) in &self.phases {
+            writeln!(f, "{}: {:.3}s", name, time.as_secs_f64())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Collects the `SolveStats` of a count as it goes
+#[derive(Default)]
+struct StatsRecorder {
+    stats: SolveStats,
+    started: bool,
+    phase: Option<(&'static str, Instant)>,
+}
+
+impl StatsRecorder {
+    fn finish_phase(&mut self) {
+        if let Some((name, start)) = self.phase.take() {
+            self.stats.phases.push((name, start.elapsed()));
+        }
+    }
+
+    fn record_memory(&mut self, memory: usize) {
+        self.stats.peak_memory = self.stats.peak_memory.max(memory);
+    }
+}
+
+/// The order in which `Tiler::get_single_tiling` tries the ways of placing tiles.  Each
+/// strategy places its next tile at the most constrained cell of the board, and they differ
+/// in the order they try the tiles covering it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum SearchStrategy {
+    /// A depth first search, trying the placements in the order `place_tile` makes them
+    #[default]
+    Dfs,
+
+    /// Iterative deepening on the number of placements tried at each cell: depth first
+    /// searches trying only the first placement at each cell, then the first two, then the
+    /// first four and so on, until a search has tried every placement.  Tilings which mostly
+    /// make the first choice at each cell are found without wandering through the rest of
+    /// the search.
+    Iddfs,
+
+    /// A depth first search trying the placements which leave the least freedom (see
+    /// `RectangularBoard::freedom`) first.  The more constrained a board, the sooner the
+    /// search finds out whether it can be finished, so less time is spent exploring boards
+    /// which can't.  Ties are broken in favour of the placement leaving the most
+    /// constrained cell.
+    LeastFreedom,
+}
+
+/// Which boards `Tiler` counts as finished tilings, see `Tiler::with_target`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Target {
+    /// Tilings covering every cell of the board
+    #[default]
+    CoverAll,
+
+    /// Tilings covering at least this many cells, leaving the rest uncovered.  For boards
+    /// which can't be tiled, this asks how close a tiling can get.
+    CoverAtLeast(usize),
+
+    /// Tilings covering exactly the cells of a region of the board, given as rows of cells
+    /// where true marks the cells of the region.  The cells outside of the region (including
+    /// any beyond the ends of the rows given) are treated as holes in the board.
+    CoverRegion(Vec<Vec<bool>>),
+}
+
+/// Chooses which tilings `Tiler::render_all_tilings_sampled` renders, where the tilings are
+/// numbered from 0 in the order given by `BoardGraph::nth_path`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SampleStrategy {
+    /// The first `n` tilings
+    First(usize),
+
+    /// `n` distinct tilings chosen uniformly at random
+    RandomUniform(usize),
+
+    /// Every `k`th tiling, starting with the first
+    EveryKth(usize),
+}
+
+/// The unmarked cells of a board, up to translation.  Regions with the same shape have the
+/// same number of tilings.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "fs", derive(Serialize, Deserialize))]
+struct RegionShape {
+    // translating a region of the triangular lattice by an odd number of cells turns its
+    // triangles upside down, so we remember which way its first triangle points
+    parity: usize,
+
+    // the width of the bounding box of the region, and a bit for each cell of the bounding
+    // box, read row by row, set if the cell is part of the region
+    width: usize,
+    cells: Vec<u64>,
+}
+
+impl RegionShape {
+    fn new(board: &RectangularBoard) -> Self {
+        let unmarked = |i: usize, j: usize| !board.board[i][j];
+
+        let rows: Vec<usize> = (0..board.height)
+            .filter(|&i| (0..board.width).any(|j| unmarked(i, j)))
+            .collect();
+        let cols: Vec<usize> = (0..board.width)
+            .filter(|&j| (0..board.height).any(|i| unmarked(i, j)))
+            .collect();
+
+        let (top, bottom) = (rows[0], rows[rows.len() - 1]);
+        let (left, right) = (cols[0], cols[cols.len() - 1]);
+        let width = right - left + 1;
+
+        let mut cells = vec![0u64; ((bottom - top + 1) * width).div_ceil(64)];
+        for i in top..=bottom {
+            for j in left..=right {
+                if unmarked(i, j) {
+                    let index = (i - top) * width + (j - left);
+                    cells[index / 64] |= 1 << (index % 64);
+                }
+            }
+        }
+
+        RegionShape {
+            parity: match board.lattice() {
+                Lattice::Square => 0,
+                Lattice::Triangular => (top + left) % 2,
+            },
+            width,
+            cells,
+        }
+    }
+}
+
+/// Adds `count` ways of reaching `board` to the count of its orbit in `orbits`, which maps
+/// the key of each orbit to the key of the board representing it and the count of the orbit.
+///
+/// Orbits are represented by the least key of the boards reached in them, rather than the
+/// key of their canonical form, so that the boards in the frontier are all reached by the
+/// search itself.  Placing tiles on the canonical forms instead covers different cells
+/// first in boards which are reflections of one another, and mixing them makes the
+/// frontier several times larger.
+fn merge_into_orbit(
+    orbits: &mut HashMap<BoardKey, (BoardKey, Count)>,
+    orbit: BoardKey,
+    board: BoardKey,
+    count: &Count,
+    arith: Arith,
+) {
+    let (representative, total) = orbits
+        .entry(orbit)
+        .or_insert_with(|| (board.clone(), Count::zero(arith)));
+
+    if board < *representative {
+        *representative = board;
+    }
+    *total += count;
+}
+
+/// Returns the image of a tile under the given symmetry
+fn transform_tile(tile: &CellTile, symmetry: Symmetry, board: &RectangularBoard) -> CellTile {
+    let height = tile.cells().iter().map(|(r, _)| r + 1).max().unwrap() as usize;
+    let width = tile.cells().iter().map(|(_, c)| c + 1).max().unwrap() as usize;
+
+    // transforming within the tile's bounding box rather than the board moves the image by
+    // a translation, which on the triangular lattice must not change the way triangles point
+    let (board_row, board_col) = symmetry.apply((0, 0), board.width, board.height);
+    let (tile_row, tile_col) = symmetry.apply((0, 0), width, height);
+    let shift = ((board_row + board_col + tile_row + tile_col) % 2) as i32;
+
+    CellTile::with_lattice(
+        tile.cells().iter().map(|&(r, c)| {
+            let (r, c) = symmetry.apply((r as usize, c as usize), width, height);
+            (r as i32, c as i32 + shift)
+        }),
+        tile.lattice(),
+    )
+}
+
+/// The children found by one thread while generating a layer of the tiling graph, each
+/// with the position it was first found at and its parents
+type GraphShard<B> = HashMap<B, ((usize, usize), Vec<usize>)>;
+
+/// Merges the smaller shard `right` into `left`
+fn merge_graph_shards<B: Board>(mut left: GraphShard<B>, right: GraphShard<B>) -> GraphShard<B> {
+    for (board, (first, parents)) in right {
+        match left.entry(board) {
+            Entry::Occupied(mut entry) => {
+                let (left_first, left_parents) = entry.get_mut();
+                *left_first = (*left_first).min(first);
+                left_parents.extend(parents);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert((first, parents));
+            }
+        }
+    }
+
+    left
+}
+
+/// The number of boards expanded at once when counting from a spilled frontier
+#[cfg(feature = "fs")]
+const SPILL_CHUNK_SIZE: usize = 1 << 12;
+
+/// A file of (board, count) records written by `count_completions_spilled`, in which the
+/// same board may appear more than once.  The file is deleted when the bucket is dropped.
+#[cfg(feature = "fs")]
+struct SpillBucket {
+    file: NamedTempFile,
+    salt: u64,
+    records: usize,
+    bytes: u64,
+}
+
+#[cfg(feature = "fs")]
+impl SpillBucket {
+    /// Reads every record in this bucket, merging the counts of repeated boards
+    fn load(self) -> Result<HashMap<BoardKey, BigUint>> {
+        let mut boards = HashMap::new();
+
+        self.for_each(|key, count| {
+            *boards.entry(key).or_insert_with(BigUint::zero) += count;
+            Ok(())
+        })?;
+
+        Ok(boards)
+    }
+
+    /// Splits this bucket into buckets of roughly `bucket_memory` bytes
+    fn split(self, directory: &Path, bucket_memory: usize) -> Result<Vec<SpillBucket>> {
+        let count = (self.bytes / bucket_memory as u64) as usize + 2;
+        let mut buckets = SpillBuckets::create(directory, self.salt + 1, count)?;
+
+        self.for_each(|key, count| buckets.push(&key, &count))?;
+
+        buckets.finish()
+    }
+
+    fn for_each<F>(&self, mut f: F) -> Result<()>
+    where
+        F: FnMut(BoardKey, BigUint) -> Result<()>,
+    {
+        let mut reader = BufReader::new(self.file.reopen()?);
+
+        for _ in 0..self.records {
+            let (key, count) = bincode::deserialize_from(&mut reader)
+                .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            f(key, count)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A set of bucket files being written, with each board sent to the bucket chosen by its
+/// hash
+#[cfg(feature = "fs")]
+struct SpillBuckets {
+    buckets: Vec<(SpillBucket, BufWriter<File>)>,
+}
+
+#[cfg(feature = "fs")]
+impl SpillBuckets {
+    /// Creates `count` empty bucket files in the given directory.  Boards are assigned to
+    /// buckets by hashing them along with `salt`, so that buckets created with different
+    /// salts split boards differently.
+    fn create(directory: &Path, salt: u64, count: usize) -> Result<Self> {
+        let buckets = (0..count)
+            .map(|_| {
+                let file = NamedTempFile::new_in(directory)?;
+                let writer = BufWriter::new(file.reopen()?);
+                let bucket = SpillBucket {
+                    file,
+                    salt,
+                    records: 0,
+                    bytes: 0,
+                };
+
+                Ok((bucket, writer))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(SpillBuckets { buckets })
+    }
+
+    fn push(&mut self, key: &BoardKey, count: &BigUint) -> Result<()> {
+        let mut hasher = DefaultHasher::new();
+        (self.buckets[0].0.salt, key).hash(&mut hasher);
+        let index = (hasher.finish() % self.buckets.len() as u64) as usize;
+
+        let (bucket, writer) = &mut self.buckets[index];
+        let record =
+            bincode::serialize(&(key, count)).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        writer.write_all(&record)?;
+
+        bucket.records += 1;
+        bucket.bytes += record.len() as u64;
+        Ok(())
+    }
+
+    /// Flushes every bucket file, and returns the non-empty buckets
+    fn finish(self) -> Result<Vec<SpillBucket>> {
+        let mut buckets = Vec::new();
+
+        for (bucket, mut writer) in self.buckets {
+            writer.flush()?;
+
+            if bucket.records > 0 {
+                buckets.push(bucket);
+            }
+        }
+
+        Ok(buckets)
+    }
+}
+
+/// Renders and removes every tiling in the batch in parallel, returning false if `emit` asked
+/// to stop
+#[cfg(feature = "fs")]
+fn emit_batch(batch: &mut Vec<Tiling>, emit: &(dyn Fn(Tiling) -> bool + Sync)) -> bool {
+    std::mem::take(batch).into_par_iter().all(emit)
+}
@@ -0,0 +1,343 @@
+use crate::board::{Board, RectangularBoard, Symmetry};
+use crate::tile::CellTile;
+use std::fmt;
+
+/// A single tile placed on a board, along with the (row, column) cells it covers
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TilePlacement {
+    tile: CellTile,
+    cells: Vec<(usize, usize)>,
+}
+
+impl TilePlacement {
+    /// Creates a placement covering the given (row, column) cells
+    ///
+    /// # Panics
+    ///
+    /// Will panic if cells is empty
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::tile::{CellTile, Tile};
+    /// use dcc_tiler::tiling::TilePlacement;
+    ///
+    /// let placement = TilePlacement::new(vec![(3, 1), (3, 2)]);
+    /// assert_eq!(placement.tile(), &CellTile::from(Tile::domino()));
+    /// ```
+    pub fn new(cells: Vec<(usize, usize)>) -> Self {
+        let tile = CellTile::new(cells.iter().map(|&(i, j)| (i as i32, j as i32)));
+
+        TilePlacement { tile, cells }
+    }
+
+    /// Returns the shape of the placed tile
+    pub fn tile(&self) -> &CellTile {
+        &self.tile
+    }
+
+    /// Returns the (row, column) cells covered by the placed tile
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells
+    }
+}
+
+/// A straight line along the edges of the cells of a board
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FaultLine {
+    /// The line between row `k - 1` and row `k`
+    Horizontal(usize),
+
+    /// The line between column `k - 1` and column `k`
+    Vertical(usize),
+}
+
+/// A tiling of a board, stored as the initial board together with the tiles
+/// placed on it, in the order they were placed.  Tilings found for a target other than
+/// covering the whole board (see `Tiler::with_target`) may leave cells uncovered.
+#[derive(Clone, Debug)]
+pub struct Tiling {
+    initial: RectangularBoard,
+    placements: Vec<TilePlacement>,
+}
+
+impl Tiling {
+    /// Creates a tiling from a sequence of boards, where each board is obtained from
+    /// the previous one by placing down a single tile, or by leaving a single cell uncovered
+    /// (see `RectangularBoard::leave_uncovered`).
+    ///
+    /// # Panics
+    ///
+    /// Will panic if boards is empty
+    pub fn new<B: Board>(boards: Vec<B>) -> Self {
+        assert!(!boards.is_empty());
+
+        let placements = boards
+            .windows(2)
+            .filter(|pair| pair[0].grid().uncovered_count() == pair[1].grid().uncovered_count())
+            .map(|pair| TilePlacement::new(pair[0].grid().diff(pair[1].grid())))
+            .collect();
+
+        Tiling {
+            initial: boards[0].grid().clone(),
+            placements,
+        }
+    }
+
+    /// Creates a tiling of `initial` from the tiles placed on it
+    pub fn from_placements(initial: RectangularBoard, placements: Vec<TilePlacement>) -> Self {
+        Tiling {
+            initial,
+            placements,
+        }
+    }
+
+    /// Returns the board that was tiled
+    pub fn initial_board(&self) -> &RectangularBoard {
+        &self.initial
+    }
+
+    /// Returns the tiles placed on the board, in the order they were placed
+    pub fn placements(&self) -> &[TilePlacement] {
+        &self.placements
+    }
+
+    /// Returns every (row, column) cell covered by a placed tile
+    pub fn covered_cells(&self) -> Vec<(usize, usize)> {
+        self.placements
+            .iter()
+            .flat_map(|placement| placement.cells().iter().copied())
+            .collect()
+    }
+
+    /// Returns the board with every placed tile marked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tiling::{TilePlacement, Tiling};
+    ///
+    /// let board = RectangularBoard::new(2, 1);
+    /// let tiling = Tiling::from_placements(board, vec![TilePlacement::new(vec![(0, 0), (0, 1)])]);
+    ///
+    /// assert!(tiling.to_board().is_all_marked());
+    /// ```
+    pub fn to_board(&self) -> RectangularBoard {
+        self.initial.mark_cells(&self.covered_cells())
+    }
+
+    /// Returns the sequence of boards obtained by placing down one tile at a time,
+    /// starting from the initial board
+    pub fn boards(&self) -> Vec<RectangularBoard> {
+        let mut boards = vec![self.initial.clone()];
+
+        for placement in &self.placements {
+            let board = boards.last().unwrap().mark_cells(placement.cells());
+            boards.push(board);
+        }
+
+        boards
+    }
+
+    /// Returns the image of this tiling under the given symmetry, with tiles placed in the
+    /// same order
+    pub fn transformed(&self, symmetry: Symmetry) -> Tiling {
+        let (width, height) = (self.initial.width, self.initial.height);
+
+        Tiling {
+            initial: self.initial.transformed(symmetry),
+            placements: self
+                .placements
+                .iter()
+                .map(|placement| {
+                    TilePlacement::new(
+                        placement
+                            .cells()
+                            .iter()
+                            .map(|cell| symmetry.apply(*cell, width, height))
+                            .collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the cells covered by each tile of this tiling, with the cells of each tile
+    /// sorted and the tiles themselves sorted - so that the same tiling always has the
+    /// same normal form, regardless of the order its tiles were placed in.
+    pub fn normal_form(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut form: Vec<Vec<(usize, usize)>> = self
+            .placements
+            .iter()
+            .map(|placement| {
+                let mut cells = placement.cells().to_vec();
+                cells.sort_unstable();
+                cells
+            })
+            .collect();
+        form.sort_unstable();
+
+        form
+    }
+
+    /// Returns the smallest normal form of the images of this tiling under the given
+    /// symmetries, which is the same for all tilings which are images of one another.
+    pub fn canonical_form(&self, symmetries: &[Symmetry]) -> Vec<Vec<(usize, usize)>> {
+        symmetries
+            .iter()
+            .map(|symmetry| self.transformed(*symmetry).normal_form())
+            .chain(std::iter::once(self.normal_form()))
+            .min()
+            .unwrap()
+    }
+
+    /// Determines whether this tiling has the smallest normal form amongst its images under
+    /// the given symmetries.  Exactly one tiling from each family of tilings which are
+    /// images of one another is canonical.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::{RectangularBoard, Symmetry};
+    /// use dcc_tiler::tiling::{TilePlacement, Tiling};
+    ///
+    /// let board = RectangularBoard::new(2, 2);
+    /// let horizontal = Tiling::from_placements(
+    ///     board.clone(),
+    ///     vec![TilePlacement::new(vec![(0, 0), (0, 1)]), TilePlacement::new(vec![(1, 0), (1, 1)])],
+    /// );
+    /// let vertical = horizontal.transformed(Symmetry::Rotate90);
+    ///
+    /// let symmetries = board.symmetries();
+    /// assert_ne!(horizontal.is_canonical(&symmetries), vertical.is_canonical(&symmetries));
+    /// ```
+    pub fn is_canonical(&self, symmetries: &[Symmetry]) -> bool {
+        self.normal_form() == self.canonical_form(symmetries)
+    }
+
+    /// Returns the fault lines of this tiling - the lines between two rows or two columns of
+    /// the board which have tiles on both sides, but which no tile crosses.  A tile crosses a
+    /// line if it covers two cells which meet along it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tiling::{FaultLine, TilePlacement, Tiling};
+    ///
+    /// // two horizontal dominoes, one above the other
+    /// let tiling = Tiling::from_placements(
+    ///     RectangularBoard::new(2, 2),
+    ///     vec![TilePlacement::new(vec![(0, 0), (0, 1)]), TilePlacement::new(vec![(1, 0), (1, 1)])],
+    /// );
+    ///
+    /// assert_eq!(tiling.fault_lines(), vec![FaultLine::Horizontal(1)]);
+    /// assert!(!tiling.is_fault_free());
+    /// ```
+    pub fn fault_lines(&self) -> Vec<FaultLine> {
+        let grid = self.to_grid();
+        let (width, height) = (self.initial.width, self.initial.height);
+
+        // whether the cells on either side of a line are covered by the same tile
+        let joined = |a: (usize, usize), b: (usize, usize)| {
+            grid[a.0][a.1] != 0 && grid[a.0][a.1] == grid[b.0][b.1]
+        };
+        let covered_rows: Vec<bool> = grid
+            .iter()
+            .map(|row| row.iter().any(|&id| id != 0))
+            .collect();
+        let covered_cols: Vec<bool> = (0..width)
+            .map(|j| grid.iter().any(|row| row[j] != 0))
+            .collect();
+
+        let horizontal = (1..height)
+            .filter(|&k| {
+                covered_rows[..k].contains(&true)
+                    && covered_rows[k..].contains(&true)
+                    && (0..width).all(|j| !joined((k - 1, j), (k, j)))
+            })
+            .map(FaultLine::Horizontal);
+        let vertical = (1..width)
+            .filter(|&k| {
+                covered_cols[..k].contains(&true)
+                    && covered_cols[k..].contains(&true)
+                    && (0..height).all(|i| !joined((i, k - 1), (i, k)))
+            })
+            .map(FaultLine::Vertical);
+
+        horizontal.chain(vertical).collect()
+    }
+
+    /// Determines whether this tiling has no fault lines
+    pub fn is_fault_free(&self) -> bool {
+        self.fault_lines().is_empty()
+    }
+
+    /// Returns a matrix with an entry for each cell of the board, holding the ID of the
+    /// tile covering that cell.  Tiles are numbered from 1 in the order they were placed,
+    /// and cells which were already marked on the initial board have ID 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiling::Tiling;
+    ///
+    /// let board = RectangularBoard::new(2, 1);
+    /// let tiles = TileCollection::from(Tile::box_tile());
+    ///
+    /// let first = board.place_tile(&tiles).remove(0);
+    /// let second = first.place_tile(&tiles).remove(0);
+    /// let tiling = Tiling::new(vec![board, first, second]);
+    ///
+    /// let grid = tiling.to_grid();
+    /// assert_eq!(grid.len(), 1);
+    /// assert_eq!(grid[0].iter().sum::<u32>(), 3);
+    /// ```
+    pub fn to_grid(&self) -> Vec<Vec<u32>> {
+        let mut grid = vec![vec![0; self.initial.width]; self.initial.height];
+
+        for (id, placement) in self.placements.iter().enumerate() {
+            for &(i, j) in placement.cells() {
+                grid[i][j] = id as u32 + 1;
+            }
+        }
+
+        grid
+    }
+
+    /// Returns a plain text representation of `to_grid`, with one line per row of the board
+    /// and the tile IDs in each row separated by spaces.
+    pub fn to_text(&self) -> String {
+        let grid = self.to_grid();
+        let width = grid
+            .iter()
+            .flatten()
+            .max()
+            .map_or(1, |id| id.to_string().len());
+
+        grid.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|id| format!("{:>width$}", id, width = width))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl From<Vec<RectangularBoard>> for Tiling {
+    fn from(boards: Vec<RectangularBoard>) -> Self {
+        Tiling::new(boards)
+    }
+}
+
+impl fmt::Display for Tiling {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_text())
+    }
+}
@@ -0,0 +1,231 @@
+//! Weights of tiles, for computing partition functions.
+//!
+//! A weight is a polynomial in a single variable `x` with rational coefficients, which covers
+//! plain rational weights (constant polynomials), floating point weights (which are converted
+//! to rationals exactly) and generating functions in one variable - such as the monomer-dimer
+//! polynomial, where each monomer has weight `x`.
+use num::{BigInt, BigRational, BigUint, One, Signed, ToPrimitive, Zero};
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul};
+
+/// A polynomial in `x` with rational coefficients
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::weight::Weight;
+///
+/// let w = Weight::x() + Weight::from(2u32);
+/// assert_eq!((&w * &w).to_string(), "x^2 + 4x + 4");
+/// assert_eq!(Weight::from_f64(0.5).unwrap().to_string(), "1/2");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Weight {
+    // the coefficient of x^i is coefficients[i], with no trailing zeros
+    coefficients: Vec<BigRational>,
+}
+
+impl Weight {
+    /// Returns the weight `x`
+    pub fn x() -> Self {
+        Weight::monomial(BigRational::one(), 1)
+    }
+
+    /// Returns the weight `coefficient * x^degree`
+    pub fn monomial(coefficient: BigRational, degree: usize) -> Self {
+        let mut coefficients = vec![BigRational::zero(); degree + 1];
+        coefficients[degree] = coefficient;
+
+        Weight::from_coefficients(coefficients)
+    }
+
+    /// Creates a weight from its coefficients, starting with the constant term
+    pub fn from_coefficients(mut coefficients: Vec<BigRational>) -> Self {
+        while coefficients.last().is_some_and(|c| c.is_zero()) {
+            coefficients.pop();
+        }
+
+        Weight { coefficients }
+    }
+
+    /// Creates a constant weight with exactly the value of the given float, or None if it
+    /// is infinite or NaN
+    pub fn from_f64(value: f64) -> Option<Self> {
+        BigRational::from_float(value).map(Weight::from)
+    }
+
+    /// Returns the coefficients of this weight, starting with the constant term.  The zero
+    /// weight has no coefficients.
+    pub fn coefficients(&self) -> &[BigRational] {
+        &self.coefficients
+    }
+
+    /// Returns the degree of this weight, or None for the zero weight
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    /// Returns the value of this weight at the given `x`
+    pub fn evaluate(&self, x: &BigRational) -> BigRational {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(BigRational::zero(), |acc, c| acc * x + c)
+    }
+
+    /// Returns the value of this weight at the given `x` as a float, or None if it is too
+    /// large to be represented
+    pub fn evaluate_f64(&self, x: f64) -> Option<f64> {
+        let x = BigRational::from_float(x)?;
+
+        self.evaluate(&x).to_f64()
+    }
+}
+
+impl Zero for Weight {
+    fn zero() -> Self {
+        Weight {
+            coefficients: Vec::new(),
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+}
+
+impl One for Weight {
+    fn one() -> Self {
+        Weight::from(BigRational::one())
+    }
+}
+
+impl From<BigRational> for Weight {
+    fn from(value: BigRational) -> Self {
+        Weight::from_coefficients(vec![value])
+    }
+}
+
+impl From<BigInt> for Weight {
+    fn from(value: BigInt) -> Self {
+        Weight::from(BigRational::from_integer(value))
+    }
+}
+
+impl From<BigUint> for Weight {
+    fn from(value: BigUint) -> Self {
+        Weight::from(BigInt::from(value))
+    }
+}
+
+impl From<u32> for Weight {
+    fn from(value: u32) -> Self {
+        Weight::from(BigInt::from(value))
+    }
+}
+
+impl Add for &Weight {
+    type Output = Weight;
+
+    fn add(self, other: &Weight) -> Weight {
+        let len = self.coefficients.len().max(other.coefficients.len());
+        let zero = BigRational::zero();
+
+        Weight::from_coefficients(
+            (0..len)
+                .map(|i| {
+                    self.coefficients.get(i).unwrap_or(&zero)
+                        + other.coefficients.get(i).unwrap_or(&zero)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Add for Weight {
+    type Output = Weight;
+
+    fn add(self, other: Weight) -> Weight {
+        &self + &other
+    }
+}
+
+impl AddAssign for Weight {
+    fn add_assign(&mut self, other: Weight) {
+        *self = &*self + &other;
+    }
+}
+
+impl Mul for &Weight {
+    type Output = Weight;
+
+    fn mul(self, other: &Weight) -> Weight {
+        if self.is_zero() || other.is_zero() {
+            return Weight::zero();
+        }
+
+        let mut coefficients =
+            vec![BigRational::zero(); self.coefficients.len() + other.coefficients.len() - 1];
+
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in other.coefficients.iter().enumerate() {
+                coefficients[i + j] += a * b;
+            }
+        }
+
+        Weight::from_coefficients(coefficients)
+    }
+}
+
+impl Mul for Weight {
+    type Output = Weight;
+
+    fn mul(self, other: Weight) -> Weight {
+        &self * &other
+    }
+}
+
+impl fmt::Display for Weight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        let mut first = true;
+
+        for (degree, c) in self.coefficients.iter().enumerate().rev() {
+            if c.is_zero() {
+                continue;
+            }
+
+            if first {
+                if c.is_negative() {
+                    write!(f, "-")?;
+                }
+            } else if c.is_negative() {
+                write!(f, " - ")?;
+            } else {
+                write!(f, " + ")?;
+            }
+            first = false;
+
+            // leave out coefficients of 1 on the powers of x, and bracket fractions
+            let c = c.abs();
+            if degree == 0 {
+                write!(f, "{}", c)?;
+            } else if !c.is_integer() {
+                write!(f, "({})", c)?;
+            } else if !c.is_one() {
+                write!(f, "{}", c)?;
+            }
+
+            match degree {
+                0 => {}
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{}", degree)?,
+            }
+        }
+
+        Ok(())
+    }
+}